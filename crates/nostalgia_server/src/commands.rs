@@ -0,0 +1,172 @@
+use std::{fs, io, path::PathBuf};
+
+use tokio::sync::Mutex;
+use world::{PasteOptions, Schematic, World};
+
+const SCHEMATICS_PATH: &str = "assets/schematics";
+/// Largest number of blocks a command may copy or paste, as every pasted block is sent
+/// to all players.
+const MAX_VOLUME: usize = 32768;
+
+const USAGE: &str = "Usage: /schematic save <name> <x1> <y1> <z1> <x2> <y2> <z2>, \
+                     /schematic load <name> [0|90|180|270] [-a]";
+
+/// Admin commands, sent as chat messages starting with `/`.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Command {
+    /// Exports the blocks between two corners to a schematic file.
+    SaveSchematic {
+        name: String,
+        from: (i32, i32, i32),
+        to: (i32, i32, i32),
+    },
+    /// Pastes a schematic file at the feet of the player, `-a` skips its air blocks.
+    LoadSchematic { name: String, options: PasteOptions },
+}
+
+/// What a command did, to tell the player and everyone who can see the blocks.
+pub struct CommandOutcome {
+    pub reply: String,
+    pub changed: Vec<(i32, i32, i32)>,
+}
+
+/// Names are used as file names, so only letters, digits, `-` and `_` are allowed.
+fn schematic_path(name: &str) -> Result<PathBuf, String> {
+    let valid = !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+    if !valid {
+        return Err(format!("Invalid schematic name '{name}'"));
+    }
+
+    Ok(PathBuf::from(SCHEMATICS_PATH).join(format!("{name}.schematic")))
+}
+
+/// Runs file I/O on the blocking thread pool instead of the connection's task.
+async fn blocking<T: Send + 'static>(
+    task: impl FnOnce() -> io::Result<T> + Send + 'static,
+) -> io::Result<T> {
+    tokio::task::spawn_blocking(task)
+        .await
+        .unwrap_or_else(|error| Err(io::Error::other(error)))
+}
+
+fn parse_coordinates(values: &[&str]) -> Result<(i32, i32, i32), String> {
+    let parse = |value: &str| {
+        value
+            .parse()
+            .map_err(|_| format!("Invalid coordinate '{value}'"))
+    };
+
+    match values {
+        [x, y, z] => Ok((parse(x)?, parse(y)?, parse(z)?)),
+        _ => Err(USAGE.to_string()),
+    }
+}
+
+impl Command {
+    /// Parses a chat message without its leading `/`.
+    pub fn parse(line: &str) -> Result<Self, String> {
+        let arguments: Vec<&str> = line.split_whitespace().collect();
+        match arguments.as_slice() {
+            ["schematic", "save", name, coordinates @ ..] if coordinates.len() == 6 => {
+                Ok(Self::SaveSchematic {
+                    name: name.to_string(),
+                    from: parse_coordinates(&coordinates[..3])?,
+                    to: parse_coordinates(&coordinates[3..])?,
+                })
+            }
+            ["schematic", "load", name, flags @ ..] => {
+                let mut options = PasteOptions::default();
+                for &flag in flags {
+                    match flag {
+                        "-a" => options.skip_air = true,
+                        "0" | "90" | "180" | "270" => {
+                            options.rotation = (flag.parse::<u16>().unwrap() / 90) as u8
+                        }
+                        _ => return Err(format!("Unknown option '{flag}'")),
+                    }
+                }
+
+                Ok(Self::LoadSchematic {
+                    name: name.to_string(),
+                    options,
+                })
+            }
+            ["schematic", ..] => Err(USAGE.to_string()),
+            [command, ..] => Err(format!("Unknown command '{command}'")),
+            [] => Err("Missing command".to_string()),
+        }
+    }
+
+    /// Runs the command for a player standing at `position`. The world is only locked
+    /// while blocks are copied or pasted, not while files are read or written.
+    pub async fn execute(
+        &self,
+        world: &Mutex<World>,
+        position: (i32, i32, i32),
+    ) -> Result<CommandOutcome, String> {
+        match self {
+            Self::SaveSchematic { name, from, to } => {
+                let path = schematic_path(name)?;
+                let volume = [(from.0, to.0), (from.1, to.1), (from.2, to.2)]
+                    .iter()
+                    .try_fold(1usize, |volume, (a, b)| {
+                        volume.checked_mul((a.abs_diff(*b) as usize).checked_add(1)?)
+                    });
+                if volume.is_none_or(|volume| volume > MAX_VOLUME) {
+                    return Err(format!("Can't save more than {MAX_VOLUME} blocks"));
+                }
+
+                let schematic = world
+                    .lock()
+                    .await
+                    .export_schematic(*from, *to)
+                    .map_err(|error| format!("Failed to save {name}: {error}"))?;
+                let bytes = schematic
+                    .to_bytes()
+                    .map_err(|error| format!("Failed to save {name}: {error}"))?;
+                blocking(move || {
+                    fs::create_dir_all(SCHEMATICS_PATH)?;
+                    fs::write(&path, bytes)
+                })
+                .await
+                .map_err(|error| format!("Failed to save {name}: {error}"))?;
+
+                Ok(CommandOutcome {
+                    reply: format!(
+                        "Saved {name} ({}x{}x{})",
+                        schematic.width, schematic.height, schematic.length
+                    ),
+                    changed: Vec::new(),
+                })
+            }
+            Self::LoadSchematic { name, options } => {
+                let path = schematic_path(name)?;
+                let schematic = blocking(move || fs::read(&path).and_then(Schematic::from_bytes))
+                    .await
+                    .map_err(|error| format!("Failed to load {name}: {error}"))?;
+
+                let volume = [schematic.width, schematic.height, schematic.length]
+                    .iter()
+                    .try_fold(1usize, |volume, &size| volume.checked_mul(size as usize));
+                if volume.is_none_or(|volume| volume > MAX_VOLUME) {
+                    return Err(format!(
+                        "Can't paste more than {MAX_VOLUME} blocks, {name} is {}x{}x{}",
+                        schematic.width, schematic.height, schematic.length
+                    ));
+                }
+
+                let changed = world
+                    .lock()
+                    .await
+                    .paste_schematic(&schematic, position, options);
+                Ok(CommandOutcome {
+                    reply: format!("Pasted {name}, {} blocks changed", changed.len()),
+                    changed,
+                })
+            }
+        }
+    }
+}
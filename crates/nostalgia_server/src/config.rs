@@ -0,0 +1,182 @@
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use world::{
+    invalid_data, FlatGenerator, Generator, LevelData, TerrainGenerator, VoidGenerator, World,
+};
+
+/// Kind of world created when there is no saved world yet.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum LevelType {
+    /// Hills, oceans, caves and trees.
+    #[default]
+    Default,
+    /// The layers from `generator-settings` everywhere.
+    Flat,
+    /// Nothing but a platform to spawn on.
+    Void,
+}
+
+/// Settings read from `server.properties`, with one `key=value` pair per line.
+#[derive(Clone, Debug)]
+pub struct ServerConfig {
+    pub level_name: String,
+    pub level_type: LevelType,
+    /// Seed of new worlds, a random one is used if this is `None`.
+    pub level_seed: Option<i64>,
+    /// Layers of flat worlds, see [`FlatGenerator::from_layers`].
+    pub generator_settings: String,
+    pub game_mode: i32,
+    /// Names of the players allowed to run admin commands.
+    pub ops: Vec<String>,
+}
+
+impl Default for ServerConfig {
+    fn default() -> Self {
+        Self {
+            level_name: "MainWorld".to_string(),
+            level_type: LevelType::Default,
+            level_seed: None,
+            generator_settings: FlatGenerator::DEFAULT_LAYERS.to_string(),
+            game_mode: 0,
+            ops: Vec::new(),
+        }
+    }
+}
+
+/// Seeds which aren't numbers are hashed the same way as on Java, so a seed written as
+/// text gives the same number everywhere.
+fn hash_seed(seed: &str) -> i64 {
+    seed.encode_utf16()
+        .fold(0i32, |hash, c| hash.wrapping_mul(31).wrapping_add(c as i32)) as i64
+}
+
+impl ServerConfig {
+    /// Reads the configuration, falling back to the defaults if the file doesn't exist.
+    pub fn load(path: &Path) -> io::Result<Self> {
+        match fs::read_to_string(path) {
+            Ok(contents) => Self::parse(&contents),
+            Err(error) if error.kind() == io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(error) => Err(error),
+        }
+    }
+
+    pub fn parse(contents: &str) -> io::Result<Self> {
+        let mut config = Self::default();
+        for line in contents.lines().map(str::trim) {
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let Some((key, value)) = line.split_once('=') else {
+                return Err(invalid_data(format!("Expected key=value, got '{line}'")));
+            };
+
+            let value = value.trim();
+            match key.trim() {
+                "level-name" => config.level_name = value.to_string(),
+                "level-type" => {
+                    config.level_type = match value.to_ascii_lowercase().as_str() {
+                        "default" => LevelType::Default,
+                        "flat" => LevelType::Flat,
+                        "void" => LevelType::Void,
+                        _ => return Err(invalid_data(format!("Unknown level type '{value}'"))),
+                    }
+                }
+                "level-seed" => {
+                    config.level_seed = match value {
+                        "" => None,
+                        seed => Some(seed.parse().unwrap_or_else(|_| hash_seed(seed))),
+                    }
+                }
+                "generator-settings" => config.generator_settings = value.to_string(),
+                "gamemode" => {
+                    config.game_mode = value
+                        .parse()
+                        .map_err(|_| invalid_data(format!("Invalid game mode '{value}'")))?
+                }
+                "ops" => {
+                    config.ops = value
+                        .split(',')
+                        .map(str::trim)
+                        .filter(|name| !name.is_empty())
+                        .map(str::to_string)
+                        .collect()
+                }
+                _ => {}
+            }
+        }
+
+        Ok(config)
+    }
+
+    /// Player names are compared ignoring case, like on Java servers.
+    pub fn is_op(&self, username: &str) -> bool {
+        self.ops.iter().any(|op| op.eq_ignore_ascii_case(username))
+    }
+
+    pub fn world_path(&self) -> PathBuf {
+        Path::new("assets").join(&self.level_name)
+    }
+
+    /// Creates a new world of the configured type.
+    pub fn generate_world(&self) -> io::Result<World> {
+        let level = LevelData {
+            name: self.level_name.clone(),
+            seed: self.level_seed.unwrap_or_else(rand::random),
+            game_type: self.game_mode,
+            ..Default::default()
+        };
+
+        let generator: Box<dyn Generator> = match self.level_type {
+            LevelType::Default => Box::new(TerrainGenerator::new(level.seed)),
+            LevelType::Flat => Box::new(FlatGenerator::from_layers(&self.generator_settings)?),
+            LevelType::Void => Box::new(VoidGenerator::new(level.spawn_position())),
+        };
+
+        Ok(World::generate(level, generator.as_ref()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn settings_are_read_from_key_value_lines() {
+        let config = ServerConfig::parse(
+            "# Comment\n\
+             level-name = Flatland\n\
+             level-type=FLAT\n\
+             generator-settings=bedrock,3*stone\n\
+             gamemode=1\n\
+             ops=Alice, bob,\n\
+             unknown-key=ignored\n",
+        )
+        .unwrap();
+
+        assert_eq!(config.level_name, "Flatland");
+        assert_eq!(config.level_type, LevelType::Flat);
+        assert_eq!(config.generator_settings, "bedrock,3*stone");
+        assert_eq!(config.game_mode, 1);
+        assert!(config.is_op("ALICE") && config.is_op("Bob"));
+        assert_eq!(config.level_seed, None);
+    }
+
+    #[test]
+    fn text_seeds_are_hashed_like_java() {
+        let seed = |line: &str| ServerConfig::parse(line).unwrap().level_seed;
+        assert_eq!(seed("level-seed=-1234"), Some(-1234));
+        assert_eq!(seed("level-seed=hello"), Some(99162322));
+        assert_eq!(seed("level-seed="), None);
+    }
+
+    #[test]
+    fn invalid_settings_are_rejected() {
+        assert!(ServerConfig::parse("level-type=amplified").is_err());
+        assert!(ServerConfig::parse("gamemode=creative").is_err());
+        assert!(ServerConfig::parse("level-name").is_err());
+    }
+}
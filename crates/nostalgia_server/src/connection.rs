@@ -1,19 +1,51 @@
 use entity::entity_flags;
 use network::{peer::Peer, reliability::Reliability, NetworkError};
 use protocol::{
-    interop::{EntityData, SyncedEntityData},
+    interop::{EntityData, RequestedChunk, SyncedEntityData},
     Packet, *,
 };
 use std::{io::Cursor, num::NonZeroU32, sync::Arc, time::Duration};
 use tokio::sync::{mpsc::Sender, Mutex};
 use types::Vector3;
-use world::World;
+use world::{Block, BlockID, World, BLOCK_REGISTRY};
+
+use crate::{commands::Command, config::ServerConfig};
 
 const MINECRAFT_TICKRATE: u64 = 100;
 const MINECRAFT_TICKRATE_MS: f64 = 1000.0 / MINECRAFT_TICKRATE as f64;
 
+const CREATIVE_GAME_TYPE: i32 = 1;
+const MAX_REACH_DISTANCE: f32 = 8.0;
+
+/// Where players appear, standing on the ground near the world's spawn.
+fn spawn_position(world: &World) -> Vector3 {
+    let (x, y, z) = world
+        .find_safe_spawn()
+        .unwrap_or_else(|| world.level.spawn_position());
+
+    Vector3 {
+        x: x as f32 + 0.5,
+        y: y as f32 + 1.6,
+        z: z as f32 + 0.5,
+    }
+}
+
+/// Offset of the block adjacent to the clicked face, `None` when an item is used in the air.
+fn face_offset(face: i32) -> Option<(i32, i32, i32)> {
+    match face {
+        0 => Some((0, -1, 0)),
+        1 => Some((0, 1, 0)),
+        2 => Some((0, 0, -1)),
+        3 => Some((0, 0, 1)),
+        4 => Some((-1, 0, 0)),
+        5 => Some((1, 0, 0)),
+        _ => None,
+    }
+}
+
 pub struct Connection {
     peer: Peer,
+    config: Arc<ServerConfig>,
     world: Arc<Mutex<World>>,
     global_packet_sender: Arc<Mutex<Sender<(Option<NonZeroU32>, Packet)>>>,
     client_id: Option<NonZeroU32>,
@@ -28,11 +60,13 @@ pub struct Connection {
 impl Connection {
     pub fn new(
         peer: Peer,
+        config: Arc<ServerConfig>,
         world: Arc<Mutex<World>>,
         global_packet_sender: Arc<Mutex<Sender<(Option<NonZeroU32>, Packet)>>>,
     ) -> Self {
         Self {
             peer,
+            config,
             world,
             global_packet_sender,
             client_id: None,
@@ -61,7 +95,10 @@ impl Connection {
 
         match minecraft_packet {
             Packet::LoginRequest(login_request) => self.handle_login_request(login_request).await?,
-            Packet::Message(message) => self.broadcast_packet(false, message.clone()).await?,
+            Packet::Message(message) => match message.message.strip_prefix('/') {
+                Some(command) => self.handle_command(command).await?,
+                None => self.broadcast_packet(false, message.clone()).await?,
+            },
             Packet::MovePlayer(move_player) => {
                 self.position = move_player.pos;
                 self.broadcast_packet(true, move_player.clone()).await?
@@ -74,29 +111,20 @@ impl Connection {
                 })
                 .await?;
             }
-            Packet::UseItem(use_item) => {
-                self.send_packet(Message {
-                    username: "Server".to_string(),
-                    message: format!("Use item: {:?}", use_item),
-                })
-                .await?;
-                self.send_packet(UpdateBlock {
-                    // entity_id: 1,
-                    x: use_item.x - 1,
-                    z: use_item.z,
-                    y: use_item.y as u8,
-                    block: 3,
-                    meta: 0,
-                })
-                .await?;
-                self.send_packet(RemoveBlock {
-                    entity_id: 1,
-                    x: use_item.x + 1,
-                    z: use_item.z,
-                    y: use_item.y as u8,
-                })
-                .await?;
+            Packet::RequestChunk(request_chunk) => self.handle_request_chunk(request_chunk).await?,
+            Packet::UseItem(use_item) => self.handle_use_item(use_item).await?,
+            Packet::PlaceBlock(place_block) => {
+                self.place_block(
+                    place_block.x,
+                    place_block.y as i32,
+                    place_block.z,
+                    place_block.block,
+                    place_block.meta,
+                )
+                .await?
             }
+            Packet::RemoveBlock(remove_block) => self.handle_remove_block(remove_block).await?,
+            Packet::Respawn(_respawn) => self.handle_respawn().await?,
             _ => {
                 println!("Unhandled packet: {:?}", minecraft_packet);
             }
@@ -105,6 +133,199 @@ impl Connection {
         Ok(())
     }
 
+    fn can_reach(&self, x: i32, y: i32, z: i32) -> bool {
+        let dx = x as f32 + 0.5 - self.position.x;
+        let dy = y as f32 + 0.5 - self.position.y;
+        let dz = z as f32 + 0.5 - self.position.z;
+
+        dx * dx + dy * dy + dz * dz <= MAX_REACH_DISTANCE * MAX_REACH_DISTANCE
+    }
+
+    async fn handle_use_item(&mut self, use_item: UseItem) -> network::Result<()> {
+        let Some((dx, dy, dz)) = face_offset(use_item.face) else {
+            return Ok(());
+        };
+
+        // Items that aren't blocks are used, not placed
+        let Ok(block) = u8::try_from(use_item.block) else {
+            return Ok(());
+        };
+
+        if BlockID(block) == BlockID::Air {
+            return Ok(());
+        }
+
+        self.place_block(
+            use_item.x + dx,
+            use_item.y + dy,
+            use_item.z + dz,
+            block,
+            use_item.meta,
+        )
+        .await
+    }
+
+    async fn place_block(
+        &mut self,
+        x: i32,
+        y: i32,
+        z: i32,
+        block: u8,
+        meta: u8,
+    ) -> network::Result<()> {
+        let mut world = self.world.clone().lock_owned().await;
+
+        let Some(current) = world.get_block(x, y, z) else {
+            return Ok(());
+        };
+
+        // Placing air would be breaking the block without going through RemoveBlock
+        let id = BlockID(block);
+        if id == BlockID::Air
+            || !BLOCK_REGISTRY.is_registered(id)
+            || !current.id.is_replaceable()
+            || !self.can_reach(x, y, z)
+        {
+            drop(world);
+            return self.send_block_correction(x, y, z, current).await;
+        }
+
+        let meta = meta & 0x0F;
+        world.set_block(
+            x,
+            y,
+            z,
+            Block::existing(id, current.sky_light, current.block_light, meta),
+        );
+        drop(world);
+
+        self.broadcast_packet(
+            true,
+            UpdateBlock {
+                x,
+                z,
+                y: y as u8,
+                block,
+                meta,
+            },
+        )
+        .await
+    }
+
+    async fn handle_remove_block(&mut self, remove_block: RemoveBlock) -> network::Result<()> {
+        let (x, y, z) = (remove_block.x, remove_block.y as i32, remove_block.z);
+        let mut world = self.world.clone().lock_owned().await;
+
+        let Some(current) = world.get_block(x, y, z) else {
+            return Ok(());
+        };
+
+        let unbreakable =
+            current.id.properties().hardness < 0.0 && world.level.game_type != CREATIVE_GAME_TYPE;
+        if current.id == BlockID::Air || unbreakable || !self.can_reach(x, y, z) {
+            drop(world);
+            return self.send_block_correction(x, y, z, current).await;
+        }
+
+        world.set_block(
+            x,
+            y,
+            z,
+            Block::existing(BlockID::Air, current.sky_light, current.block_light, 0),
+        );
+        drop(world);
+
+        self.broadcast_packet(
+            true,
+            RemoveBlock {
+                entity_id: self.entity_id,
+                x,
+                z,
+                y: y as u8,
+            },
+        )
+        .await
+    }
+
+    async fn reply(&mut self, message: String) -> network::Result<()> {
+        self.send_packet(Message {
+            username: "server".to_string(),
+            message,
+        })
+        .await
+    }
+
+    async fn handle_command(&mut self, line: &str) -> network::Result<()> {
+        let is_op = self
+            .username
+            .as_ref()
+            .is_some_and(|username| self.config.is_op(username));
+        if !is_op {
+            return self
+                .reply("You don't have permission to run commands".to_string())
+                .await;
+        }
+
+        let command = match Command::parse(line) {
+            Ok(command) => command,
+            Err(error) => return self.reply(error).await,
+        };
+
+        // The position is at eye height, commands act on the block the player stands in
+        let feet = (
+            self.position.x.floor() as i32,
+            (self.position.y - 1.6).floor() as i32,
+            self.position.z.floor() as i32,
+        );
+
+        let outcome = match command.execute(&self.world, feet).await {
+            Ok(outcome) => outcome,
+            Err(error) => return self.reply(error).await,
+        };
+
+        // Another change may have landed in between, so send what the blocks hold now
+        let world = self.world.clone().lock_owned().await;
+        let updates: Vec<UpdateBlock> = outcome
+            .changed
+            .iter()
+            .filter_map(|&(x, y, z)| {
+                let block = world.get_block(x, y, z)?;
+                Some(UpdateBlock {
+                    x,
+                    z,
+                    y: y as u8,
+                    block: block.id.0,
+                    meta: block.metadata,
+                })
+            })
+            .collect();
+        drop(world);
+
+        for update in updates {
+            self.broadcast_packet(false, update).await?;
+        }
+
+        self.reply(outcome.reply).await
+    }
+
+    /// Reverts a client side prediction by resending the block the server has.
+    async fn send_block_correction(
+        &mut self,
+        x: i32,
+        y: i32,
+        z: i32,
+        block: Block,
+    ) -> network::Result<()> {
+        self.send_packet(UpdateBlock {
+            x,
+            z,
+            y: y as u8,
+            block: block.id.0,
+            meta: block.metadata,
+        })
+        .await
+    }
+
     async fn spawn_chicken(&mut self, pos: Vector3) -> network::Result<()> {
         let base_chicken_metadata = SyncedEntityData::from(&[
             (1, EntityData::Short(11265)),
@@ -135,17 +356,15 @@ impl Connection {
             return Ok(());
         }
 
+        self.position = spawn_position(&world);
+
         self.send_packet(LoginResponse { status: 0 }).await?;
         self.send_packet(StartGame {
-            world_seed: world.seed as i32,
+            world_seed: world.level.seed as i32,
             generator_version: 0,
-            gamemode: world.game_type,
+            gamemode: world.level.game_type,
             entity_id: 1,
-            position: Vector3 {
-                x: world.spawn_position.0 as f32 + 0.5,
-                y: world.spawn_position.1 as f32 + 1.6,
-                z: world.spawn_position.2 as f32 + 0.5,
-            },
+            position: self.position,
         })
         .await?;
 
@@ -155,14 +374,63 @@ impl Connection {
         Ok(())
     }
 
+    async fn handle_respawn(&mut self) -> network::Result<()> {
+        let world = self.world.clone().lock_owned().await;
+        self.position = spawn_position(&world);
+        drop(world);
+
+        self.broadcast_packet(
+            false,
+            Respawn {
+                entity_id: self.entity_id,
+                pos: self.position,
+            },
+        )
+        .await
+    }
+
+    async fn handle_request_chunk(&mut self, request_chunk: RequestChunk) -> network::Result<()> {
+        let world = self.world.clone().lock_owned().await;
+
+        let Some(chunk) = world.chunk_at(request_chunk.x, request_chunk.z) else {
+            println!(
+                "Requested chunk out of bounds: ({}, {})",
+                request_chunk.x, request_chunk.z
+            );
+            return Ok(());
+        };
+
+        let data = chunk.to_network_bytes();
+        drop(world);
+
+        // Chunk payloads are far larger than the MTU, only ordered packets get fragmented
+        self.send_packet_with_reliability(
+            SendChunkData {
+                x: request_chunk.x,
+                z: request_chunk.z,
+                is_new: 0,
+                chunk: RequestedChunk { data },
+            },
+            Reliability::ReliableOrdered,
+        )
+        .await
+    }
+
     pub async fn send_packet(&mut self, packet: impl Into<Packet>) -> network::Result<()> {
+        self.send_packet_with_reliability(packet, Reliability::Reliable)
+            .await
+    }
+
+    pub async fn send_packet_with_reliability(
+        &mut self,
+        packet: impl Into<Packet>,
+        reliability: Reliability,
+    ) -> network::Result<()> {
         let mut cursor = Cursor::new(Vec::new());
         let packet = packet.into();
         packet.serialize(&mut cursor)?;
 
-        self.peer
-            .send(cursor.get_ref(), Reliability::Reliable)
-            .await?;
+        self.peer.send(cursor.get_ref(), reliability).await?;
 
         Ok(())
     }
@@ -1,14 +1,18 @@
+mod commands;
+mod config;
 #[allow(dead_code)]
 
 mod connection;
 
+use config::ServerConfig;
 use connection::Connection;
 use network::{listener::Listener, protocol::ConnectedPacket, reliability::FrameVec, NetworkError};
 use protocol::Packet;
 use std::{
     num::NonZeroU32,
-    path::PathBuf,
+    path::{Path, PathBuf},
     sync::Arc,
+    time::Duration,
 };
 use tokio::sync::{
     mpsc::{channel, Receiver, Sender},
@@ -16,8 +20,12 @@ use tokio::sync::{
 };
 use world::World;
 
+const CONFIG_PATH: &str = "server.properties";
+const AUTOSAVE_INTERVAL: Duration = Duration::from_secs(5 * 60);
+
 struct Application {
     listener: Listener,
+    config: Arc<ServerConfig>,
     world: Arc<Mutex<World>>,
     connections: Arc<Mutex<Vec<Arc<Mutex<Connection>>>>>,
     global_packet_sender: Arc<Mutex<Sender<(Option<NonZeroU32>, Packet)>>>,
@@ -26,11 +34,12 @@ struct Application {
 }
 
 impl Application {
-    pub fn new(listener: Listener, world: World) -> Self {
+    pub fn new(listener: Listener, config: ServerConfig, world: World) -> Self {
         let (global_packet_sender, global_packet_receiver) = channel(64);
 
         Self {
             listener,
+            config: Arc::new(config),
             world: Arc::new(Mutex::new(world)),
             connections: Arc::new(Mutex::new(Vec::new())),
             global_packet_sender: Arc::new(Mutex::new(global_packet_sender)),
@@ -39,15 +48,31 @@ impl Application {
         }
     }
 
+    pub fn start_autosave(&self, path: PathBuf) {
+        let world = self.world.clone();
+        tokio::spawn(async move {
+            loop {
+                tokio::time::sleep(AUTOSAVE_INTERVAL).await;
+                save_world(&world, path.clone()).await;
+            }
+        });
+    }
+
+    pub async fn save(&self, path: PathBuf) {
+        save_world(&self.world, path).await;
+    }
+
     pub async fn run(&mut self) -> Result<(), NetworkError> {
         loop {
             tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
             let peer = self.listener.accept().await?;
 
+            let config = self.config.clone();
             let world = self.world.clone();
             let global_packet_sender = self.global_packet_sender.clone();
             let connection = Arc::new(Mutex::new(Connection::new(
                 peer,
+                config,
                 world,
                 global_packet_sender,
             )));
@@ -124,8 +149,19 @@ async fn main() {
         .await
         .expect("Failed to start the server");
 
-    let world_path = PathBuf::from("assets/MainWorld");
-    let world = World::from_file(world_path).expect("Failed to load the world");
+    let config =
+        ServerConfig::load(Path::new(CONFIG_PATH)).expect("Failed to read the configuration");
+    let world_path = config.world_path();
+    let world = if world_path.join("level.dat").exists() {
+        let world = World::from_file(world_path.clone()).expect("Failed to load the world");
+        for problem in world.chunk_problems() {
+            println!("{}", problem);
+        }
+
+        world
+    } else {
+        generate_world(&config, &world_path)
+    };
 
     let packet_bytes: [u8; 87] = [
         0x84, 0xd0, 0x04, 0x00, 0x40, 0x00, 0x70, 0x8c, 0x03, 0x00, 0x97, 0x00, 0x00, 0x00, 0x02,
@@ -138,11 +174,48 @@ async fn main() {
 
     dump_wireshark_packets(&packet_bytes);
 
-    let mut application = Application::new(listener, world);
-    match application.run().await {
-        Ok(_) => println!("Server closed"),
-        Err(error) => println!("Server closed ({:#?})", error),
+    let mut application = Application::new(listener, config, world);
+    application.start_autosave(world_path.clone());
+    tokio::select! {
+        result = application.run() => match result {
+            Ok(_) => println!("Server closed"),
+            Err(error) => println!("Server closed ({:#?})", error),
+        },
+        _ = tokio::signal::ctrl_c() => println!("Shutting down"),
     }
+
+    // Changes since the last autosave would be lost otherwise
+    application.save(world_path).await;
+}
+
+async fn save_world(world: &Mutex<World>, path: PathBuf) {
+    // Only serializing needs the world, the files are written without holding it up
+    let files = world.lock().await.to_files();
+    let saved = match files {
+        Ok(files) => tokio::task::spawn_blocking(move || files.write(&path))
+            .await
+            .unwrap_or_else(|error| Err(std::io::Error::other(error))),
+        Err(error) => Err(error),
+    };
+
+    match saved {
+        Ok(_) => println!("World saved"),
+        Err(error) => println!("Failed to save the world ({:#?})", error),
+    }
+}
+
+/// Generates a new world as configured and saves it, so the seed is kept.
+fn generate_world(config: &ServerConfig, path: &Path) -> World {
+    println!("Generating a new {:?} world", config.level_type);
+    let world = config
+        .generate_world()
+        .expect("Failed to generate the world");
+
+    println!("Generated the world with seed {}", world.level.seed);
+    world
+        .save(path.to_path_buf())
+        .expect("Failed to save the new world");
+    world
 }
 
 fn dump_wireshark_packets(packet_bytes: &[u8]) {
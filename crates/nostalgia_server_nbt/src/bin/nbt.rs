@@ -0,0 +1,142 @@
+use std::{error::Error, path::Path, process::ExitCode};
+
+use nbt::{Compound, Header, Nbt, NbtError, NbtPath, Tag, ENTITIES_VERSION, LEVEL_STORAGE_VERSION};
+
+const USAGE: &str = "\
+Usage:
+    nbt dump <file>              Print an NBT file as SNBT
+    nbt apply <file> <edits>     Merge an SNBT compound into the file
+    nbt write <file> <snbt>      Replace the contents of the file with SNBT
+    nbt get <file> <path>        Print the tags at a path, such as Entities[0].Pos
+    nbt set <file> <path> <value>
+                                 Set the tags at a path to an SNBT value
+    nbt remove <file> <path>     Remove the tags at a path
+
+<edits> and <value> are either SNBT or @path to read it from a file. <snbt> is always
+a path. level.dat and entities.dat keep their Pocket Edition headers.";
+
+fn read_file(path: &str) -> Result<(Nbt, Header), Box<dyn Error>> {
+    let bytes = std::fs::read(path).map_err(|error| format!("{}: {}", path, error))?;
+    Ok(Nbt::from_pocket_file(bytes)?)
+}
+
+fn write_file(path: &str, nbt: &Nbt, header: Header) -> Result<(), Box<dyn Error>> {
+    std::fs::write(path, nbt.to_pocket_file(header)?)?;
+    Ok(())
+}
+
+fn read_snbt(argument: &str) -> Result<Tag, Box<dyn Error>> {
+    let snbt = match argument.strip_prefix('@') {
+        Some(path) => std::fs::read_to_string(path)?,
+        None => argument.to_string(),
+    };
+
+    Ok(snbt.parse()?)
+}
+
+fn into_compound(tag: Tag) -> Result<Compound, Box<dyn Error>> {
+    match tag {
+        Tag::Compound(tags) => Ok(tags),
+        _ => Err("SNBT must be a compound".into()),
+    }
+}
+
+/// Merges `edits` into `tags`, descending into compounds present in both and replacing
+/// everything else. Replaced lists keep their element type if the edit leaves them empty.
+fn merge(tags: &mut Compound, edits: Compound) {
+    for (key, edit) in edits {
+        match (tags.get_mut(&key), edit) {
+            (Some(Tag::Compound(tags)), Tag::Compound(edits)) => merge(tags, edits),
+            (original, mut edit) => {
+                if let Some(original) = original {
+                    edit.keep_list_types(original);
+                }
+                tags.insert(key, edit);
+            }
+        }
+    }
+}
+
+/// Header for a file that doesn't exist yet, guessed from its name.
+fn header_for(path: &str) -> Header {
+    match Path::new(path).file_name().and_then(|name| name.to_str()) {
+        Some("level.dat") => Header::Level(LEVEL_STORAGE_VERSION),
+        Some("entities.dat") => Header::Entities(ENTITIES_VERSION),
+        _ => Header::None,
+    }
+}
+
+fn run(args: &[String]) -> Result<(), Box<dyn Error>> {
+    match args {
+        [command, file] if command == "dump" => {
+            let (nbt, _) = read_file(file)?;
+            println!("{:#}", nbt.root());
+        }
+        [command, file, edits] if command == "apply" => {
+            let (mut nbt, header) = read_file(file)?;
+            let edits = into_compound(read_snbt(edits)?)?;
+            match nbt.root_mut() {
+                Tag::Compound(tags) => merge(tags, edits),
+                _ => unreachable!("NBT roots are always compounds"),
+            }
+            write_file(file, &nbt, header)?;
+        }
+        [command, file, snbt] if command == "write" => {
+            let mut root = read_snbt(&format!("@{}", snbt))?;
+            let header = match Path::new(file).exists() {
+                true => {
+                    let (original, header) = read_file(file)?;
+                    root.keep_list_types(original.root());
+                    header
+                }
+                false => header_for(file),
+            };
+            write_file(file, &Nbt::new(into_compound(root)?), header)?;
+        }
+        [command, file, path] if command == "get" => {
+            let (nbt, _) = read_file(file)?;
+            let path: NbtPath = path.parse()?;
+            let mut found = false;
+            for tag in nbt.root().iter_at(&path) {
+                println!("{:#}", tag);
+                found = true;
+            }
+
+            if !found {
+                return Err(NbtError::PathNotFound(path.to_string()).into());
+            }
+        }
+        [command, file, path, value] if command == "set" => {
+            let (mut nbt, header) = read_file(file)?;
+            let path = path.parse()?;
+            let mut value = read_snbt(value)?;
+            if let Some(original) = nbt.root().get_at(&path) {
+                value.keep_list_types(original);
+            }
+            nbt.root_mut().set_at(&path, value)?;
+            write_file(file, &nbt, header)?;
+        }
+        [command, file, path] if command == "remove" => {
+            let (mut nbt, header) = read_file(file)?;
+            let path: NbtPath = path.parse()?;
+            if nbt.root_mut().remove_at(&path).is_empty() {
+                return Err(NbtError::PathNotFound(path.to_string()).into());
+            }
+            write_file(file, &nbt, header)?;
+        }
+        _ => return Err(USAGE.into()),
+    }
+
+    Ok(())
+}
+
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    match run(&args) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(error) => {
+            eprintln!("{}", error);
+            ExitCode::FAILURE
+        }
+    }
+}
@@ -0,0 +1,79 @@
+use std::io::{Read, Write};
+
+use flate2::{read::GzDecoder, read::ZlibDecoder, write::GzEncoder, write::ZlibEncoder};
+
+use crate::{NbtError, Result};
+
+const GZIP_MAGIC: [u8; 2] = [0x1F, 0x8B];
+
+/// Largest output [`Compression::decompress`] produces, bigger files are rejected
+/// instead of being inflated into memory.
+pub const MAX_DECOMPRESSED_SIZE: usize = 64 * 1024 * 1024;
+
+/// Compression wrapped around an NBT file. Pocket files are stored uncompressed, Java
+/// `level.dat` and schematic files are gzipped and region chunks use zlib.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Compression {
+    #[default]
+    None,
+    Gzip,
+    Zlib,
+}
+
+impl Compression {
+    /// Guesses the compression from the first bytes of a file. Uncompressed files always
+    /// start with the compound tag type (10), which can't be mistaken for either header.
+    pub fn detect(bytes: &[u8]) -> Self {
+        match bytes {
+            [a, b, ..] if [*a, *b] == GZIP_MAGIC => Compression::Gzip,
+            // Deflate with a window of at most 32K, and the header checksum has to match
+            [cmf, flg, ..] if cmf & 0x0F == 8 && cmf >> 4 <= 7 => {
+                if (u16::from(*cmf) << 8 | u16::from(*flg)) % 31 == 0 {
+                    Compression::Zlib
+                } else {
+                    Compression::None
+                }
+            }
+            _ => Compression::None,
+        }
+    }
+
+    /// Decompresses the bytes, failing if they inflate past [`MAX_DECOMPRESSED_SIZE`].
+    pub fn decompress(self, bytes: Vec<u8>) -> Result<Vec<u8>> {
+        self.decompress_with_limit(bytes, MAX_DECOMPRESSED_SIZE)
+    }
+
+    /// Decompresses the bytes, failing if they inflate past `limit` bytes.
+    pub fn decompress_with_limit(self, bytes: Vec<u8>, limit: usize) -> Result<Vec<u8>> {
+        let decoder: Box<dyn Read + '_> = match self {
+            Compression::None => return Ok(bytes),
+            Compression::Gzip => Box::new(GzDecoder::new(bytes.as_slice())),
+            Compression::Zlib => Box::new(ZlibDecoder::new(bytes.as_slice())),
+        };
+
+        // One byte past the limit is enough to tell the output doesn't fit
+        let mut output = Vec::new();
+        decoder.take(limit as u64 + 1).read_to_end(&mut output)?;
+        if output.len() > limit {
+            return Err(NbtError::DecompressedTooLarge(limit));
+        }
+
+        Ok(output)
+    }
+
+    pub fn compress(self, bytes: Vec<u8>) -> Result<Vec<u8>> {
+        Ok(match self {
+            Compression::None => bytes,
+            Compression::Gzip => {
+                let mut encoder = GzEncoder::new(Vec::new(), flate2::Compression::default());
+                encoder.write_all(&bytes)?;
+                encoder.finish()?
+            }
+            Compression::Zlib => {
+                let mut encoder = ZlibEncoder::new(Vec::new(), flate2::Compression::default());
+                encoder.write_all(&bytes)?;
+                encoder.finish()?
+            }
+        })
+    }
+}
@@ -0,0 +1,312 @@
+use std::fmt;
+
+use serde::{
+    de::{
+        self, value::MapDeserializer, value::SeqDeserializer, DeserializeOwned, EnumAccess,
+        IntoDeserializer, MapAccess, SeqAccess, VariantAccess, Visitor,
+    },
+    forward_to_deserialize_any, Deserialize, Deserializer,
+};
+
+use crate::{
+    ser::{INT_ARRAY_TOKEN, LONG_ARRAY_TOKEN},
+    Compound, NbtError, Result, Tag,
+};
+
+/// Converts a tag into any deserializable value, the counterpart of [`crate::to_tag`].
+pub fn from_tag<T: DeserializeOwned>(tag: Tag) -> Result<T> {
+    T::deserialize(tag)
+}
+
+impl<'de> Deserialize<'de> for Tag {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> std::result::Result<Self, D::Error> {
+        deserializer.deserialize_any(TagVisitor)
+    }
+}
+
+struct TagVisitor;
+
+impl<'de> Visitor<'de> for TagVisitor {
+    type Value = Tag;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("an NBT tag")
+    }
+
+    fn visit_bool<E: de::Error>(self, value: bool) -> std::result::Result<Tag, E> {
+        Ok(Tag::Byte(value as u8))
+    }
+
+    fn visit_i8<E: de::Error>(self, value: i8) -> std::result::Result<Tag, E> {
+        Ok(Tag::Byte(value as u8))
+    }
+
+    fn visit_u8<E: de::Error>(self, value: u8) -> std::result::Result<Tag, E> {
+        Ok(Tag::Byte(value))
+    }
+
+    fn visit_i16<E: de::Error>(self, value: i16) -> std::result::Result<Tag, E> {
+        Ok(Tag::Short(value))
+    }
+
+    fn visit_i32<E: de::Error>(self, value: i32) -> std::result::Result<Tag, E> {
+        Ok(Tag::Int(value))
+    }
+
+    fn visit_i64<E: de::Error>(self, value: i64) -> std::result::Result<Tag, E> {
+        Ok(Tag::Long(value))
+    }
+
+    fn visit_u64<E: de::Error>(self, value: u64) -> std::result::Result<Tag, E> {
+        i64::try_from(value)
+            .map(Tag::Long)
+            .map_err(|_| E::custom(format!("{} does not fit into a tag", value)))
+    }
+
+    fn visit_f32<E: de::Error>(self, value: f32) -> std::result::Result<Tag, E> {
+        Ok(Tag::Float(value))
+    }
+
+    fn visit_f64<E: de::Error>(self, value: f64) -> std::result::Result<Tag, E> {
+        Ok(Tag::Double(value))
+    }
+
+    fn visit_str<E: de::Error>(self, value: &str) -> std::result::Result<Tag, E> {
+        Ok(Tag::String(value.to_string()))
+    }
+
+    fn visit_string<E: de::Error>(self, value: String) -> std::result::Result<Tag, E> {
+        Ok(Tag::String(value))
+    }
+
+    fn visit_bytes<E: de::Error>(self, value: &[u8]) -> std::result::Result<Tag, E> {
+        Ok(Tag::ByteArray(value.to_vec()))
+    }
+
+    fn visit_byte_buf<E: de::Error>(self, value: Vec<u8>) -> std::result::Result<Tag, E> {
+        Ok(Tag::ByteArray(value))
+    }
+
+    fn visit_unit<E: de::Error>(self) -> std::result::Result<Tag, E> {
+        Ok(Tag::End())
+    }
+
+    fn visit_some<D: Deserializer<'de>>(
+        self,
+        deserializer: D,
+    ) -> std::result::Result<Tag, D::Error> {
+        deserializer.deserialize_any(self)
+    }
+
+    fn visit_newtype_struct<D: Deserializer<'de>>(
+        self,
+        deserializer: D,
+    ) -> std::result::Result<Tag, D::Error> {
+        deserializer.deserialize_any(self)
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> std::result::Result<Tag, A::Error> {
+        let mut list = Vec::with_capacity(seq.size_hint().unwrap_or_default());
+        while let Some(tag) = seq.next_element()? {
+            list.push(tag);
+        }
+
+        Ok(Tag::list(list))
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> std::result::Result<Tag, A::Error> {
+        let mut tags = Compound::new();
+        while let Some(key) = map.next_key::<String>()? {
+            // Int and long arrays are handed out as a single entry map, see `deserialize_any`
+            if tags.is_empty() && key == INT_ARRAY_TOKEN {
+                return Ok(Tag::IntArray(map.next_value()?));
+            }
+            if tags.is_empty() && key == LONG_ARRAY_TOKEN {
+                return Ok(Tag::LongArray(map.next_value()?));
+            }
+
+            tags.insert(key, map.next_value()?);
+        }
+
+        Ok(Tag::Compound(tags))
+    }
+}
+
+impl<'de> IntoDeserializer<'de, NbtError> for Tag {
+    type Deserializer = Self;
+
+    fn into_deserializer(self) -> Self {
+        self
+    }
+}
+
+fn array_entry(
+    token: &'static str,
+    list: Vec<Tag>,
+) -> MapDeserializer<'static, std::iter::Once<(&'static str, Tag)>, NbtError> {
+    MapDeserializer::new(std::iter::once((token, Tag::list(list))))
+}
+
+impl<'de> Deserializer<'de> for Tag {
+    type Error = NbtError;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        match self {
+            Tag::End() => visitor.visit_unit(),
+            Tag::Byte(value) => visitor.visit_u8(value),
+            Tag::Short(value) => visitor.visit_i16(value),
+            Tag::Int(value) => visitor.visit_i32(value),
+            Tag::Long(value) => visitor.visit_i64(value),
+            Tag::Float(value) => visitor.visit_f32(value),
+            Tag::Double(value) => visitor.visit_f64(value),
+            Tag::ByteArray(array) => visitor.visit_byte_buf(array),
+            Tag::String(value) => visitor.visit_string(value),
+            Tag::List(_, list) => visitor.visit_seq(SeqDeserializer::new(list.into_iter())),
+            Tag::Compound(tags) => visitor.visit_map(MapDeserializer::new(tags.into_iter())),
+            // Serde has no notion of typed arrays, so they are wrapped in a map that only
+            // the tag visitor recognizes. Everything else asks for a sequence and gets one.
+            Tag::IntArray(array) => visitor.visit_map(array_entry(
+                INT_ARRAY_TOKEN,
+                array.into_iter().map(Tag::Int).collect(),
+            )),
+            Tag::LongArray(array) => visitor.visit_map(array_entry(
+                LONG_ARRAY_TOKEN,
+                array.into_iter().map(Tag::Long).collect(),
+            )),
+        }
+    }
+
+    fn deserialize_bool<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        match self {
+            Tag::Byte(value) => visitor.visit_bool(value != 0),
+            tag => tag.deserialize_any(visitor),
+        }
+    }
+
+    fn deserialize_i8<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        match self {
+            Tag::Byte(value) => visitor.visit_i8(value as i8),
+            tag => tag.deserialize_any(visitor),
+        }
+    }
+
+    fn deserialize_bytes<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        self.deserialize_byte_buf(visitor)
+    }
+
+    fn deserialize_byte_buf<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        match self {
+            Tag::ByteArray(array) => visitor.visit_byte_buf(array),
+            tag => tag.deserialize_seq(visitor),
+        }
+    }
+
+    fn deserialize_seq<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        match self {
+            Tag::ByteArray(array) => {
+                visitor.visit_seq(SeqDeserializer::new(array.into_iter().map(Tag::Byte)))
+            }
+            Tag::IntArray(array) => {
+                visitor.visit_seq(SeqDeserializer::new(array.into_iter().map(Tag::Int)))
+            }
+            Tag::LongArray(array) => {
+                visitor.visit_seq(SeqDeserializer::new(array.into_iter().map(Tag::Long)))
+            }
+            tag => tag.deserialize_any(visitor),
+        }
+    }
+
+    fn deserialize_tuple<V: Visitor<'de>>(self, _length: usize, visitor: V) -> Result<V::Value> {
+        self.deserialize_seq(visitor)
+    }
+
+    fn deserialize_tuple_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _length: usize,
+        visitor: V,
+    ) -> Result<V::Value> {
+        self.deserialize_seq(visitor)
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        visitor.visit_some(self)
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value> {
+        match self {
+            Tag::String(variant) => visitor.visit_enum(variant.into_deserializer()),
+            Tag::Compound(tags) if tags.len() == 1 => {
+                let (variant, value) = tags.into_iter().next().unwrap();
+                visitor.visit_enum(Variant { variant, value })
+            }
+            tag => Err(NbtError::Message(format!(
+                "Expected an enum, found tag type {}",
+                tag.tag_type()
+            ))),
+        }
+    }
+
+    fn deserialize_ignored_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        visitor.visit_unit()
+    }
+
+    forward_to_deserialize_any! {
+        i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        unit unit_struct map struct identifier
+    }
+}
+
+/// An enum variant with data, stored as a compound with the variant name as its only key.
+struct Variant {
+    variant: String,
+    value: Tag,
+}
+
+impl<'de> EnumAccess<'de> for Variant {
+    type Error = NbtError;
+    type Variant = Tag;
+
+    fn variant_seed<V: de::DeserializeSeed<'de>>(self, seed: V) -> Result<(V::Value, Tag)> {
+        let variant: de::value::StringDeserializer<NbtError> = self.variant.into_deserializer();
+        let variant = seed.deserialize(variant)?;
+        Ok((variant, self.value))
+    }
+}
+
+impl<'de> VariantAccess<'de> for Tag {
+    type Error = NbtError;
+
+    fn unit_variant(self) -> Result<()> {
+        Ok(())
+    }
+
+    fn newtype_variant_seed<T: de::DeserializeSeed<'de>>(self, seed: T) -> Result<T::Value> {
+        seed.deserialize(self)
+    }
+
+    fn tuple_variant<V: Visitor<'de>>(self, _length: usize, visitor: V) -> Result<V::Value> {
+        self.deserialize_seq(visitor)
+    }
+
+    fn struct_variant<V: Visitor<'de>>(
+        self,
+        _fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value> {
+        self.deserialize_any(visitor)
+    }
+}
@@ -0,0 +1,95 @@
+use std::{fmt, io};
+
+pub type Result<T> = std::result::Result<T, NbtError>;
+
+#[derive(Debug)]
+pub enum NbtError {
+    Io(io::Error),
+
+    UnknownTagType(u8),
+    RootNotCompound(u8),
+    InvalidString,
+    InvalidLength(i32),
+    DepthLimitExceeded,
+    /// Compressed data inflates to more than the given number of bytes.
+    DecompressedTooLarge(usize),
+
+    StringTooLong(usize),
+    ArrayTooLong(usize),
+    MixedListTypes,
+
+    /// Malformed SNBT, with the byte offset the error was found at.
+    InvalidSnbt(usize, String),
+    InvalidPath(String),
+    PathNotFound(String),
+
+    /// Raised while converting between tags and Rust types with serde.
+    Message(String),
+}
+
+impl fmt::Display for NbtError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            NbtError::Io(error) => write!(f, "{}", error),
+            NbtError::UnknownTagType(tag_type) => write!(f, "Unknown tag type: {}", tag_type),
+            NbtError::RootNotCompound(tag_type) => {
+                write!(f, "Root tag must be a compound tag, found {}", tag_type)
+            }
+            NbtError::InvalidString => write!(f, "String is not valid UTF-8"),
+            NbtError::InvalidLength(length) => write!(f, "Invalid length: {}", length),
+            NbtError::DepthLimitExceeded => write!(f, "Tags are nested too deeply"),
+            NbtError::DecompressedTooLarge(limit) => {
+                write!(f, "Decompressed data is larger than {} bytes", limit)
+            }
+            NbtError::StringTooLong(length) => {
+                write!(f, "String of {} bytes is too long for NBT", length)
+            }
+            NbtError::ArrayTooLong(length) => {
+                write!(f, "Array of {} elements is too long for NBT", length)
+            }
+            NbtError::MixedListTypes => write!(f, "List elements must all have the same type"),
+            NbtError::InvalidSnbt(position, message) => {
+                write!(f, "Invalid SNBT at {}: {}", position, message)
+            }
+            NbtError::InvalidPath(message) => write!(f, "Invalid path: {}", message),
+            NbtError::PathNotFound(path) => write!(f, "Nothing found at \"{}\"", path),
+            NbtError::Message(message) => write!(f, "{}", message),
+        }
+    }
+}
+
+impl std::error::Error for NbtError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            NbtError::Io(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for NbtError {
+    fn from(error: io::Error) -> Self {
+        Self::Io(error)
+    }
+}
+
+impl From<NbtError> for io::Error {
+    fn from(error: NbtError) -> Self {
+        match error {
+            NbtError::Io(error) => error,
+            error => io::Error::new(io::ErrorKind::InvalidData, error),
+        }
+    }
+}
+
+impl serde::ser::Error for NbtError {
+    fn custom<T: fmt::Display>(message: T) -> Self {
+        Self::Message(message.to_string())
+    }
+}
+
+impl serde::de::Error for NbtError {
+    fn custom<T: fmt::Display>(message: T) -> Self {
+        Self::Message(message.to_string())
+    }
+}
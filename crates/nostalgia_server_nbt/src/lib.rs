@@ -1,9 +1,34 @@
-use std::{
-    collections::HashMap,
-    io::{self, Cursor, Read},
-};
+mod compression;
+mod de;
+mod error;
+mod path;
+mod pocket;
+mod ser;
+mod snbt;
 
-use byteorder::{LittleEndian, ReadBytesExt};
+use std::io::{Cursor, Read, Write};
+
+use byteorder::{ReadBytesExt, WriteBytesExt};
+use indexmap::IndexMap;
+
+pub use byteorder::{BigEndian, ByteOrder, LittleEndian};
+pub use compression::*;
+pub use de::from_tag;
+pub use error::*;
+pub use path::NbtPath;
+pub use pocket::{Header, ENTITIES_MAGIC, ENTITIES_VERSION, LEVEL_STORAGE_VERSION};
+pub use ser::to_tag;
+
+/// Compound tags keep their insertion order so files are written back unchanged.
+pub type Compound = IndexMap<String, Tag>;
+
+/// Element type of empty lists built with [`Tag::list`], matching what the original
+/// client writes.
+pub const EMPTY_LIST_TAG_TYPE: u8 = 1;
+
+/// Maximum nesting of lists and compounds, deeper files are rejected instead of
+/// overflowing the stack.
+pub const MAX_DEPTH: usize = 512;
 
 #[derive(Debug, Clone, PartialEq)]
 pub enum Tag {
@@ -16,26 +41,18 @@ pub enum Tag {
     Double(f64),
     ByteArray(Vec<u8>),
     String(String),
-    List(Vec<Tag>),
-    Compound(HashMap<String, Tag>),
+    /// The element type followed by the elements, which must all be of that type. The
+    /// type is kept so empty lists are written back as they were read.
+    List(u8, Vec<Tag>),
+    Compound(Compound),
     IntArray(Vec<i32>),
     LongArray(Vec<i64>),
 }
 
 macro_rules! tag_getter {
     { $name:ident, $tag: ident, $result: ty } => {
-        pub fn $name(&self, name: &str) -> Option<&$result> {
-            let mut tag = self;
-            for part in name.split('.') {
-                match tag {
-                    Tag::Compound(tags) => {
-                        tag = tags.get(part)?;
-                    }
-                    _ => return None,
-                }
-            }
-
-            match tag {
+        pub fn $name(&self, path: &str) -> Option<&$result> {
+            match self.get_at(&path.parse().ok()?)? {
                 Tag::$tag(value) => Some(value),
                 _ => None,
             }
@@ -44,6 +61,55 @@ macro_rules! tag_getter {
 }
 
 impl Tag {
+    /// Builds a list with the type of its first element, or [`EMPTY_LIST_TAG_TYPE`] if
+    /// it's empty.
+    pub fn list(tags: Vec<Tag>) -> Tag {
+        let tag_type = tags.first().map_or(EMPTY_LIST_TAG_TYPE, Tag::tag_type);
+        Tag::List(tag_type, tags)
+    }
+
+    pub fn tag_type(&self) -> u8 {
+        match self {
+            Tag::End() => 0,
+            Tag::Byte(_) => 1,
+            Tag::Short(_) => 2,
+            Tag::Int(_) => 3,
+            Tag::Long(_) => 4,
+            Tag::Float(_) => 5,
+            Tag::Double(_) => 6,
+            Tag::ByteArray(_) => 7,
+            Tag::String(_) => 8,
+            Tag::List(..) => 9,
+            Tag::Compound(_) => 10,
+            Tag::IntArray(_) => 11,
+            Tag::LongArray(_) => 12,
+        }
+    }
+
+    /// Gives empty lists the element type of the list at the same place in `original`.
+    /// SNBT can't spell the type of an empty list, so edits parsed from it would
+    /// otherwise change the type of lists they leave empty.
+    pub fn keep_list_types(&mut self, original: &Tag) {
+        match (self, original) {
+            (Tag::List(tag_type, list), Tag::List(original_type, original)) => {
+                if list.is_empty() {
+                    *tag_type = *original_type;
+                }
+                for (tag, original) in list.iter_mut().zip(original) {
+                    tag.keep_list_types(original);
+                }
+            }
+            (Tag::Compound(tags), Tag::Compound(original)) => {
+                for (key, tag) in tags {
+                    if let Some(original) = original.get(key) {
+                        tag.keep_list_types(original);
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
     tag_getter! { get_byte, Byte, u8 }
     tag_getter! { get_short, Short, i16 }
     tag_getter! { get_int, Int, i32 }
@@ -52,110 +118,253 @@ impl Tag {
     tag_getter! { get_double, Double, f64 }
     tag_getter! { get_byte_array, ByteArray, Vec<u8> }
     tag_getter! { get_string, String, String }
-    tag_getter! { get_list, List, Vec<Tag> }
-    tag_getter! { get_compound, Compound, HashMap<String, Tag> }
+
+    pub fn get_list(&self, path: &str) -> Option<&Vec<Tag>> {
+        match self.get_at(&path.parse().ok()?)? {
+            Tag::List(_, list) => Some(list),
+            _ => None,
+        }
+    }
+
+    tag_getter! { get_compound, Compound, Compound }
     tag_getter! { get_int_array, IntArray, Vec<i32> }
     tag_getter! { get_long_array, LongArray, Vec<i64> }
+
+    fn write_string<B: ByteOrder>(cursor: &mut Cursor<Vec<u8>>, value: &str) -> Result<()> {
+        let length =
+            u16::try_from(value.len()).map_err(|_| NbtError::StringTooLong(value.len()))?;
+
+        cursor.write_u16::<B>(length)?;
+        cursor.write_all(value.as_bytes())?;
+        Ok(())
+    }
+
+    fn write_length<B: ByteOrder>(cursor: &mut Cursor<Vec<u8>>, length: usize) -> Result<()> {
+        let length = i32::try_from(length).map_err(|_| NbtError::ArrayTooLong(length))?;
+        cursor.write_i32::<B>(length)?;
+        Ok(())
+    }
+
+    /// Writes the payload of the tag in little-endian, without its type and name.
+    pub fn write(&self, cursor: &mut Cursor<Vec<u8>>) -> Result<()> {
+        self.write_with::<LittleEndian>(cursor)
+    }
+
+    /// Writes the tag type, the name and then the payload of the tag in little-endian.
+    pub fn write_named(&self, cursor: &mut Cursor<Vec<u8>>, name: &str) -> Result<()> {
+        self.write_named_with::<LittleEndian>(cursor, name)
+    }
+
+    /// Writes the payload of the tag with the given byte order, without its type and name.
+    pub fn write_with<B: ByteOrder>(&self, cursor: &mut Cursor<Vec<u8>>) -> Result<()> {
+        match self {
+            Tag::End() => {}
+            Tag::Byte(value) => cursor.write_u8(*value)?,
+            Tag::Short(value) => cursor.write_i16::<B>(*value)?,
+            Tag::Int(value) => cursor.write_i32::<B>(*value)?,
+            Tag::Long(value) => cursor.write_i64::<B>(*value)?,
+            Tag::Float(value) => cursor.write_f32::<B>(*value)?,
+            Tag::Double(value) => cursor.write_f64::<B>(*value)?,
+            Tag::ByteArray(array) => {
+                Self::write_length::<B>(cursor, array.len())?;
+                cursor.write_all(array)?;
+            }
+            Tag::String(value) => Self::write_string::<B>(cursor, value)?,
+            Tag::List(tag_type, list) => {
+                let tag_type = *tag_type;
+                if list.iter().any(|tag| tag.tag_type() != tag_type) {
+                    return Err(NbtError::MixedListTypes);
+                }
+
+                cursor.write_u8(tag_type)?;
+                Self::write_length::<B>(cursor, list.len())?;
+                for tag in list {
+                    tag.write_with::<B>(cursor)?;
+                }
+            }
+            Tag::Compound(tags) => {
+                for (name, tag) in tags {
+                    tag.write_named_with::<B>(cursor, name)?;
+                }
+                cursor.write_u8(0)?;
+            }
+            Tag::IntArray(array) => {
+                Self::write_length::<B>(cursor, array.len())?;
+                for value in array {
+                    cursor.write_i32::<B>(*value)?;
+                }
+            }
+            Tag::LongArray(array) => {
+                Self::write_length::<B>(cursor, array.len())?;
+                for value in array {
+                    cursor.write_i64::<B>(*value)?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Writes the tag type, the name and then the payload of the tag with the given byte order.
+    pub fn write_named_with<B: ByteOrder>(
+        &self,
+        cursor: &mut Cursor<Vec<u8>>,
+        name: &str,
+    ) -> Result<()> {
+        cursor.write_u8(self.tag_type())?;
+        Self::write_string::<B>(cursor, name)?;
+        self.write_with::<B>(cursor)
+    }
 }
 
 pub struct Nbt {
+    name: String,
     root: Tag,
 }
 
 impl Nbt {
-    fn read_string(cursor: &mut Cursor<Vec<u8>>) -> io::Result<String> {
-        let length = cursor.read_u16::<LittleEndian>()?;
+    pub fn new(tags: Compound) -> Self {
+        Self {
+            name: String::new(),
+            root: Tag::Compound(tags),
+        }
+    }
+
+    /// Creates a file with a named root, which some Java Edition formats check.
+    pub fn named(name: impl Into<String>, tags: Compound) -> Self {
+        Self {
+            name: name.into(),
+            root: Tag::Compound(tags),
+        }
+    }
+
+    fn remaining(cursor: &Cursor<Vec<u8>>) -> usize {
+        cursor
+            .get_ref()
+            .len()
+            .saturating_sub(cursor.position() as usize)
+    }
+
+    /// Reads a length prefix, rejecting negative lengths and ones that can't possibly fit
+    /// in the rest of the buffer, before anything gets allocated for them.
+    fn read_length<B: ByteOrder>(
+        cursor: &mut Cursor<Vec<u8>>,
+        element_size: usize,
+    ) -> Result<usize> {
+        let length = cursor.read_i32::<B>()?;
+        if length < 0 || (length as usize).saturating_mul(element_size) > Self::remaining(cursor) {
+            return Err(NbtError::InvalidLength(length));
+        }
+
+        Ok(length as usize)
+    }
+
+    fn read_string<B: ByteOrder>(cursor: &mut Cursor<Vec<u8>>) -> Result<String> {
+        let length = cursor.read_u16::<B>()?;
         let mut string = vec![0u8; length as usize];
         cursor.read_exact(&mut string)?;
-        Ok(String::from_utf8(string).unwrap())
+        String::from_utf8(string).map_err(|_| NbtError::InvalidString)
     }
 
-    fn read_byte_array(cursor: &mut Cursor<Vec<u8>>) -> io::Result<Vec<u8>> {
-        let length = cursor.read_i32::<LittleEndian>()?;
-        let mut array = vec![0u8; length as usize];
+    fn read_byte_array<B: ByteOrder>(cursor: &mut Cursor<Vec<u8>>) -> Result<Vec<u8>> {
+        let length = Self::read_length::<B>(cursor, 1)?;
+        let mut array = vec![0u8; length];
         cursor.read_exact(&mut array)?;
         Ok(array)
     }
 
-    fn read_int_array(cursor: &mut Cursor<Vec<u8>>) -> io::Result<Vec<i32>> {
-        let length = cursor.read_i32::<LittleEndian>()?;
-        let mut array = vec![0i32; length as usize];
-        for i in 0..length {
-            array[i as usize] = cursor.read_i32::<LittleEndian>()?;
-        }
+    fn read_int_array<B: ByteOrder>(cursor: &mut Cursor<Vec<u8>>) -> Result<Vec<i32>> {
+        let length = Self::read_length::<B>(cursor, 4)?;
+        let mut array = vec![0i32; length];
+        cursor.read_i32_into::<B>(&mut array)?;
         Ok(array)
     }
 
-    fn read_long_array(cursor: &mut Cursor<Vec<u8>>) -> io::Result<Vec<i64>> {
-        let length = cursor.read_i32::<LittleEndian>()?;
-        let mut array = vec![0i64; length as usize];
-        for i in 0..length {
-            array[i as usize] = cursor.read_i64::<LittleEndian>()?;
-        }
+    fn read_long_array<B: ByteOrder>(cursor: &mut Cursor<Vec<u8>>) -> Result<Vec<i64>> {
+        let length = Self::read_length::<B>(cursor, 8)?;
+        let mut array = vec![0i64; length];
+        cursor.read_i64_into::<B>(&mut array)?;
         Ok(array)
     }
 
-    fn read_list(cursor: &mut Cursor<Vec<u8>>) -> io::Result<Tag> {
+    fn read_list<B: ByteOrder>(cursor: &mut Cursor<Vec<u8>>, depth: usize) -> Result<Tag> {
         let tag_type = cursor.read_u8()?;
-        let length = cursor.read_i32::<LittleEndian>()?;
-        let mut list = Vec::with_capacity(length as usize);
+        let length = Self::read_length::<B>(cursor, Self::min_payload_size(tag_type)?)?;
+
+        // Lists of end tags carry no data, they are only valid when empty
+        if tag_type == 0 && length != 0 {
+            return Err(NbtError::InvalidLength(length as i32));
+        }
 
+        let mut list = Vec::with_capacity(length);
         for _ in 0..length {
-            let tag = match tag_type {
-                0 => Tag::End(),
-                1 => Tag::Byte(cursor.read_u8()?),
-                2 => Tag::Short(cursor.read_i16::<LittleEndian>()?),
-                3 => Tag::Int(cursor.read_i32::<LittleEndian>()?),
-                4 => Tag::Long(cursor.read_i64::<LittleEndian>()?),
-                5 => Tag::Float(cursor.read_f32::<LittleEndian>()?),
-                6 => Tag::Double(cursor.read_f64::<LittleEndian>()?),
-                7 => Tag::ByteArray(Self::read_byte_array(cursor)?),
-                8 => Tag::String(Self::read_string(cursor)?),
-                9 => Self::read_list(cursor)?,
-                10 => Self::read_compound(cursor)?,
-                11 => Tag::IntArray(Self::read_int_array(cursor)?),
-                12 => Tag::LongArray(Self::read_long_array(cursor)?),
-                _ => panic!("Unknown tag type: {}", tag_type),
-            };
-
-            list.push(tag);
+            list.push(Self::read_payload::<B>(cursor, tag_type, depth + 1)?);
         }
 
-        Ok(Tag::List(list))
+        Ok(Tag::List(tag_type, list))
     }
 
-    fn read_named_tag(cursor: &mut Cursor<Vec<u8>>) -> io::Result<(String, Tag)> {
+    /// Smallest number of bytes the payload of a tag type can take up.
+    fn min_payload_size(tag_type: u8) -> Result<usize> {
+        Ok(match tag_type {
+            0 => 0,
+            1 => 1,
+            2 => 2,
+            3 | 5 => 4,
+            4 | 6 => 8,
+            7 | 9 | 11 | 12 => 4,
+            8 => 2,
+            10 => 1,
+            _ => return Err(NbtError::UnknownTagType(tag_type)),
+        })
+    }
+
+    fn read_payload<B: ByteOrder>(
+        cursor: &mut Cursor<Vec<u8>>,
+        tag_type: u8,
+        depth: usize,
+    ) -> Result<Tag> {
+        if depth > MAX_DEPTH {
+            return Err(NbtError::DepthLimitExceeded);
+        }
+
+        Ok(match tag_type {
+            0 => Tag::End(),
+            1 => Tag::Byte(cursor.read_u8()?),
+            2 => Tag::Short(cursor.read_i16::<B>()?),
+            3 => Tag::Int(cursor.read_i32::<B>()?),
+            4 => Tag::Long(cursor.read_i64::<B>()?),
+            5 => Tag::Float(cursor.read_f32::<B>()?),
+            6 => Tag::Double(cursor.read_f64::<B>()?),
+            7 => Tag::ByteArray(Self::read_byte_array::<B>(cursor)?),
+            8 => Tag::String(Self::read_string::<B>(cursor)?),
+            9 => Self::read_list::<B>(cursor, depth)?,
+            10 => Self::read_compound::<B>(cursor, depth)?,
+            11 => Tag::IntArray(Self::read_int_array::<B>(cursor)?),
+            12 => Tag::LongArray(Self::read_long_array::<B>(cursor)?),
+            _ => return Err(NbtError::UnknownTagType(tag_type)),
+        })
+    }
+
+    fn read_named_tag<B: ByteOrder>(
+        cursor: &mut Cursor<Vec<u8>>,
+        depth: usize,
+    ) -> Result<(String, Tag)> {
         let tag_type = cursor.read_u8()?;
         if tag_type == 0 {
             return Ok((String::new(), Tag::End()));
         }
 
-        let name = Self::read_string(cursor)?;
-
-        let tag = match tag_type {
-            0 => Tag::End(),
-            1 => Tag::Byte(cursor.read_u8()?),
-            2 => Tag::Short(cursor.read_i16::<LittleEndian>()?),
-            3 => Tag::Int(cursor.read_i32::<LittleEndian>()?),
-            4 => Tag::Long(cursor.read_i64::<LittleEndian>()?),
-            5 => Tag::Float(cursor.read_f32::<LittleEndian>()?),
-            6 => Tag::Double(cursor.read_f64::<LittleEndian>()?),
-            7 => Tag::ByteArray(Self::read_byte_array(cursor)?),
-            8 => Tag::String(Self::read_string(cursor)?),
-            9 => Self::read_list(cursor)?,
-            10 => Self::read_compound(cursor)?,
-            11 => Tag::IntArray(Self::read_int_array(cursor)?),
-            12 => Tag::LongArray(Self::read_long_array(cursor)?),
-            _ => panic!("Unknown tag type: {}", tag_type),
-        };
+        let name = Self::read_string::<B>(cursor)?;
+        let tag = Self::read_payload::<B>(cursor, tag_type, depth)?;
 
         Ok((name, tag))
     }
 
-    fn read_compound(cursor: &mut Cursor<Vec<u8>>) -> io::Result<Tag> {
-        let mut tags = HashMap::new();
+    fn read_compound<B: ByteOrder>(cursor: &mut Cursor<Vec<u8>>, depth: usize) -> Result<Tag> {
+        let mut tags = Compound::new();
         loop {
-            let (tag_name, tag) = Self::read_named_tag(cursor)?;
+            let (tag_name, tag) = Self::read_named_tag::<B>(cursor, depth + 1)?;
             match tag {
                 Tag::End() => break,
                 _ => {
@@ -167,16 +376,50 @@ impl Nbt {
         Ok(Tag::Compound(tags))
     }
 
-    pub fn from_bytes(mut cursor: &mut Cursor<Vec<u8>>) -> io::Result<Self> {
-        let (.., tag) = Self::read_named_tag(&mut cursor)?;
+    /// Reads little-endian NBT, as used by Pocket Edition.
+    pub fn from_bytes(cursor: &mut Cursor<Vec<u8>>) -> Result<Self> {
+        Self::from_bytes_with::<LittleEndian>(cursor)
+    }
+
+    pub fn from_bytes_with<B: ByteOrder>(cursor: &mut Cursor<Vec<u8>>) -> Result<Self> {
+        let (name, tag) = Self::read_named_tag::<B>(cursor, 0)?;
         match tag {
             Tag::Compound(tags) => Ok(Self {
+                name,
                 root: Tag::Compound(tags),
             }),
-            _ => panic!("Root tag must be a compound tag"),
+            tag => Err(NbtError::RootNotCompound(tag.tag_type())),
         }
     }
 
+    /// Writes little-endian NBT, as used by Pocket Edition.
+    pub fn to_bytes(&self, cursor: &mut Cursor<Vec<u8>>) -> Result<()> {
+        self.to_bytes_with::<LittleEndian>(cursor)
+    }
+
+    pub fn to_bytes_with<B: ByteOrder>(&self, cursor: &mut Cursor<Vec<u8>>) -> Result<()> {
+        self.root.write_named_with::<B>(cursor, &self.name)
+    }
+
+    /// Reads a whole file, decompressing it first if it is gzipped or zlib compressed.
+    /// Java Edition files are big-endian, so they are read with `from_compressed::<BigEndian>`.
+    pub fn from_compressed<B: ByteOrder>(bytes: Vec<u8>) -> Result<(Self, Compression)> {
+        let compression = Compression::detect(&bytes);
+        let mut cursor = Cursor::new(compression.decompress(bytes)?);
+        Ok((Self::from_bytes_with::<B>(&mut cursor)?, compression))
+    }
+
+    /// Writes a whole file with the given byte order and compression.
+    pub fn to_compressed<B: ByteOrder>(&self, compression: Compression) -> Result<Vec<u8>> {
+        let mut cursor = Cursor::new(Vec::new());
+        self.to_bytes_with::<B>(&mut cursor)?;
+        compression.compress(cursor.into_inner())
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
     pub fn root(&self) -> &Tag {
         &self.root
     }
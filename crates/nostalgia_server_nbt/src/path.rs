@@ -0,0 +1,372 @@
+use std::{fmt, str::FromStr};
+
+use crate::{Compound, NbtError, Result, Tag};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Segment {
+    /// Child of a compound.
+    Key(String),
+    /// Element of a list, negative indices count from the end.
+    Index(i64),
+    /// Every element of a list, written as `[]`.
+    All,
+}
+
+/// A path into nested tags such as `Entities[3].Pos[1]` or `Entities[].Motion`.
+///
+/// Keys are separated by dots and can be quoted to include dots or brackets, as in
+/// `"odd.key"[0]`. Array tags can't be indexed into since their elements aren't tags.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NbtPath {
+    segments: Vec<Segment>,
+}
+
+impl NbtPath {
+    fn invalid(path: &str, message: &str) -> NbtError {
+        NbtError::InvalidPath(format!("{} in \"{}\"", message, path))
+    }
+
+    fn parse_key(
+        path: &str,
+        characters: &mut std::iter::Peekable<std::str::Chars>,
+    ) -> Result<String> {
+        let mut key = String::new();
+        if characters.peek() == Some(&'"') {
+            characters.next();
+            loop {
+                match characters.next() {
+                    Some('\\') => match characters.next() {
+                        Some(character) => key.push(character),
+                        None => return Err(Self::invalid(path, "Unterminated key")),
+                    },
+                    Some('"') => return Ok(key),
+                    Some(character) => key.push(character),
+                    None => return Err(Self::invalid(path, "Unterminated key")),
+                }
+            }
+        }
+
+        while let Some(&character) = characters.peek() {
+            if matches!(character, '.' | '[' | ']' | '"') {
+                break;
+            }
+            key.push(character);
+            characters.next();
+        }
+
+        if key.is_empty() {
+            return Err(Self::invalid(path, "Empty key"));
+        }
+
+        Ok(key)
+    }
+
+    /// Resolves a possibly negative index against the length of a list.
+    fn resolve_index(index: i64, length: usize) -> Option<usize> {
+        let index = if index < 0 {
+            length as i64 + index
+        } else {
+            index
+        };
+
+        (0..length as i64)
+            .contains(&index)
+            .then_some(index as usize)
+    }
+
+    fn collect<'a>(tag: &'a Tag, segments: &[Segment], matches: &mut Vec<&'a Tag>) {
+        let Some((segment, rest)) = segments.split_first() else {
+            matches.push(tag);
+            return;
+        };
+
+        match (segment, tag) {
+            (Segment::Key(key), Tag::Compound(tags)) => {
+                if let Some(tag) = tags.get(key) {
+                    Self::collect(tag, rest, matches);
+                }
+            }
+            (Segment::Index(index), Tag::List(_, list)) => {
+                if let Some(index) = Self::resolve_index(*index, list.len()) {
+                    Self::collect(&list[index], rest, matches);
+                }
+            }
+            (Segment::All, Tag::List(_, list)) => {
+                for tag in list {
+                    Self::collect(tag, rest, matches);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn collect_mut<'a>(tag: &'a mut Tag, segments: &[Segment], matches: &mut Vec<&'a mut Tag>) {
+        let Some((segment, rest)) = segments.split_first() else {
+            matches.push(tag);
+            return;
+        };
+
+        match (segment, tag) {
+            (Segment::Key(key), Tag::Compound(tags)) => {
+                if let Some(tag) = tags.get_mut(key) {
+                    Self::collect_mut(tag, rest, matches);
+                }
+            }
+            (Segment::Index(index), Tag::List(_, list)) => {
+                if let Some(index) = Self::resolve_index(*index, list.len()) {
+                    Self::collect_mut(&mut list[index], rest, matches);
+                }
+            }
+            (Segment::All, Tag::List(_, list)) => {
+                for tag in list {
+                    Self::collect_mut(tag, rest, matches);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Splits off the last segment, which the mutating operations apply to.
+    fn split_last(&self) -> Result<(&[Segment], &Segment)> {
+        match self.segments.split_last() {
+            Some((last, parents)) => Ok((parents, last)),
+            None => Err(NbtError::InvalidPath("Empty path".to_string())),
+        }
+    }
+
+    fn not_found(&self) -> NbtError {
+        NbtError::PathNotFound(self.to_string())
+    }
+}
+
+fn check_list_type(list: &[Tag], skip: Option<usize>, value: &Tag) -> Result<()> {
+    let mixed = list
+        .iter()
+        .enumerate()
+        .filter(|(index, _)| Some(*index) != skip)
+        .any(|(_, tag)| tag.tag_type() != value.tag_type());
+
+    if mixed {
+        return Err(NbtError::MixedListTypes);
+    }
+
+    Ok(())
+}
+
+impl FromStr for NbtPath {
+    type Err = NbtError;
+
+    fn from_str(path: &str) -> Result<Self> {
+        let mut segments = Vec::new();
+        let mut characters = path.chars().peekable();
+
+        loop {
+            match characters.peek() {
+                Some('[') => {
+                    characters.next();
+                    let mut index = String::new();
+                    loop {
+                        match characters.next() {
+                            Some(']') => break,
+                            Some(character) => index.push(character),
+                            None => return Err(Self::invalid(path, "Unterminated index")),
+                        }
+                    }
+
+                    if index.trim().is_empty() {
+                        segments.push(Segment::All);
+                    } else {
+                        let index = index
+                            .trim()
+                            .parse()
+                            .map_err(|_| Self::invalid(path, "Invalid index"))?;
+                        segments.push(Segment::Index(index));
+                    }
+                }
+                Some('.') if !segments.is_empty() => {
+                    characters.next();
+                    segments.push(Segment::Key(Self::parse_key(path, &mut characters)?));
+                }
+                Some(_) if segments.is_empty() => {
+                    segments.push(Segment::Key(Self::parse_key(path, &mut characters)?));
+                }
+                Some(_) => return Err(Self::invalid(path, "Expected '.' or '['")),
+                None if segments.is_empty() => return Err(Self::invalid(path, "Empty path")),
+                None => return Ok(Self { segments }),
+            }
+        }
+    }
+}
+
+impl fmt::Display for NbtPath {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (position, segment) in self.segments.iter().enumerate() {
+            match segment {
+                Segment::Key(key) => {
+                    if position > 0 {
+                        f.write_str(".")?;
+                    }
+
+                    if key.contains(['.', '[', ']', '"']) {
+                        write!(f, "\"{}\"", key.replace('\\', "\\\\").replace('"', "\\\""))?;
+                    } else {
+                        f.write_str(key)?;
+                    }
+                }
+                Segment::Index(index) => write!(f, "[{}]", index)?,
+                Segment::All => f.write_str("[]")?,
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl Tag {
+    /// Returns the first tag matching the path.
+    pub fn get_at(&self, path: &NbtPath) -> Option<&Tag> {
+        self.iter_at(path).next()
+    }
+
+    pub fn get_at_mut(&mut self, path: &NbtPath) -> Option<&mut Tag> {
+        self.iter_at_mut(path).next()
+    }
+
+    /// Iterates over every tag matching the path, more than one if it contains `[]`.
+    pub fn iter_at(&self, path: &NbtPath) -> impl Iterator<Item = &Tag> {
+        let mut matches = Vec::new();
+        NbtPath::collect(self, &path.segments, &mut matches);
+        matches.into_iter()
+    }
+
+    pub fn iter_at_mut(&mut self, path: &NbtPath) -> impl Iterator<Item = &mut Tag> {
+        let mut matches = Vec::new();
+        NbtPath::collect_mut(self, &path.segments, &mut matches);
+        matches.into_iter()
+    }
+
+    /// Sets the value at the path, replacing list elements and adding or replacing
+    /// compound keys. Missing compounds along the way are created. Returns how many
+    /// values were set.
+    pub fn set_at(&mut self, path: &NbtPath, value: Tag) -> Result<usize> {
+        let (parents, last) = path.split_last()?;
+
+        // Create the compounds leading up to the value, as long as the path only has keys
+        if matches!(last, Segment::Key(_))
+            && parents
+                .iter()
+                .all(|segment| matches!(segment, Segment::Key(_)))
+        {
+            let mut tag = &mut *self;
+            for segment in parents {
+                let (Segment::Key(key), Tag::Compound(tags)) = (segment, tag) else {
+                    break;
+                };
+                tag = tags
+                    .entry(key.clone())
+                    .or_insert_with(|| Tag::Compound(Compound::new()));
+            }
+        }
+
+        let mut count = 0;
+        for parent in self.iter_at_mut(&NbtPath {
+            segments: parents.to_vec(),
+        }) {
+            match (last, parent) {
+                (Segment::Key(key), Tag::Compound(tags)) => {
+                    tags.insert(key.clone(), value.clone());
+                }
+                (Segment::Index(index), Tag::List(tag_type, list)) => {
+                    let index = NbtPath::resolve_index(*index, list.len())
+                        .ok_or_else(|| path.not_found())?;
+                    check_list_type(list, Some(index), &value)?;
+                    list[index] = value.clone();
+                    *tag_type = value.tag_type();
+                }
+                (Segment::All, Tag::List(tag_type, list)) => {
+                    for element in list.iter_mut() {
+                        *element = value.clone();
+                    }
+                    if !list.is_empty() {
+                        *tag_type = value.tag_type();
+                    }
+                    count += list.len();
+                    continue;
+                }
+                _ => continue,
+            }
+            count += 1;
+        }
+
+        match count {
+            0 => Err(path.not_found()),
+            count => Ok(count),
+        }
+    }
+
+    /// Inserts a value into a list before the index at the end of the path, or appends
+    /// it when the path ends in `[]`.
+    pub fn insert_at(&mut self, path: &NbtPath, value: Tag) -> Result<usize> {
+        let (parents, last) = path.split_last()?;
+        if let Segment::Key(_) = last {
+            return Err(NbtError::InvalidPath(format!(
+                "Can only insert into lists, not \"{}\"",
+                path
+            )));
+        }
+
+        let mut count = 0;
+        for parent in self.iter_at_mut(&NbtPath {
+            segments: parents.to_vec(),
+        }) {
+            let Tag::List(tag_type, list) = parent else {
+                continue;
+            };
+
+            let index = match last {
+                Segment::Index(index) if (0..=list.len() as i64).contains(index) => *index as usize,
+                Segment::Index(index) => {
+                    NbtPath::resolve_index(*index, list.len()).ok_or_else(|| path.not_found())?
+                }
+                _ => list.len(),
+            };
+
+            check_list_type(list, None, &value)?;
+            list.insert(index, value.clone());
+            *tag_type = value.tag_type();
+            count += 1;
+        }
+
+        match count {
+            0 => Err(path.not_found()),
+            count => Ok(count),
+        }
+    }
+
+    /// Removes and returns every tag matching the path.
+    pub fn remove_at(&mut self, path: &NbtPath) -> Vec<Tag> {
+        let Ok((parents, last)) = path.split_last() else {
+            return Vec::new();
+        };
+
+        let mut removed = Vec::new();
+        for parent in self.iter_at_mut(&NbtPath {
+            segments: parents.to_vec(),
+        }) {
+            match (last, parent) {
+                (Segment::Key(key), Tag::Compound(tags)) => {
+                    removed.extend(tags.shift_remove(key));
+                }
+                (Segment::Index(index), Tag::List(_, list)) => {
+                    if let Some(index) = NbtPath::resolve_index(*index, list.len()) {
+                        removed.push(list.remove(index));
+                    }
+                }
+                (Segment::All, Tag::List(_, list)) => removed.append(list),
+                _ => {}
+            }
+        }
+
+        removed
+    }
+}
@@ -0,0 +1,99 @@
+use std::io::{Cursor, Read, Write};
+
+use byteorder::{LittleEndian, ReadBytesExt, WriteBytesExt};
+
+use crate::{Nbt, NbtError, Result};
+
+/// Storage version in the header of a legacy `level.dat`.
+pub const LEVEL_STORAGE_VERSION: i32 = 3;
+pub const ENTITIES_MAGIC: &[u8; 4] = b"ENT\0";
+pub const ENTITIES_VERSION: u32 = 1;
+const COMPOUND_TAG_TYPE: u8 = 10;
+
+/// Header in front of the NBT of a Pocket Edition file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Header {
+    /// `level.dat`: the storage version followed by the length of the NBT.
+    Level(i32),
+    /// `entities.dat`: `ENT\0`, a version and the length of the NBT.
+    Entities(u32),
+    /// Bare NBT without a header.
+    None,
+}
+
+impl Header {
+    /// Guesses the header from the start of a file.
+    pub fn detect(bytes: &[u8]) -> Self {
+        let mut cursor = Cursor::new(bytes);
+        let mut magic = [0u8; 4];
+        if cursor.read_exact(&mut magic).is_ok() && &magic == ENTITIES_MAGIC {
+            let version = cursor.read_u32::<LittleEndian>().unwrap_or_default();
+            return Header::Entities(version);
+        }
+
+        let mut cursor = Cursor::new(bytes);
+        if let (Ok(version), Ok(length)) = (
+            cursor.read_i32::<LittleEndian>(),
+            cursor.read_i32::<LittleEndian>(),
+        ) {
+            // Some files have padding after the NBT, the length only has to fit
+            let fits = usize::try_from(length).is_ok_and(|length| length <= bytes.len() - 8);
+            if fits && bytes.get(8) == Some(&COMPOUND_TAG_TYPE) {
+                return Header::Level(version);
+            }
+        }
+
+        Header::None
+    }
+}
+
+impl Nbt {
+    /// Reads a Pocket Edition file, skipping over and returning its header.
+    pub fn from_pocket_file(bytes: Vec<u8>) -> Result<(Self, Header)> {
+        let header = Header::detect(&bytes);
+        let mut cursor = Cursor::new(bytes);
+
+        let length = match header {
+            Header::Level(_) => {
+                cursor.set_position(4);
+                cursor.read_i32::<LittleEndian>()?
+            }
+            Header::Entities(_) => {
+                cursor.set_position(8);
+                cursor.read_i32::<LittleEndian>()?
+            }
+            Header::None => cursor.get_ref().len() as i32,
+        };
+
+        let start = cursor.position() as usize;
+        if length < 0 || start + length as usize > cursor.get_ref().len() {
+            return Err(NbtError::InvalidLength(length));
+        }
+
+        Ok((Self::from_bytes(&mut cursor)?, header))
+    }
+
+    /// Writes a Pocket Edition file, with the lengths in the header filled in.
+    pub fn to_pocket_file(&self, header: Header) -> Result<Vec<u8>> {
+        let mut nbt = Cursor::new(Vec::new());
+        self.to_bytes(&mut nbt)?;
+        let nbt = nbt.into_inner();
+
+        let mut cursor = Cursor::new(Vec::with_capacity(nbt.len() + 12));
+        match header {
+            Header::Level(version) => {
+                cursor.write_i32::<LittleEndian>(version)?;
+                cursor.write_i32::<LittleEndian>(nbt.len() as i32)?;
+            }
+            Header::Entities(version) => {
+                cursor.write_all(ENTITIES_MAGIC)?;
+                cursor.write_u32::<LittleEndian>(version)?;
+                cursor.write_u32::<LittleEndian>(nbt.len() as u32)?;
+            }
+            Header::None => {}
+        }
+
+        cursor.write_all(&nbt)?;
+        Ok(cursor.into_inner())
+    }
+}
@@ -0,0 +1,433 @@
+use serde::{
+    ser::{self, Serialize},
+    Serializer,
+};
+
+use crate::{Compound, NbtError, Result, Tag};
+
+/// Newtype names used to carry the array tags through serde, which only knows about
+/// sequences. Anything else serializing a sequence ends up as a list tag.
+pub(crate) const BYTE_ARRAY_TOKEN: &str = "__nbt_byte_array";
+pub(crate) const INT_ARRAY_TOKEN: &str = "__nbt_int_array";
+pub(crate) const LONG_ARRAY_TOKEN: &str = "__nbt_long_array";
+
+/// Converts any serializable value into a tag. Structs and maps become compound tags,
+/// sequences become lists and `None` fields are left out.
+pub fn to_tag<T: Serialize + ?Sized>(value: &T) -> Result<Tag> {
+    value
+        .serialize(TagSerializer)?
+        .ok_or_else(|| NbtError::Message("Value can't be represented as a tag".to_string()))
+}
+
+impl Serialize for Tag {
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        match self {
+            Tag::End() => serializer.serialize_unit(),
+            Tag::Byte(value) => serializer.serialize_u8(*value),
+            Tag::Short(value) => serializer.serialize_i16(*value),
+            Tag::Int(value) => serializer.serialize_i32(*value),
+            Tag::Long(value) => serializer.serialize_i64(*value),
+            Tag::Float(value) => serializer.serialize_f32(*value),
+            Tag::Double(value) => serializer.serialize_f64(*value),
+            Tag::ByteArray(array) => serializer.serialize_bytes(array),
+            Tag::String(value) => serializer.serialize_str(value),
+            Tag::List(_, list) => serializer.collect_seq(list),
+            Tag::Compound(tags) => serializer.collect_map(tags),
+            Tag::IntArray(array) => serializer.serialize_newtype_struct(INT_ARRAY_TOKEN, array),
+            Tag::LongArray(array) => serializer.serialize_newtype_struct(LONG_ARRAY_TOKEN, array),
+        }
+    }
+}
+
+fn out_of_range(value: impl std::fmt::Display) -> NbtError {
+    NbtError::Message(format!("{} does not fit into a tag", value))
+}
+
+/// Serializes into `None` for values without a tag representation, such as `None` or
+/// `()`, so compounds can skip them.
+struct TagSerializer;
+
+impl Serializer for TagSerializer {
+    type Ok = Option<Tag>;
+    type Error = NbtError;
+
+    type SerializeSeq = ListSerializer;
+    type SerializeTuple = ListSerializer;
+    type SerializeTupleStruct = ListSerializer;
+    type SerializeTupleVariant = VariantSerializer<ListSerializer>;
+    type SerializeMap = CompoundSerializer;
+    type SerializeStruct = CompoundSerializer;
+    type SerializeStructVariant = VariantSerializer<CompoundSerializer>;
+
+    fn serialize_bool(self, value: bool) -> Result<Self::Ok> {
+        Ok(Some(Tag::Byte(value as u8)))
+    }
+
+    fn serialize_i8(self, value: i8) -> Result<Self::Ok> {
+        Ok(Some(Tag::Byte(value as u8)))
+    }
+
+    fn serialize_i16(self, value: i16) -> Result<Self::Ok> {
+        Ok(Some(Tag::Short(value)))
+    }
+
+    fn serialize_i32(self, value: i32) -> Result<Self::Ok> {
+        Ok(Some(Tag::Int(value)))
+    }
+
+    fn serialize_i64(self, value: i64) -> Result<Self::Ok> {
+        Ok(Some(Tag::Long(value)))
+    }
+
+    fn serialize_u8(self, value: u8) -> Result<Self::Ok> {
+        Ok(Some(Tag::Byte(value)))
+    }
+
+    fn serialize_u16(self, value: u16) -> Result<Self::Ok> {
+        let value = i16::try_from(value).map_err(|_| out_of_range(value))?;
+        Ok(Some(Tag::Short(value)))
+    }
+
+    fn serialize_u32(self, value: u32) -> Result<Self::Ok> {
+        let value = i32::try_from(value).map_err(|_| out_of_range(value))?;
+        Ok(Some(Tag::Int(value)))
+    }
+
+    fn serialize_u64(self, value: u64) -> Result<Self::Ok> {
+        let value = i64::try_from(value).map_err(|_| out_of_range(value))?;
+        Ok(Some(Tag::Long(value)))
+    }
+
+    fn serialize_f32(self, value: f32) -> Result<Self::Ok> {
+        Ok(Some(Tag::Float(value)))
+    }
+
+    fn serialize_f64(self, value: f64) -> Result<Self::Ok> {
+        Ok(Some(Tag::Double(value)))
+    }
+
+    fn serialize_char(self, value: char) -> Result<Self::Ok> {
+        Ok(Some(Tag::String(value.to_string())))
+    }
+
+    fn serialize_str(self, value: &str) -> Result<Self::Ok> {
+        Ok(Some(Tag::String(value.to_string())))
+    }
+
+    fn serialize_bytes(self, value: &[u8]) -> Result<Self::Ok> {
+        Ok(Some(Tag::ByteArray(value.to_vec())))
+    }
+
+    fn serialize_none(self) -> Result<Self::Ok> {
+        Ok(None)
+    }
+
+    fn serialize_some<T: Serialize + ?Sized>(self, value: &T) -> Result<Self::Ok> {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<Self::Ok> {
+        Ok(None)
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<Self::Ok> {
+        Ok(None)
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        _index: u32,
+        variant: &'static str,
+    ) -> Result<Self::Ok> {
+        Ok(Some(Tag::String(variant.to_string())))
+    }
+
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(
+        self,
+        name: &'static str,
+        value: &T,
+    ) -> Result<Self::Ok> {
+        let tag = value.serialize(self)?;
+        if !matches!(name, BYTE_ARRAY_TOKEN | INT_ARRAY_TOKEN | LONG_ARRAY_TOKEN) {
+            return Ok(tag);
+        }
+
+        let list = match tag {
+            Some(Tag::List(_, list)) => list,
+            Some(Tag::ByteArray(array)) if name == BYTE_ARRAY_TOKEN => {
+                return Ok(Some(Tag::ByteArray(array)))
+            }
+            _ => return Err(NbtError::Message(format!("{} must be a sequence", name))),
+        };
+
+        let array = match name {
+            BYTE_ARRAY_TOKEN => list
+                .into_iter()
+                .map(|tag| match tag {
+                    Tag::Byte(value) => Ok(value),
+                    _ => Err(NbtError::MixedListTypes),
+                })
+                .collect::<Result<_>>()
+                .map(Tag::ByteArray),
+            INT_ARRAY_TOKEN => list
+                .into_iter()
+                .map(|tag| match tag {
+                    Tag::Int(value) => Ok(value),
+                    _ => Err(NbtError::MixedListTypes),
+                })
+                .collect::<Result<_>>()
+                .map(Tag::IntArray),
+            _ => list
+                .into_iter()
+                .map(|tag| match tag {
+                    Tag::Long(value) => Ok(value),
+                    _ => Err(NbtError::MixedListTypes),
+                })
+                .collect::<Result<_>>()
+                .map(Tag::LongArray),
+        }?;
+
+        Ok(Some(array))
+    }
+
+    fn serialize_newtype_variant<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        _index: u32,
+        variant: &'static str,
+        value: &T,
+    ) -> Result<Self::Ok> {
+        Ok(Some(variant_tag(variant, value.serialize(self)?)))
+    }
+
+    fn serialize_seq(self, length: Option<usize>) -> Result<Self::SerializeSeq> {
+        Ok(ListSerializer {
+            list: Vec::with_capacity(length.unwrap_or_default()),
+        })
+    }
+
+    fn serialize_tuple(self, length: usize) -> Result<Self::SerializeTuple> {
+        self.serialize_seq(Some(length))
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _name: &'static str,
+        length: usize,
+    ) -> Result<Self::SerializeTupleStruct> {
+        self.serialize_seq(Some(length))
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _index: u32,
+        variant: &'static str,
+        length: usize,
+    ) -> Result<Self::SerializeTupleVariant> {
+        Ok(VariantSerializer {
+            variant,
+            inner: self.serialize_seq(Some(length))?,
+        })
+    }
+
+    fn serialize_map(self, _length: Option<usize>) -> Result<Self::SerializeMap> {
+        Ok(CompoundSerializer {
+            tags: Compound::new(),
+            key: None,
+        })
+    }
+
+    fn serialize_struct(self, _name: &'static str, length: usize) -> Result<Self::SerializeStruct> {
+        self.serialize_map(Some(length))
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _index: u32,
+        variant: &'static str,
+        length: usize,
+    ) -> Result<Self::SerializeStructVariant> {
+        Ok(VariantSerializer {
+            variant,
+            inner: self.serialize_map(Some(length))?,
+        })
+    }
+}
+
+struct ListSerializer {
+    list: Vec<Tag>,
+}
+
+impl ListSerializer {
+    fn push<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<()> {
+        let tag = value
+            .serialize(TagSerializer)?
+            .ok_or_else(|| NbtError::Message("Lists can't contain empty values".to_string()))?;
+
+        if self
+            .list
+            .first()
+            .is_some_and(|first| first.tag_type() != tag.tag_type())
+        {
+            return Err(NbtError::MixedListTypes);
+        }
+
+        self.list.push(tag);
+        Ok(())
+    }
+}
+
+impl ser::SerializeSeq for ListSerializer {
+    type Ok = Option<Tag>;
+    type Error = NbtError;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<()> {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<Self::Ok> {
+        Ok(Some(Tag::list(self.list)))
+    }
+}
+
+impl ser::SerializeTuple for ListSerializer {
+    type Ok = Option<Tag>;
+    type Error = NbtError;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<()> {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<Self::Ok> {
+        ser::SerializeSeq::end(self)
+    }
+}
+
+impl ser::SerializeTupleStruct for ListSerializer {
+    type Ok = Option<Tag>;
+    type Error = NbtError;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<()> {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<Self::Ok> {
+        ser::SerializeSeq::end(self)
+    }
+}
+
+struct CompoundSerializer {
+    tags: Compound,
+    key: Option<String>,
+}
+
+impl CompoundSerializer {
+    fn insert<T: Serialize + ?Sized>(&mut self, key: String, value: &T) -> Result<()> {
+        if let Some(tag) = value.serialize(TagSerializer)? {
+            self.tags.insert(key, tag);
+        }
+
+        Ok(())
+    }
+}
+
+impl ser::SerializeMap for CompoundSerializer {
+    type Ok = Option<Tag>;
+    type Error = NbtError;
+
+    fn serialize_key<T: Serialize + ?Sized>(&mut self, key: &T) -> Result<()> {
+        match key.serialize(TagSerializer)? {
+            Some(Tag::String(key)) => {
+                self.key = Some(key);
+                Ok(())
+            }
+            _ => Err(NbtError::Message(
+                "Compound keys must be strings".to_string(),
+            )),
+        }
+    }
+
+    fn serialize_value<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<()> {
+        let key = self
+            .key
+            .take()
+            .ok_or_else(|| NbtError::Message("Value serialized before its key".to_string()))?;
+
+        self.insert(key, value)
+    }
+
+    fn end(self) -> Result<Self::Ok> {
+        Ok(Some(Tag::Compound(self.tags)))
+    }
+}
+
+impl ser::SerializeStruct for CompoundSerializer {
+    type Ok = Option<Tag>;
+    type Error = NbtError;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<()> {
+        self.insert(key.to_string(), value)
+    }
+
+    fn end(self) -> Result<Self::Ok> {
+        ser::SerializeMap::end(self)
+    }
+}
+
+/// Enum variants with data are written as a compound with the variant name as its only key.
+fn variant_tag(variant: &str, tag: Option<Tag>) -> Tag {
+    let mut tags = Compound::new();
+    if let Some(tag) = tag {
+        tags.insert(variant.to_string(), tag);
+    }
+
+    Tag::Compound(tags)
+}
+
+struct VariantSerializer<S> {
+    variant: &'static str,
+    inner: S,
+}
+
+impl ser::SerializeTupleVariant for VariantSerializer<ListSerializer> {
+    type Ok = Option<Tag>;
+    type Error = NbtError;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<()> {
+        self.inner.push(value)
+    }
+
+    fn end(self) -> Result<Self::Ok> {
+        Ok(Some(variant_tag(
+            self.variant,
+            ser::SerializeSeq::end(self.inner)?,
+        )))
+    }
+}
+
+impl ser::SerializeStructVariant for VariantSerializer<CompoundSerializer> {
+    type Ok = Option<Tag>;
+    type Error = NbtError;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<()> {
+        self.inner.insert(key.to_string(), value)
+    }
+
+    fn end(self) -> Result<Self::Ok> {
+        Ok(Some(variant_tag(
+            self.variant,
+            ser::SerializeMap::end(self.inner)?,
+        )))
+    }
+}
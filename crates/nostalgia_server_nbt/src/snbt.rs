@@ -0,0 +1,434 @@
+use std::{fmt, str::FromStr};
+
+use crate::{Compound, NbtError, Result, Tag, MAX_DEPTH};
+
+const INDENT: &str = "    ";
+
+/// Keys made of these characters are written without quotes.
+fn is_unquoted_char(character: char) -> bool {
+    character.is_ascii_alphanumeric() || matches!(character, '_' | '-' | '.' | '+')
+}
+
+fn write_string(f: &mut fmt::Formatter, value: &str) -> fmt::Result {
+    f.write_str("\"")?;
+    for character in value.chars() {
+        match character {
+            '"' => f.write_str("\\\"")?,
+            '\\' => f.write_str("\\\\")?,
+            '\n' => f.write_str("\\n")?,
+            '\r' => f.write_str("\\r")?,
+            '\t' => f.write_str("\\t")?,
+            character => write!(f, "{}", character)?,
+        }
+    }
+    f.write_str("\"")
+}
+
+fn write_key(f: &mut fmt::Formatter, key: &str) -> fmt::Result {
+    if !key.is_empty() && key.chars().all(is_unquoted_char) {
+        f.write_str(key)
+    } else {
+        write_string(f, key)
+    }
+}
+
+fn write_array<T: fmt::Display>(
+    f: &mut fmt::Formatter,
+    prefix: &str,
+    suffix: &str,
+    values: &[T],
+) -> fmt::Result {
+    write!(f, "[{};", prefix)?;
+    for (index, value) in values.iter().enumerate() {
+        let separator = if index == 0 { " " } else { ", " };
+        write!(f, "{}{}{}", separator, value, suffix)?;
+    }
+    f.write_str("]")
+}
+
+fn write_newline(f: &mut fmt::Formatter, indent: usize) -> fmt::Result {
+    f.write_str("\n")?;
+    for _ in 0..indent {
+        f.write_str(INDENT)?;
+    }
+    Ok(())
+}
+
+/// Writes the tag as SNBT, spreading compounds and nested lists over multiple lines
+/// when an indentation level is given.
+fn write_tag(f: &mut fmt::Formatter, tag: &Tag, indent: Option<usize>) -> fmt::Result {
+    match tag {
+        Tag::End() => Ok(()),
+        // Java prints bytes as signed, which the parser accepts along with unsigned values
+        Tag::Byte(value) => write!(f, "{}b", *value as i8),
+        Tag::Short(value) => write!(f, "{}s", value),
+        Tag::Int(value) => write!(f, "{}", value),
+        Tag::Long(value) => write!(f, "{}L", value),
+        Tag::Float(value) => write!(f, "{}f", value),
+        Tag::Double(value) => write!(f, "{}d", value),
+        Tag::ByteArray(array) => {
+            let array: Vec<i8> = array.iter().map(|value| *value as i8).collect();
+            write_array(f, "B", "b", &array)
+        }
+        Tag::String(value) => write_string(f, value),
+        Tag::List(_, list) => {
+            let multiline = indent.is_some()
+                && list
+                    .iter()
+                    .any(|tag| matches!(tag, Tag::Compound(_) | Tag::List(..)));
+
+            f.write_str("[")?;
+            for (index, tag) in list.iter().enumerate() {
+                if index > 0 {
+                    f.write_str(",")?;
+                }
+
+                match indent {
+                    Some(indent) if multiline => {
+                        write_newline(f, indent + 1)?;
+                        write_tag(f, tag, Some(indent + 1))?;
+                    }
+                    _ => {
+                        if index > 0 {
+                            f.write_str(" ")?;
+                        }
+                        write_tag(f, tag, indent)?;
+                    }
+                }
+            }
+
+            if let (Some(indent), true) = (indent, multiline) {
+                write_newline(f, indent)?;
+            }
+            f.write_str("]")
+        }
+        Tag::Compound(tags) => {
+            f.write_str("{")?;
+            for (index, (key, tag)) in tags.iter().enumerate() {
+                if index > 0 {
+                    f.write_str(",")?;
+                }
+
+                match indent {
+                    Some(indent) => write_newline(f, indent + 1)?,
+                    None if index > 0 => f.write_str(" ")?,
+                    None => {}
+                }
+
+                write_key(f, key)?;
+                f.write_str(": ")?;
+                write_tag(f, tag, indent.map(|indent| indent + 1))?;
+            }
+
+            if let (Some(indent), false) = (indent, tags.is_empty()) {
+                write_newline(f, indent)?;
+            }
+            f.write_str("}")
+        }
+        Tag::IntArray(array) => write_array(f, "I", "", array),
+        Tag::LongArray(array) => write_array(f, "L", "L", array),
+    }
+}
+
+/// Formats the tag as SNBT on a single line, or indented with `{:#}`.
+impl fmt::Display for Tag {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write_tag(f, self, f.alternate().then_some(0))
+    }
+}
+
+/// Parses SNBT, such as `{Pos: [1.0d, 64.0d, 1.0d], Health: 20s}`.
+impl FromStr for Tag {
+    type Err = NbtError;
+
+    fn from_str(input: &str) -> Result<Self> {
+        let mut parser = Parser { input, position: 0 };
+        let tag = parser.parse_value(0)?;
+
+        parser.skip_whitespace();
+        if parser.position != input.len() {
+            return Err(parser.error("Unexpected trailing characters"));
+        }
+
+        Ok(tag)
+    }
+}
+
+struct Parser<'a> {
+    input: &'a str,
+    position: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn error(&self, message: &str) -> NbtError {
+        NbtError::InvalidSnbt(self.position, message.to_string())
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.input[self.position..].chars().next()
+    }
+
+    fn next(&mut self) -> Option<char> {
+        let character = self.peek()?;
+        self.position += character.len_utf8();
+        Some(character)
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self.peek().is_some_and(char::is_whitespace) {
+            self.next();
+        }
+    }
+
+    fn expect(&mut self, expected: char) -> Result<()> {
+        self.skip_whitespace();
+        match self.next() {
+            Some(character) if character == expected => Ok(()),
+            _ => Err(self.error(&format!("Expected '{}'", expected))),
+        }
+    }
+
+    /// Consumes the separator between elements, returning whether another one follows.
+    fn next_element(&mut self, end: char) -> Result<bool> {
+        self.skip_whitespace();
+        match self.next() {
+            Some(',') => Ok(true),
+            Some(character) if character == end => Ok(false),
+            _ => Err(self.error(&format!("Expected ',' or '{}'", end))),
+        }
+    }
+
+    fn parse_value(&mut self, depth: usize) -> Result<Tag> {
+        if depth > MAX_DEPTH {
+            return Err(NbtError::DepthLimitExceeded);
+        }
+
+        self.skip_whitespace();
+        match self.peek() {
+            Some('{') => self.parse_compound(depth),
+            Some('[') => self.parse_list(depth),
+            Some('"' | '\'') => Ok(Tag::String(self.parse_quoted()?)),
+            Some(_) => self.parse_unquoted(),
+            None => Err(self.error("Unexpected end of input")),
+        }
+    }
+
+    fn parse_key(&mut self) -> Result<String> {
+        self.skip_whitespace();
+        match self.peek() {
+            Some('"' | '\'') => self.parse_quoted(),
+            _ => {
+                let key = self.take_unquoted();
+                if key.is_empty() {
+                    return Err(self.error("Expected a key"));
+                }
+                Ok(key.to_string())
+            }
+        }
+    }
+
+    fn parse_compound(&mut self, depth: usize) -> Result<Tag> {
+        self.expect('{')?;
+
+        let mut tags = Compound::new();
+        self.skip_whitespace();
+        if self.peek() == Some('}') {
+            self.next();
+            return Ok(Tag::Compound(tags));
+        }
+
+        loop {
+            let key = self.parse_key()?;
+            self.expect(':')?;
+            let tag = self.parse_value(depth + 1)?;
+            tags.insert(key, tag);
+
+            if !self.next_element('}')? {
+                return Ok(Tag::Compound(tags));
+            }
+        }
+    }
+
+    fn parse_list(&mut self, depth: usize) -> Result<Tag> {
+        self.expect('[')?;
+
+        // Typed arrays start with their element type, as in `[I; 1, 2, 3]`
+        if let [array_type @ (b'B' | b'I' | b'L'), b';', ..] =
+            &self.input.as_bytes()[self.position..]
+        {
+            let array_type = *array_type as char;
+            self.position += 2;
+            return self.parse_array(array_type);
+        }
+
+        let mut list = Vec::new();
+        self.skip_whitespace();
+        if self.peek() == Some(']') {
+            self.next();
+            return Ok(Tag::list(list));
+        }
+
+        loop {
+            self.skip_whitespace();
+            let start = self.position;
+            let tag = self.parse_value(depth + 1)?;
+            if list
+                .first()
+                .is_some_and(|first: &Tag| first.tag_type() != tag.tag_type())
+            {
+                self.position = start;
+                return Err(self.error("List elements must all have the same type"));
+            }
+            list.push(tag);
+
+            if !self.next_element(']')? {
+                return Ok(Tag::list(list));
+            }
+        }
+    }
+
+    fn parse_array(&mut self, array_type: char) -> Result<Tag> {
+        let mut values = Vec::new();
+        self.skip_whitespace();
+        if self.peek() == Some(']') {
+            self.next();
+        } else {
+            loop {
+                self.skip_whitespace();
+                let start = self.position;
+                let value = match self.parse_unquoted()? {
+                    Tag::Byte(value) => value as i64,
+                    Tag::Short(value) => value as i64,
+                    Tag::Int(value) => value as i64,
+                    Tag::Long(value) => value,
+                    _ => {
+                        self.position = start;
+                        return Err(self.error("Expected an integer"));
+                    }
+                };
+                values.push((start, value));
+
+                if !self.next_element(']')? {
+                    break;
+                }
+            }
+        }
+
+        let out_of_range = |parser: &mut Self, start| {
+            parser.position = start;
+            parser.error("Value is out of range for the array type")
+        };
+
+        match array_type {
+            'B' => values
+                .into_iter()
+                .map(|(start, value)| match value {
+                    -128..=-1 => Ok(value as i8 as u8),
+                    0..=255 => Ok(value as u8),
+                    _ => Err(out_of_range(self, start)),
+                })
+                .collect::<Result<_>>()
+                .map(Tag::ByteArray),
+            'I' => values
+                .into_iter()
+                .map(|(start, value)| i32::try_from(value).map_err(|_| out_of_range(self, start)))
+                .collect::<Result<_>>()
+                .map(Tag::IntArray),
+            _ => Ok(Tag::LongArray(
+                values.into_iter().map(|(_, value)| value).collect(),
+            )),
+        }
+    }
+
+    fn parse_quoted(&mut self) -> Result<String> {
+        let quote = self.next().ok_or_else(|| self.error("Expected a string"))?;
+
+        let mut value = String::new();
+        loop {
+            match self.next() {
+                Some('\\') => match self.next() {
+                    Some('n') => value.push('\n'),
+                    Some('r') => value.push('\r'),
+                    Some('t') => value.push('\t'),
+                    Some(character @ ('\\' | '"' | '\'')) => value.push(character),
+                    _ => return Err(self.error("Invalid escape sequence")),
+                },
+                Some(character) if character == quote => return Ok(value),
+                Some(character) => value.push(character),
+                None => return Err(self.error("Unterminated string")),
+            }
+        }
+    }
+
+    fn take_unquoted(&mut self) -> &'a str {
+        let start = self.position;
+        while self.peek().is_some_and(is_unquoted_char) {
+            self.next();
+        }
+        &self.input[start..self.position]
+    }
+
+    /// Parses numbers with their type suffix, booleans and bare strings.
+    fn parse_unquoted(&mut self) -> Result<Tag> {
+        let start = self.position;
+        let token = self.take_unquoted();
+        if token.is_empty() {
+            return Err(self.error("Expected a value"));
+        }
+
+        let out_of_range = |parser: &mut Self| {
+            parser.position = start;
+            parser.error("Number is out of range")
+        };
+
+        match token {
+            "true" => return Ok(Tag::Byte(1)),
+            "false" => return Ok(Tag::Byte(0)),
+            _ => {}
+        }
+
+        let (number, suffix) = token.split_at(token.len() - 1);
+        // Floats that aren't finite are written as NaN and inf followed by their suffix
+        let numeric = matches!(number, "NaN" | "inf")
+            || token.starts_with(|character: char| {
+                character.is_ascii_digit() || matches!(character, '-' | '+' | '.')
+            });
+
+        if numeric {
+            let is_integer = number.parse::<i128>().is_ok();
+            let tag = match suffix {
+                "b" | "B" if is_integer => Some(match number.parse::<i16>() {
+                    Ok(value @ -128..=-1) => Tag::Byte(value as i8 as u8),
+                    Ok(value @ 0..=255) => Tag::Byte(value as u8),
+                    _ => return Err(out_of_range(self)),
+                }),
+                "s" | "S" if is_integer => {
+                    Some(Tag::Short(number.parse().map_err(|_| out_of_range(self))?))
+                }
+                "l" | "L" if is_integer => {
+                    Some(Tag::Long(number.parse().map_err(|_| out_of_range(self))?))
+                }
+                "f" | "F" => number.parse().ok().map(Tag::Float),
+                "d" | "D" => number.parse().ok().map(Tag::Double),
+                _ => None,
+            };
+
+            if let Some(tag) = tag {
+                return Ok(tag);
+            }
+
+            if let Ok(value) = token.parse::<i32>() {
+                return Ok(Tag::Int(value));
+            }
+
+            if token.contains(['.', 'e', 'E']) {
+                if let Ok(value) = token.parse::<f64>() {
+                    return Ok(Tag::Double(value));
+                }
+            }
+        }
+
+        // Anything else, including integers too large for an int, is a bare string
+        Ok(Tag::String(token.to_string()))
+    }
+}
@@ -0,0 +1,14 @@
+//! Helpers shared by the integration tests, each of which only uses some of them.
+#![allow(dead_code)]
+
+use nbt::{Compound, Tag};
+
+pub fn tags(tags: &[(&str, Tag)]) -> Compound {
+    tags.iter()
+        .map(|(name, tag)| (name.to_string(), tag.clone()))
+        .collect()
+}
+
+pub fn compound(tags: &[(&str, Tag)]) -> Tag {
+    Tag::Compound(self::tags(tags))
+}
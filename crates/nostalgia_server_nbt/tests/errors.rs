@@ -0,0 +1,95 @@
+use std::io::Cursor;
+
+use nbt::{Nbt, NbtError, Tag, MAX_DEPTH};
+
+fn parse(bytes: Vec<u8>) -> Result<Nbt, NbtError> {
+    Nbt::from_bytes(&mut Cursor::new(bytes))
+}
+
+#[test]
+fn truncated_input_is_an_io_error() {
+    let bytes = vec![10, 0, 0, 3, 1, 0, b'a', 0x01, 0x00];
+    assert!(matches!(parse(bytes), Err(NbtError::Io(_))));
+}
+
+#[test]
+fn rejects_unknown_tag_types() {
+    let bytes = vec![10, 0, 0, 13, 1, 0, b'a', 0];
+    assert!(matches!(parse(bytes), Err(NbtError::UnknownTagType(13))));
+}
+
+#[test]
+fn rejects_non_compound_roots() {
+    let bytes = vec![3, 0, 0, 1, 0, 0, 0];
+    assert!(matches!(parse(bytes), Err(NbtError::RootNotCompound(3))));
+}
+
+#[test]
+fn rejects_invalid_utf8() {
+    let bytes = vec![10, 0, 0, 8, 1, 0, b'a', 2, 0, 0xC3, 0x28, 0];
+    assert!(matches!(parse(bytes), Err(NbtError::InvalidString)));
+}
+
+#[test]
+fn rejects_negative_lengths() {
+    let bytes = vec![10, 0, 0, 7, 1, 0, b'a', 0xFF, 0xFF, 0xFF, 0xFF, 0];
+    assert!(matches!(parse(bytes), Err(NbtError::InvalidLength(-1))));
+}
+
+#[test]
+fn rejects_lengths_past_the_end_of_the_input() {
+    // An int array claiming i32::MAX elements must fail before allocating 8 GiB
+    let bytes = vec![10, 0, 0, 11, 1, 0, b'a', 0xFF, 0xFF, 0xFF, 0x7F, 0];
+    assert!(matches!(
+        parse(bytes),
+        Err(NbtError::InvalidLength(i32::MAX))
+    ));
+}
+
+#[test]
+fn rejects_non_empty_lists_of_end_tags() {
+    let bytes = vec![10, 0, 0, 9, 1, 0, b'a', 0, 3, 0, 0, 0, 0];
+    assert!(matches!(parse(bytes), Err(NbtError::InvalidLength(3))));
+}
+
+#[test]
+fn rejects_deeply_nested_lists() {
+    let mut bytes = vec![10, 0, 0, 9, 1, 0, b'a'];
+    for _ in 0..MAX_DEPTH + 1 {
+        bytes.extend_from_slice(&[9, 1, 0, 0, 0]);
+    }
+    bytes.extend_from_slice(&[1, 0, 0, 0, 0]);
+
+    assert!(matches!(parse(bytes), Err(NbtError::DepthLimitExceeded)));
+}
+
+#[test]
+fn accepts_nesting_up_to_the_limit() {
+    let mut tag = Tag::Compound(Default::default());
+    for _ in 0..MAX_DEPTH - 1 {
+        tag = Tag::list(vec![tag]);
+    }
+
+    let nbt = Nbt::new([("a".to_string(), tag)].into_iter().collect());
+    let mut cursor = Cursor::new(Vec::new());
+    nbt.to_bytes(&mut cursor).unwrap();
+
+    let parsed = parse(cursor.into_inner()).unwrap();
+    assert_eq!(parsed.root(), nbt.root());
+}
+
+#[test]
+fn writer_errors_are_typed() {
+    let mut cursor = Cursor::new(Vec::new());
+    let list = Tag::list(vec![Tag::Int(1), Tag::Byte(2)]);
+    assert!(matches!(
+        list.write(&mut cursor),
+        Err(NbtError::MixedListTypes)
+    ));
+
+    let string = Tag::String("a".repeat(u16::MAX as usize + 1));
+    assert!(matches!(
+        string.write(&mut cursor),
+        Err(NbtError::StringTooLong(65536))
+    ));
+}
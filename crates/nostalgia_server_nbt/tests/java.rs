@@ -0,0 +1,121 @@
+use std::io::Cursor;
+
+use nbt::{BigEndian, Compound, Compression, LittleEndian, Nbt, NbtError, Tag};
+
+fn level() -> Nbt {
+    let mut data = Compound::new();
+    data.insert("LevelName".to_string(), Tag::String("World".to_string()));
+    data.insert(
+        "RandomSeed".to_string(),
+        Tag::Long(-4_162_848_385_843_270_000),
+    );
+    data.insert("SpawnY".to_string(), Tag::Int(64));
+    data.insert(
+        "Player".to_string(),
+        Tag::Compound(
+            [(
+                "Pos".to_string(),
+                Tag::list(vec![
+                    Tag::Double(0.5),
+                    Tag::Double(65.62),
+                    Tag::Double(-3.0),
+                ]),
+            )]
+            .into_iter()
+            .collect(),
+        ),
+    );
+
+    Nbt::new(
+        [("Data".to_string(), Tag::Compound(data))]
+            .into_iter()
+            .collect(),
+    )
+}
+
+#[test]
+fn writes_big_endian_layout() {
+    let nbt = Nbt::new(
+        [("Time".to_string(), Tag::Long(0x0102030405060708))]
+            .into_iter()
+            .collect(),
+    );
+
+    let mut cursor = Cursor::new(Vec::new());
+    nbt.to_bytes_with::<BigEndian>(&mut cursor).unwrap();
+
+    #[rustfmt::skip]
+    let expected = vec![
+        10, 0, 0,
+        4, 0, 4, b'T', b'i', b'm', b'e',
+        0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08,
+        0,
+    ];
+
+    assert_eq!(cursor.into_inner(), expected);
+}
+
+#[test]
+fn big_endian_round_trips() {
+    let nbt = level();
+
+    let mut cursor = Cursor::new(Vec::new());
+    nbt.to_bytes_with::<BigEndian>(&mut cursor).unwrap();
+    cursor.set_position(0);
+
+    let parsed = Nbt::from_bytes_with::<BigEndian>(&mut cursor).unwrap();
+    assert_eq!(parsed.root(), nbt.root());
+}
+
+#[test]
+fn compressed_files_round_trip() {
+    let nbt = level();
+
+    for compression in [Compression::None, Compression::Gzip, Compression::Zlib] {
+        let bytes = nbt.to_compressed::<BigEndian>(compression).unwrap();
+        assert_eq!(Compression::detect(&bytes), compression);
+
+        let (parsed, detected) = Nbt::from_compressed::<BigEndian>(bytes).unwrap();
+        assert_eq!(detected, compression);
+        assert_eq!(parsed.root(), nbt.root());
+    }
+}
+
+#[test]
+fn uncompressed_pocket_files_are_not_mistaken_for_zlib() {
+    let bytes = level()
+        .to_compressed::<LittleEndian>(Compression::None)
+        .unwrap();
+    assert_eq!(Compression::detect(&bytes), Compression::None);
+}
+
+#[test]
+fn corrupt_gzip_is_an_error() {
+    let mut bytes = level()
+        .to_compressed::<BigEndian>(Compression::Gzip)
+        .unwrap();
+    bytes.truncate(bytes.len() / 2);
+    assert!(Nbt::from_compressed::<BigEndian>(bytes).is_err());
+}
+
+#[test]
+fn decompression_stops_at_the_limit() {
+    // A megabyte of zeroes compresses to about a kilobyte
+    let zeroes = vec![0; 1024 * 1024];
+    for compression in [Compression::Gzip, Compression::Zlib] {
+        let bytes = compression.compress(zeroes.clone()).unwrap();
+        assert!(bytes.len() < 4096);
+
+        assert_eq!(
+            compression
+                .decompress_with_limit(bytes.clone(), zeroes.len())
+                .unwrap()
+                .len(),
+            zeroes.len()
+        );
+        assert!(matches!(
+            compression.decompress_with_limit(bytes, zeroes.len() - 1),
+            Err(NbtError::DecompressedTooLarge(_))
+        ));
+    }
+}
@@ -0,0 +1,174 @@
+use nbt::{NbtError, NbtPath, Tag};
+
+fn path(path: &str) -> NbtPath {
+    path.parse().unwrap()
+}
+
+fn entities() -> Tag {
+    "{
+        Entities: [
+            {id: 10, Pos: [1.0f, 64.0f, 2.0f]},
+            {id: 11, Pos: [3.0f, 65.0f, 4.0f]},
+            {id: 12, Pos: [5.0f, 66.0f, 6.0f], Items: [{Count: 1b}]}
+        ],
+        \"odd.key\": {value: 1}
+    }"
+    .parse()
+    .unwrap()
+}
+
+#[test]
+fn parses_and_prints_paths() {
+    for text in [
+        "Entities[3].Pos[1]",
+        "Entities[].Pos",
+        "\"odd.key\".value",
+        "[-1]",
+    ] {
+        assert_eq!(path(text).to_string(), text);
+    }
+
+    for invalid in ["", "a.", "a[", "a[x]", "a..b", "\"a", "a]"] {
+        assert!(
+            matches!(invalid.parse::<NbtPath>(), Err(NbtError::InvalidPath(_))),
+            "{:?} should not parse",
+            invalid
+        );
+    }
+}
+
+#[test]
+fn gets_values_through_lists() {
+    let tag = entities();
+
+    assert_eq!(
+        tag.get_at(&path("Entities[1].Pos[1]")),
+        Some(&Tag::Float(65.0))
+    );
+    assert_eq!(tag.get_at(&path("Entities[-1].id")), Some(&Tag::Int(12)));
+    assert_eq!(tag.get_at(&path("\"odd.key\".value")), Some(&Tag::Int(1)));
+    assert_eq!(tag.get_at(&path("Entities[3]")), None);
+    assert_eq!(tag.get_at(&path("Entities.id")), None);
+
+    assert_eq!(tag.get_int("Entities[0].id"), Some(&10));
+    assert_eq!(tag.get_byte("Entities[2].Items[0].Count"), Some(&1));
+}
+
+#[test]
+fn iterates_over_every_match() {
+    let tag = entities();
+    let ids: Vec<&Tag> = tag.iter_at(&path("Entities[].id")).collect();
+    assert_eq!(ids, [&Tag::Int(10), &Tag::Int(11), &Tag::Int(12)]);
+
+    let counts = tag.iter_at(&path("Entities[].Items[].Count")).count();
+    assert_eq!(counts, 1);
+}
+
+#[test]
+fn edits_values_in_place() {
+    let mut tag = entities();
+    for y in tag.iter_at_mut(&path("Entities[].Pos[1]")) {
+        *y = Tag::Float(0.0);
+    }
+    *tag.get_at_mut(&path("Entities[0].id")).unwrap() = Tag::Int(20);
+
+    assert_eq!(tag.get_float("Entities[2].Pos[1]"), Some(&0.0));
+    assert_eq!(tag.get_int("Entities[0].id"), Some(&20));
+}
+
+#[test]
+fn sets_values_and_creates_compounds() {
+    let mut tag = entities();
+
+    assert_eq!(
+        tag.set_at(&path("Entities[1].id"), Tag::Int(99)).unwrap(),
+        1
+    );
+    assert_eq!(tag.get_int("Entities[1].id"), Some(&99));
+
+    assert_eq!(
+        tag.set_at(&path("Entities[].Health"), Tag::Short(20))
+            .unwrap(),
+        3
+    );
+    assert_eq!(tag.get_short("Entities[2].Health"), Some(&20));
+
+    assert_eq!(
+        tag.set_at(&path("Player.Abilities.flying"), Tag::Byte(1))
+            .unwrap(),
+        1
+    );
+    assert_eq!(tag.get_byte("Player.Abilities.flying"), Some(&1));
+}
+
+#[test]
+fn rejects_invalid_sets() {
+    let mut tag = entities();
+
+    assert!(matches!(
+        tag.set_at(&path("Entities[0].Pos[0]"), Tag::Int(1)),
+        Err(NbtError::MixedListTypes)
+    ));
+    assert!(matches!(
+        tag.set_at(&path("Entities[5].id"), Tag::Int(1)),
+        Err(NbtError::PathNotFound(_))
+    ));
+    assert!(matches!(
+        tag.set_at(&path("Missing[0]"), Tag::Int(1)),
+        Err(NbtError::PathNotFound(_))
+    ));
+    assert_eq!(tag.get_at(&path("Missing")), None);
+}
+
+#[test]
+fn inserts_into_lists() {
+    let mut tag = entities();
+    let entity: Tag = "{id: 13}".parse().unwrap();
+
+    assert_eq!(
+        tag.insert_at(&path("Entities[]"), entity.clone()).unwrap(),
+        1
+    );
+    assert_eq!(tag.insert_at(&path("Entities[0]"), entity).unwrap(), 1);
+
+    let ids: Vec<&Tag> = tag.iter_at(&path("Entities[].id")).collect();
+    assert_eq!(
+        ids,
+        [
+            &Tag::Int(13),
+            &Tag::Int(10),
+            &Tag::Int(11),
+            &Tag::Int(12),
+            &Tag::Int(13)
+        ]
+    );
+
+    assert!(matches!(
+        tag.insert_at(&path("Entities[1].Pos[0]"), Tag::Int(1)),
+        Err(NbtError::MixedListTypes)
+    ));
+    assert!(matches!(
+        tag.insert_at(&path("Entities[0].id"), Tag::Int(1)),
+        Err(NbtError::InvalidPath(_))
+    ));
+}
+
+#[test]
+fn removes_values() {
+    let mut tag = entities();
+
+    let removed = tag.remove_at(&path("Entities[1]"));
+    assert_eq!(removed.len(), 1);
+    assert_eq!(removed[0].get_int("id"), Some(&11));
+
+    assert_eq!(tag.remove_at(&path("Entities[].Pos")).len(), 2);
+    assert_eq!(tag.get_at(&path("Entities[0].Pos")), None);
+
+    assert!(tag.remove_at(&path("Missing.key")).is_empty());
+    assert_eq!(tag.remove_at(&path("Entities[]")).len(), 2);
+    // The emptied list is still a list of compounds
+    assert_eq!(
+        tag.get_at(&path("Entities")),
+        Some(&Tag::List(10, Vec::new()))
+    );
+}
@@ -0,0 +1,195 @@
+mod common;
+
+use std::io::Cursor;
+
+use nbt::{Nbt, Tag};
+
+use common::tags;
+
+fn to_bytes(nbt: &Nbt) -> Vec<u8> {
+    let mut cursor = Cursor::new(Vec::new());
+    nbt.to_bytes(&mut cursor).unwrap();
+    cursor.into_inner()
+}
+
+fn round_trip(nbt: &Nbt) -> Nbt {
+    let mut cursor = Cursor::new(to_bytes(nbt));
+    let parsed = Nbt::from_bytes(&mut cursor).unwrap();
+    assert_eq!(cursor.position() as usize, cursor.get_ref().len());
+    parsed
+}
+
+#[test]
+fn every_tag_type_round_trips() {
+    let nbt = Nbt::new(tags(&[
+        ("byte", Tag::Byte(0xFE)),
+        ("short", Tag::Short(-12345)),
+        ("int", Tag::Int(i32::MIN)),
+        ("long", Tag::Long(i64::MAX)),
+        ("float", Tag::Float(1.5)),
+        ("double", Tag::Double(-0.25)),
+        ("byte_array", Tag::ByteArray(vec![0, 1, 2, 255])),
+        ("string", Tag::String("Nostalgia ✓".to_string())),
+        (
+            "list",
+            Tag::list(vec![Tag::Float(1.0), Tag::Float(2.0), Tag::Float(3.0)]),
+        ),
+        ("compound", Tag::Compound(tags(&[("nested", Tag::Int(7))]))),
+        ("int_array", Tag::IntArray(vec![-1, 0, 1, i32::MAX])),
+        ("long_array", Tag::LongArray(vec![i64::MIN, 0, 42])),
+    ]));
+
+    assert_eq!(round_trip(&nbt).root(), nbt.root());
+}
+
+#[test]
+fn nested_lists_and_compounds_round_trip() {
+    let entity = Tag::Compound(tags(&[
+        ("id", Tag::Int(10)),
+        (
+            "Pos",
+            Tag::list(vec![Tag::Float(128.5), Tag::Float(64.0), Tag::Float(3.25)]),
+        ),
+        (
+            "Items",
+            Tag::list(vec![
+                Tag::Compound(tags(&[("id", Tag::Short(5)), ("Count", Tag::Byte(64))])),
+                Tag::Compound(tags(&[("id", Tag::Short(50)), ("Count", Tag::Byte(1))])),
+            ]),
+        ),
+    ]));
+
+    let nbt = Nbt::new(tags(&[
+        ("Entities", Tag::list(vec![entity.clone(), entity])),
+        ("TileEntities", Tag::list(vec![])),
+        (
+            "Matrix",
+            Tag::list(vec![
+                Tag::list(vec![Tag::Int(1), Tag::Int(2)]),
+                Tag::list(vec![Tag::Int(3)]),
+            ]),
+        ),
+    ]));
+
+    assert_eq!(round_trip(&nbt).root(), nbt.root());
+}
+
+#[test]
+fn writes_little_endian_layout() {
+    let nbt = Nbt::new(tags(&[("Time", Tag::Long(0x0102030405060708))]));
+
+    #[rustfmt::skip]
+    let expected = vec![
+        10, 0, 0, // root compound, empty name
+        4, 4, 0, b'T', b'i', b'm', b'e', // long "Time"
+        0x08, 0x07, 0x06, 0x05, 0x04, 0x03, 0x02, 0x01,
+        0, // end of compound
+    ];
+
+    assert_eq!(to_bytes(&nbt), expected);
+}
+
+#[test]
+fn writes_array_lengths_as_little_endian_ints() {
+    let mut cursor = Cursor::new(Vec::new());
+    Tag::IntArray(vec![1, -1]).write(&mut cursor).unwrap();
+    assert_eq!(
+        cursor.into_inner(),
+        vec![2, 0, 0, 0, 1, 0, 0, 0, 0xFF, 0xFF, 0xFF, 0xFF]
+    );
+
+    let mut cursor = Cursor::new(Vec::new());
+    Tag::LongArray(vec![2]).write(&mut cursor).unwrap();
+    assert_eq!(
+        cursor.into_inner(),
+        vec![1, 0, 0, 0, 2, 0, 0, 0, 0, 0, 0, 0]
+    );
+}
+
+#[test]
+fn rejects_mixed_lists() {
+    let mut cursor = Cursor::new(Vec::new());
+    let list = Tag::list(vec![Tag::Int(1), Tag::Byte(2)]);
+    assert!(list.write(&mut cursor).is_err());
+}
+
+#[test]
+fn compounds_keep_their_order() {
+    let nbt = Nbt::new(tags(&[
+        ("zeta", Tag::Byte(1)),
+        ("Alpha", Tag::Byte(2)),
+        ("mid", Tag::Byte(3)),
+        ("Beta", Tag::Byte(4)),
+    ]));
+
+    let bytes = to_bytes(&nbt);
+    let parsed = round_trip(&nbt);
+    let Tag::Compound(tags) = parsed.root() else {
+        panic!("Root must be a compound");
+    };
+
+    let keys: Vec<&str> = tags.keys().map(String::as_str).collect();
+    assert_eq!(keys, ["zeta", "Alpha", "mid", "Beta"]);
+    assert_eq!(to_bytes(&parsed), bytes);
+}
+
+#[test]
+fn read_write_is_byte_identical() {
+    #[rustfmt::skip]
+    let original = vec![
+        10, 4, 0, b'r', b'o', b'o', b't', // named root compound
+        3, 1, 0, b'b', 0x01, 0x00, 0x00, 0x00, // int "b"
+        9, 1, 0, b'e', 1, 0, 0, 0, 0, // empty list "e" with the client's element type
+        1, 1, 0, b'a', 0x7F, // byte "a"
+        0,
+    ];
+
+    let nbt = Nbt::from_bytes(&mut Cursor::new(original.clone())).unwrap();
+    assert_eq!(nbt.name(), "root");
+    assert_eq!(to_bytes(&nbt), original);
+}
+
+#[test]
+fn empty_lists_keep_their_element_type() {
+    for element_type in [0, 10] {
+        #[rustfmt::skip]
+        let original = vec![
+            10, 0, 0,
+            9, 1, 0, b'e', element_type, 0, 0, 0, 0,
+            0,
+        ];
+
+        let nbt = Nbt::from_bytes(&mut Cursor::new(original.clone())).unwrap();
+        let Tag::Compound(tags) = nbt.root() else {
+            panic!("Root must be a compound");
+        };
+        assert_eq!(tags.get("e"), Some(&Tag::List(element_type, vec![])));
+        assert_eq!(to_bytes(&nbt), original);
+    }
+}
+
+#[test]
+fn list_built_empty_uses_the_client_element_type() {
+    let nbt = Nbt::new(tags(&[("e", Tag::list(vec![]))]));
+    assert_eq!(to_bytes(&nbt)[3..], [9, 1, 0, b'e', 1, 0, 0, 0, 0, 0]);
+}
+
+#[test]
+fn duplicate_keys_keep_the_first_position() {
+    #[rustfmt::skip]
+    let bytes = vec![
+        10, 0, 0,
+        1, 1, 0, b'a', 1,
+        1, 1, 0, b'b', 2,
+        1, 1, 0, b'a', 3,
+        0,
+    ];
+
+    let nbt = Nbt::from_bytes(&mut Cursor::new(bytes)).unwrap();
+    let Tag::Compound(tags) = nbt.root() else {
+        panic!("Root must be a compound");
+    };
+
+    assert_eq!(tags.len(), 2);
+    assert_eq!(tags.get_index(0), Some((&"a".to_string(), &Tag::Byte(3))));
+}
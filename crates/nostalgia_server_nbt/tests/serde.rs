@@ -0,0 +1,222 @@
+mod common;
+
+use std::io::Cursor;
+
+use nbt::{from_tag, to_tag, Compound, Nbt, Tag};
+use serde::{Deserialize, Serialize};
+
+use common::compound;
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct LevelData {
+    level_name: String,
+    random_seed: i64,
+    time: i64,
+    spawn_x: i32,
+    spawn_y: i32,
+    spawn_z: i32,
+    game_type: i32,
+    #[serde(rename = "spawnMobs")]
+    spawn_mobs: bool,
+    #[serde(rename = "dayCycleStopTime")]
+    day_cycle_stop_time: Option<i64>,
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+struct Item {
+    #[serde(rename = "Slot")]
+    slot: u8,
+    id: i16,
+    #[serde(rename = "Count")]
+    count: u8,
+    #[serde(rename = "Damage")]
+    damage: i16,
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+struct Chest {
+    id: String,
+    x: i32,
+    y: i32,
+    z: i32,
+    #[serde(rename = "Items")]
+    items: Vec<Item>,
+}
+
+#[test]
+fn level_data_from_tag() {
+    let tag = compound(&[
+        ("GameType", Tag::Int(1)),
+        ("LastPlayed", Tag::Long(1_690_000_000)),
+        ("LevelName", Tag::String("MainWorld".to_string())),
+        ("Platform", Tag::Int(2)),
+        ("RandomSeed", Tag::Long(-42)),
+        ("SpawnX", Tag::Int(128)),
+        ("SpawnY", Tag::Int(64)),
+        ("SpawnZ", Tag::Int(130)),
+        ("Time", Tag::Long(6000)),
+        ("spawnMobs", Tag::Byte(1)),
+    ]);
+
+    let level: LevelData = from_tag(tag).unwrap();
+    assert_eq!(
+        level,
+        LevelData {
+            level_name: "MainWorld".to_string(),
+            random_seed: -42,
+            time: 6000,
+            spawn_x: 128,
+            spawn_y: 64,
+            spawn_z: 130,
+            game_type: 1,
+            spawn_mobs: true,
+            day_cycle_stop_time: None,
+        }
+    );
+}
+
+#[test]
+fn missing_fields_are_errors() {
+    let tag = compound(&[("LevelName", Tag::String("MainWorld".to_string()))]);
+    assert!(from_tag::<LevelData>(tag).is_err());
+}
+
+#[test]
+fn wrong_types_are_errors() {
+    let tag = compound(&[
+        ("id", Tag::String("Chest".to_string())),
+        ("x", Tag::String("not a number".to_string())),
+        ("y", Tag::Int(0)),
+        ("z", Tag::Int(0)),
+        ("Items", Tag::list(vec![])),
+    ]);
+
+    assert!(from_tag::<Chest>(tag).is_err());
+}
+
+#[test]
+fn none_fields_are_left_out() {
+    let level = LevelData {
+        level_name: "World".to_string(),
+        random_seed: 1,
+        time: 0,
+        spawn_x: 0,
+        spawn_y: 64,
+        spawn_z: 0,
+        game_type: 0,
+        spawn_mobs: false,
+        day_cycle_stop_time: None,
+    };
+
+    let tag = to_tag(&level).unwrap();
+    assert_eq!(tag.get_byte("spawnMobs"), Some(&0));
+    assert_eq!(tag.get_long("dayCycleStopTime"), None);
+    assert_eq!(from_tag::<LevelData>(tag).unwrap(), level);
+}
+
+#[test]
+fn chest_round_trips_through_bytes() {
+    let chest = Chest {
+        id: "Chest".to_string(),
+        x: 12,
+        y: 65,
+        z: -3,
+        items: vec![
+            Item {
+                slot: 0,
+                id: 4,
+                count: 64,
+                damage: 0,
+            },
+            Item {
+                slot: 26,
+                id: 35,
+                count: 3,
+                damage: 14,
+            },
+        ],
+    };
+
+    let Tag::Compound(tags) = to_tag(&chest).unwrap() else {
+        panic!("Structs must serialize to compounds");
+    };
+    assert_eq!(tags.get("Items").map(Tag::tag_type), Some(9));
+
+    let mut cursor = Cursor::new(Vec::new());
+    Nbt::new(tags).to_bytes(&mut cursor).unwrap();
+    cursor.set_position(0);
+
+    let nbt = Nbt::from_bytes(&mut cursor).unwrap();
+    assert_eq!(from_tag::<Chest>(nbt.root().clone()).unwrap(), chest);
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+enum Action {
+    Idle,
+    Move { x: f32, z: f32 },
+    Attack(i64),
+}
+
+#[test]
+fn enums_round_trip() {
+    for action in [
+        Action::Idle,
+        Action::Move { x: 1.5, z: -2.0 },
+        Action::Attack(7),
+    ] {
+        let tag = to_tag(&action).unwrap();
+        assert_eq!(from_tag::<Action>(tag).unwrap(), action);
+    }
+
+    assert_eq!(
+        to_tag(&Action::Idle).unwrap(),
+        Tag::String("Idle".to_string())
+    );
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+struct Known {
+    #[serde(rename = "Known")]
+    known: i32,
+    #[serde(flatten)]
+    rest: Compound,
+}
+
+#[test]
+fn tags_pass_through_unchanged() {
+    let tag = compound(&[
+        ("Known", Tag::Int(1)),
+        ("Bytes", Tag::ByteArray(vec![1, 2, 3])),
+        ("Ints", Tag::IntArray(vec![4, 5])),
+        ("Longs", Tag::LongArray(vec![6])),
+        ("Shorts", Tag::list(vec![Tag::Short(7)])),
+        ("Nested", compound(&[("Float", Tag::Float(0.5))])),
+    ]);
+
+    let known: Known = from_tag(tag.clone()).unwrap();
+    assert_eq!(known.known, 1);
+    assert_eq!(known.rest.len(), 5);
+    assert_eq!(to_tag(&known).unwrap(), tag);
+}
+
+#[test]
+fn arrays_read_into_vectors() {
+    #[derive(Deserialize)]
+    struct Arrays {
+        bytes: Vec<u8>,
+        ints: Vec<i32>,
+        longs: Vec<i64>,
+    }
+
+    let tag = compound(&[
+        ("bytes", Tag::ByteArray(vec![1, 255])),
+        ("ints", Tag::IntArray(vec![-1, 2])),
+        ("longs", Tag::LongArray(vec![i64::MAX])),
+    ]);
+
+    let arrays: Arrays = from_tag(tag).unwrap();
+    assert_eq!(arrays.bytes, [1, 255]);
+    assert_eq!(arrays.ints, [-1, 2]);
+    assert_eq!(arrays.longs, [i64::MAX]);
+}
@@ -0,0 +1,229 @@
+mod common;
+
+use nbt::{Compound, Header, Nbt, NbtError, Tag};
+
+use common::compound;
+
+fn every_tag() -> Tag {
+    compound(&[
+        ("byte", Tag::Byte(200)),
+        ("short", Tag::Short(-5)),
+        ("int", Tag::Int(i32::MAX)),
+        ("long", Tag::Long(i64::MIN)),
+        ("float", Tag::Float(0.1)),
+        ("double", Tag::Double(1e300)),
+        ("byte_array", Tag::ByteArray(vec![0, 127, 128, 255])),
+        (
+            "string",
+            Tag::String("quote \" slash \\ line\nend ✓".to_string()),
+        ),
+        ("list", Tag::list(vec![Tag::Int(1), Tag::Int(2)])),
+        ("empty", Tag::list(vec![])),
+        (
+            "nested",
+            Tag::list(vec![compound(&[("a", Tag::Short(1))]), compound(&[])]),
+        ),
+        ("int_array", Tag::IntArray(vec![-1, 0, 1])),
+        ("long_array", Tag::LongArray(vec![])),
+        ("needs quotes", Tag::String("1b".to_string())),
+    ])
+}
+
+#[test]
+fn compact_and_pretty_output_parse_back() {
+    let tag = every_tag();
+
+    let compact = tag.to_string();
+    assert!(!compact.contains('\n'));
+    assert_eq!(compact.parse::<Tag>().unwrap(), tag);
+
+    let pretty = format!("{:#}", tag);
+    assert!(pretty.contains("\n    byte: -56b,\n"));
+    assert_eq!(pretty.parse::<Tag>().unwrap(), tag);
+}
+
+#[test]
+fn formats_values_with_suffixes() {
+    let tag = compound(&[
+        ("Pos", Tag::list(vec![Tag::Double(1.5), Tag::Double(64.0)])),
+        ("Health", Tag::Short(20)),
+        ("id", Tag::Int(10)),
+    ]);
+
+    assert_eq!(tag.to_string(), "{Pos: [1.5d, 64d], Health: 20s, id: 10}");
+}
+
+#[test]
+fn empty_lists_keep_their_original_type() {
+    let original = compound(&[
+        ("Entities", Tag::List(10, vec![])),
+        (
+            "Nested",
+            Tag::list(vec![compound(&[("Motion", Tag::List(6, vec![]))])]),
+        ),
+        ("Items", Tag::list(vec![Tag::Int(1)])),
+    ]);
+
+    // SNBT can't tell what the empty lists held
+    let mut tag: Tag = original.to_string().parse().unwrap();
+    assert_eq!(
+        tag.get_at(&"Entities".parse().unwrap()),
+        Some(&Tag::List(1, vec![]))
+    );
+
+    tag.keep_list_types(&original);
+    assert_eq!(tag, original);
+
+    // Lists emptied by an edit keep the type they had
+    let mut edit: Tag = "{Items: [], Entities: [1b]}".parse().unwrap();
+    edit.keep_list_types(&original);
+    assert_eq!(
+        edit,
+        compound(&[
+            ("Items", Tag::List(3, vec![])),
+            ("Entities", Tag::list(vec![Tag::Byte(1)])),
+        ])
+    );
+}
+
+#[test]
+fn floats_that_are_not_finite_parse_back() {
+    let tag = compound(&[
+        ("nan", Tag::Float(f32::NAN)),
+        ("inf", Tag::Float(f32::INFINITY)),
+        ("minus_inf", Tag::Double(f64::NEG_INFINITY)),
+        ("name", Tag::String("inf".to_string())),
+    ]);
+
+    let snbt = tag.to_string();
+    assert_eq!(
+        snbt,
+        "{nan: NaNf, inf: inff, minus_inf: -infd, name: \"inf\"}"
+    );
+
+    let parsed: Tag = snbt.parse().unwrap();
+    assert!(parsed.get_float("nan").is_some_and(|value| value.is_nan()));
+    assert_eq!(parsed.get_float("inf"), Some(&f32::INFINITY));
+    assert_eq!(parsed.get_double("minus_inf"), Some(&f64::NEG_INFINITY));
+    assert_eq!(parsed.get_string("name").map(String::as_str), Some("inf"));
+    // Without a float suffix they are still plain strings
+    assert_eq!(
+        "NaN".parse::<Tag>().unwrap(),
+        Tag::String("NaN".to_string())
+    );
+}
+
+#[test]
+fn parses_hand_written_snbt() {
+    let tag: Tag =
+        "{ OnGround: true, Motion: [0.0, -0.08, 0.0], Name: Steve, 'Item': {Count: 255b} }"
+            .parse()
+            .unwrap();
+
+    assert_eq!(
+        tag,
+        compound(&[
+            ("OnGround", Tag::Byte(1)),
+            (
+                "Motion",
+                Tag::list(vec![Tag::Double(0.0), Tag::Double(-0.08), Tag::Double(0.0)])
+            ),
+            ("Name", Tag::String("Steve".to_string())),
+            ("Item", compound(&[("Count", Tag::Byte(255))])),
+        ])
+    );
+}
+
+#[test]
+fn reports_where_parsing_failed() {
+    let error = "{a: 1, b: [1, 2s]}".parse::<Tag>().unwrap_err();
+    assert!(matches!(error, NbtError::InvalidSnbt(14, _)), "{}", error);
+
+    for invalid in [
+        "",
+        "{a 1}",
+        "{a: 1,}",
+        "[1, 2",
+        "\"unterminated",
+        "{a: 1} trailing",
+        "300b",
+        "[B; 1, 256]",
+        "[I; 1.5]",
+    ] {
+        assert!(
+            matches!(invalid.parse::<Tag>(), Err(NbtError::InvalidSnbt(..))),
+            "{:?} should not parse",
+            invalid
+        );
+    }
+}
+
+#[test]
+fn rejects_deeply_nested_snbt() {
+    let snbt = "[".repeat(1000) + &"]".repeat(1000);
+    assert!(matches!(
+        snbt.parse::<Tag>(),
+        Err(NbtError::DepthLimitExceeded)
+    ));
+}
+
+fn level() -> Nbt {
+    let mut tags = Compound::new();
+    tags.insert("LevelName".to_string(), Tag::String("World".to_string()));
+    Nbt::new(tags)
+}
+
+#[test]
+fn level_header_round_trips() {
+    let bytes = level().to_pocket_file(Header::Level(3)).unwrap();
+    assert_eq!(&bytes[..4], [3, 0, 0, 0]);
+    assert_eq!(
+        i32::from_le_bytes(bytes[4..8].try_into().unwrap()) as usize,
+        bytes.len() - 8
+    );
+
+    let (nbt, header) = Nbt::from_pocket_file(bytes).unwrap();
+    assert_eq!(header, Header::Level(3));
+    assert_eq!(nbt.root(), level().root());
+}
+
+#[test]
+fn entities_header_round_trips() {
+    let bytes = level().to_pocket_file(Header::Entities(1)).unwrap();
+    assert_eq!(&bytes[..8], b"ENT\0\x01\0\0\0");
+    assert_eq!(
+        u32::from_le_bytes(bytes[8..12].try_into().unwrap()) as usize,
+        bytes.len() - 12
+    );
+
+    let (nbt, header) = Nbt::from_pocket_file(bytes).unwrap();
+    assert_eq!(header, Header::Entities(1));
+    assert_eq!(nbt.root(), level().root());
+}
+
+#[test]
+fn level_header_allows_padding_after_the_nbt() {
+    let mut bytes = level().to_pocket_file(Header::Level(3)).unwrap();
+    bytes.extend([0; 16]);
+
+    assert_eq!(Header::detect(&bytes), Header::Level(3));
+    let (nbt, _) = Nbt::from_pocket_file(bytes).unwrap();
+    assert_eq!(nbt.root(), level().root());
+}
+
+#[test]
+fn bare_nbt_has_no_header() {
+    let bytes = level().to_pocket_file(Header::None).unwrap();
+    assert_eq!(Header::detect(&bytes), Header::None);
+    assert_eq!(Nbt::from_pocket_file(bytes).unwrap().1, Header::None);
+}
+
+#[test]
+fn truncated_entities_file_is_an_error() {
+    let mut bytes = level().to_pocket_file(Header::Entities(1)).unwrap();
+    bytes.truncate(bytes.len() - 4);
+    assert!(matches!(
+        Nbt::from_pocket_file(bytes),
+        Err(NbtError::InvalidLength(_))
+    ));
+}
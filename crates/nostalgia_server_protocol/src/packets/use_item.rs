@@ -7,6 +7,7 @@ pub struct UseItem {
     pub x: i32,
     pub y: i32,
     pub z: i32,
+    pub face: i32,
     pub block: u16,
     pub meta: u8,
     pub id: i32,
@@ -20,9 +21,10 @@ impl UseItem {
             x: reader::read_i32(&mut cursor)?,
             y: reader::read_i32(&mut cursor)?,
             z: reader::read_i32(&mut cursor)?,
+            face: reader::read_i32(&mut cursor)?,
             block: reader::read_u16(&mut cursor)?,
             meta: reader::read_u8(&mut cursor)?,
-            id: reader::read_i32_le(&mut cursor)?,
+            id: reader::read_i32(&mut cursor)?,
             f_pos: reader::read_vector3(&mut cursor)?,
             pos: reader::read_vector3(&mut cursor)?,
         })
@@ -33,6 +35,7 @@ impl UseItem {
         writer::write_i32(&mut cursor, self.x)?;
         writer::write_i32(&mut cursor, self.y)?;
         writer::write_i32(&mut cursor, self.z)?;
+        writer::write_i32(&mut cursor, self.face)?;
         writer::write_u16(&mut cursor, self.block)?;
         writer::write_u8(&mut cursor, self.meta)?;
         writer::write_i32(&mut cursor, self.id)?;
@@ -212,21 +212,17 @@ fn load_world_file(
     let world_path = PathBuf::from("assets/MainWorld");
     let world = world::World::from_file(world_path).expect("Failed to load the world");
 
-    info!("Seed: {}", world.seed);
+    info!("Seed: {}", world.level.seed);
 
-    for (chunk_index, chunk) in world.chunks.iter().enumerate() {
-        let chunk_x = chunk_index % 16;
-        let chunk_z = chunk_index / 16;
+    for (position, chunk) in world.chunks() {
+        let chunk_x = position.x;
+        let chunk_z = position.z;
 
         let corner_chunks: [Option<&world::Chunk>; 4] = [
-            world
-                .chunks
-                .get((chunk_x.checked_sub(1).unwrap_or(0xFFF)) + (chunk_z * 16)),
-            world.chunks.get((chunk_x + 1) + (chunk_z * 16)),
-            world
-                .chunks
-                .get(chunk_x + ((chunk_z.checked_sub(1).unwrap_or(0xFFF)) * 16)),
-            world.chunks.get(chunk_x + ((chunk_z + 1) * 16)),
+            world.chunk_at(chunk_x - 1, chunk_z),
+            world.chunk_at(chunk_x + 1, chunk_z),
+            world.chunk_at(chunk_x, chunk_z - 1),
+            world.chunk_at(chunk_x, chunk_z + 1),
         ];
 
         let chunk_mesh = mesher::build_cube_meshes(chunk, corner_chunks);
@@ -29,28 +29,10 @@ struct Block(world::Block);
 
 impl Voxel for Block {
     fn get_visibility(&self) -> VoxelVisibility {
-        const TRANSPARENT_BLOCKS: &[BlockID] = &[
-            BlockID::Water,
-            BlockID::StillWater,
-            BlockID::Torch,
-            BlockID::Leaves,
-            BlockID::PotatoBlock,
-            BlockID::WheatBlock,
-            BlockID::BrownMushroom,
-            BlockID::RedMushroom,
-            BlockID::SignPost,
-            BlockID::DeadBush,
-            BlockID::Flower,
-            BlockID::Dandelion,
-            BlockID::BeetrootBlock,
-            BlockID::CarrotBlock,
-            BlockID::Cactus,
-        ];
-
-        if TRANSPARENT_BLOCKS.contains(&self.0.id) {
-            VoxelVisibility::Translucent
-        } else if self.0.id == BlockID::Air {
+        if self.0.id == BlockID::Air {
             VoxelVisibility::Empty
+        } else if self.0.id.properties().transparent {
+            VoxelVisibility::Translucent
         } else {
             VoxelVisibility::Opaque
         }
@@ -0,0 +1,291 @@
+use std::{
+    error::Error,
+    path::{Path, PathBuf},
+    process::ExitCode,
+};
+
+use world::{
+    diff_worlds, ChunkPos, FlatGenerator, Generator, JavaImportOptions, LevelData,
+    TerrainGenerator, VoidGenerator, World, WorldCheck, WorldStats, ORES, ORE_LAYER_HEIGHT,
+};
+
+const USAGE: &str = "\
+Usage:
+    world import-java <java world> <output> [<chunk x> <chunk z> [<min y>]]
+                                 Convert a Java Edition world from before 1.13
+    world to-leveldb <world> <output>
+                                 Convert a world to the LevelDB format of newer clients
+    world from-leveldb <world> <output>
+                                 Convert a LevelDB world back to chunks.dat
+    world fsck <world> [--repair blank|regenerate] [--generator <generator>]
+               [--output <output>]
+                                 Check a world for damage, and optionally repair it
+    world stats <world>          Count the blocks, ores by height and entities of a world
+    world diff <before> <after>  List the blocks and entities that changed between saves
+
+Repairs replace damaged chunks with empty or freshly generated ones, turn unknown
+blocks into air and drop entities outside of the world. The repaired world is saved
+to <output>, or over the damaged one after copying its files to *.bak. Regenerating
+needs the generator the world was made with: default, flat[=<layers>] or void.
+
+Java worlds are cropped to 16x16 chunks starting at the given chunk, or centered on
+their spawn, and to the 128 blocks starting at <min y>.";
+
+fn parse<T: std::str::FromStr>(value: &str, name: &str) -> Result<T, Box<dyn Error>> {
+    value
+        .parse()
+        .map_err(|_| format!("Invalid {}: {}", name, value).into())
+}
+
+fn import_java(
+    input: &str,
+    output: &str,
+    options: JavaImportOptions,
+) -> Result<(), Box<dyn Error>> {
+    let world = World::import_java(&PathBuf::from(input), &options)
+        .map_err(|error| format!("{}: {}", input, error))?;
+
+    std::fs::create_dir_all(output)?;
+    world.save(PathBuf::from(output))?;
+    println!(
+        "Imported \"{}\" with its spawn at {:?}",
+        world.level.name,
+        world.level.spawn_position()
+    );
+    Ok(())
+}
+
+fn to_leveldb(input: &str, output: &str) -> Result<(), Box<dyn Error>> {
+    let world =
+        World::from_file(PathBuf::from(input)).map_err(|error| format!("{}: {}", input, error))?;
+    world.save_leveldb(Path::new(output))?;
+    println!("Converted \"{}\" to LevelDB", world.level.name);
+    Ok(())
+}
+
+fn from_leveldb(input: &str, output: &str) -> Result<(), Box<dyn Error>> {
+    let world = World::from_leveldb_file(Path::new(input))
+        .map_err(|error| format!("{}: {}", input, error))?;
+    world.save(PathBuf::from(output))?;
+    println!("Converted \"{}\" to chunks.dat", world.level.name);
+    Ok(())
+}
+
+/// Generator a world was made with, named like the server's `level-type`.
+enum GeneratorKind {
+    Default,
+    /// Flat layers, see [`FlatGenerator::from_layers`].
+    Flat(Option<String>),
+    Void,
+}
+
+impl GeneratorKind {
+    fn parse(value: &str) -> Result<Self, Box<dyn Error>> {
+        Ok(match value.split_once('=') {
+            None if value == "default" => GeneratorKind::Default,
+            None if value == "flat" => GeneratorKind::Flat(None),
+            Some(("flat", layers)) => GeneratorKind::Flat(Some(layers.to_string())),
+            None if value == "void" => GeneratorKind::Void,
+            _ => return Err(format!("Invalid generator: {}", value).into()),
+        })
+    }
+
+    fn create(&self, level: &LevelData) -> Result<Box<dyn Generator>, Box<dyn Error>> {
+        Ok(match self {
+            GeneratorKind::Default => Box::new(TerrainGenerator::new(level.seed)),
+            GeneratorKind::Flat(None) => Box::new(FlatGenerator::default()),
+            GeneratorKind::Flat(Some(layers)) => Box::new(FlatGenerator::from_layers(layers)?),
+            GeneratorKind::Void => Box::new(VoidGenerator::new(level.spawn_position())),
+        })
+    }
+}
+
+/// What damaged chunks are replaced with when repairing.
+enum RepairMode {
+    /// Empty chunks, leaving holes in the world.
+    Blank,
+    /// The chunks the given generator makes from the world's seed.
+    Regenerate(GeneratorKind),
+}
+
+/// Copies the files of a world to `<name>.bak` before they're overwritten.
+fn back_up(path: &Path) -> Result<(), Box<dyn Error>> {
+    for name in ["chunks.dat", "level.dat", "entities.dat"] {
+        let file = path.join(name);
+        if file.exists() {
+            std::fs::copy(&file, path.join(format!("{}.bak", name)))?;
+        }
+    }
+
+    Ok(())
+}
+
+fn fsck(
+    input: &str,
+    repair: Option<RepairMode>,
+    output: Option<&str>,
+) -> Result<(), Box<dyn Error>> {
+    let check = WorldCheck::run(Path::new(input));
+    for issue in &check.issues {
+        println!("{}", issue);
+    }
+
+    if check.is_clean() {
+        println!("No problems found");
+        return Ok(());
+    }
+
+    let count = check.issues.len();
+    let Some(mode) = repair else {
+        return Err(format!("{} problems found", count).into());
+    };
+
+    let generator = match (&mode, check.level()) {
+        (RepairMode::Regenerate(kind), Some(level)) => Some(kind.create(level)?),
+        _ => None,
+    };
+    let world = check.repair(generator.as_deref())?;
+
+    let output = match output {
+        Some(output) => PathBuf::from(output),
+        None => {
+            back_up(Path::new(input))?;
+            PathBuf::from(input)
+        }
+    };
+    world.save(output.clone())?;
+    println!("Repaired {} problems into {}", count, output.display());
+    Ok(())
+}
+
+fn load(path: &str) -> Result<World, Box<dyn Error>> {
+    Ok(World::from_file(PathBuf::from(path)).map_err(|error| format!("{}: {}", path, error))?)
+}
+
+fn stats(input: &str) -> Result<(), Box<dyn Error>> {
+    let stats = WorldStats::collect(&load(input)?);
+    let total: usize = stats.blocks.iter().sum();
+    println!("{} chunks, {} blocks", stats.chunks, total);
+
+    println!("\nBlocks:");
+    for (id, count) in stats.block_histogram() {
+        let share = count as f64 / total as f64;
+        println!(
+            "  {:>3} {:<24} {:>9} {:>6.2}% {}",
+            id.0,
+            id.properties().name,
+            count,
+            share * 100.0,
+            "#".repeat((share * 50.0).ceil() as usize)
+        );
+    }
+
+    println!("\nOres by height:");
+    print!("  {:<9}", "y");
+    for ore in ORES {
+        print!(" {:>13}", ore.properties().name);
+    }
+    println!();
+
+    for layer in (0..stats.ores[0].len()).rev() {
+        let bottom = layer * ORE_LAYER_HEIGHT;
+        print!(
+            "  {:<9}",
+            format!("{}-{}", bottom, bottom + ORE_LAYER_HEIGHT - 1)
+        );
+        for counts in &stats.ores {
+            print!(" {:>13}", counts[layer]);
+        }
+        println!();
+    }
+
+    for (title, counts) in [
+        ("Entities", &stats.entities),
+        ("Tile entities", &stats.tile_entities),
+    ] {
+        println!("\n{}: {}", title, counts.values().sum::<usize>());
+        for (name, count) in counts {
+            println!("  {:<24} {:>9}", name, count);
+        }
+    }
+
+    Ok(())
+}
+
+fn diff(before: &str, after: &str) -> Result<(), Box<dyn Error>> {
+    let changes = diff_worlds(&load(before)?, &load(after)?);
+    for change in &changes {
+        println!("{}", change);
+    }
+
+    println!("{} changes", changes.len());
+    Ok(())
+}
+
+fn run(args: &[String]) -> Result<(), Box<dyn Error>> {
+    match args {
+        [command, input, output] if command == "import-java" => {
+            import_java(input, output, JavaImportOptions::default())?;
+        }
+        [command, input, output, x, z, rest @ ..]
+            if command == "import-java" && rest.len() <= 1 =>
+        {
+            let options = JavaImportOptions {
+                origin: Some(ChunkPos::new(parse(x, "chunk x")?, parse(z, "chunk z")?)),
+                min_y: match rest {
+                    [min_y] => parse(min_y, "min y")?,
+                    _ => 0,
+                },
+            };
+            import_java(input, output, options)?;
+        }
+        [command, input, output] if command == "to-leveldb" => to_leveldb(input, output)?,
+        [command, input, output] if command == "from-leveldb" => from_leveldb(input, output)?,
+        [command, input] if command == "stats" => stats(input)?,
+        [command, before, after] if command == "diff" => diff(before, after)?,
+        [command, input, options @ ..] if command == "fsck" => {
+            let (mut repair, mut generator, mut output) = (None, None, None);
+            for option in options.chunks(2) {
+                match option {
+                    [flag, value] if flag == "--repair" => repair = Some(value.as_str()),
+                    [flag, value] if flag == "--generator" => {
+                        generator = Some(GeneratorKind::parse(value)?)
+                    }
+                    [flag, value] if flag == "--output" => output = Some(value.as_str()),
+                    _ => return Err(USAGE.into()),
+                }
+            }
+
+            // Guessing the generator would fill the holes with the wrong terrain
+            let mode = match (repair, generator) {
+                (None, None) => None,
+                (Some("blank"), None) => Some(RepairMode::Blank),
+                (Some("regenerate"), Some(generator)) => Some(RepairMode::Regenerate(generator)),
+                (Some("regenerate"), None) => {
+                    return Err("--repair regenerate needs --generator".into())
+                }
+                (_, Some(_)) => return Err("--generator needs --repair regenerate".into()),
+                (Some(mode), None) => return Err(format!("Invalid repair mode: {}", mode).into()),
+            };
+
+            if output.is_some() && mode.is_none() {
+                return Err("--output needs --repair".into());
+            }
+            fsck(input, mode, output)?;
+        }
+        _ => return Err(USAGE.into()),
+    }
+
+    Ok(())
+}
+
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    match run(&args) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(error) => {
+            eprintln!("{}", error);
+            ExitCode::FAILURE
+        }
+    }
+}
@@ -1,112 +1,148 @@
-#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Default)]
-#[repr(u8)]
-pub enum BlockID {
-    #[default]
-    Air = 0,
-    Stone = 1,
-    Grass = 2,
-    Dirt = 3,
-    Cobblestone = 4,
-    WoodenPlanks = 5,
-    Sapling = 6,
-    Bedrock = 7,
-    Water = 8,
-    StillWater = 9,
-    Lava = 10,
-    StillLava = 11,
-    Sand = 12,
-    Gravel = 13,
-    GoldOre = 14,
-    IronOre = 15,
-    CoalOre = 16,
-    Wood = 17,
-    Leaves = 18,
-    Sponge = 19,
-    Glass = 20,
-    LapisOre = 21,
-    LapisBlock = 22,
-    Sandstone = 24,
-    BedBlock = 26,
-    Cobweb = 30,
-    TallGrass = 31,
-    DeadBush = 32,
-    Wool = 35,
-    Dandelion = 37,
-    Flower = 38,
-    BrownMushroom = 39,
-    RedMushroom = 40,
-    GoldBlock = 41,
-    IronBlock = 42,
-    DoubleSlabs = 43,
-    Slab = 44,
-    Bricks = 45,
-    Tnt = 46,
-    Bookshelf = 47,
-    MossyStone = 48,
-    Obsidian = 49,
-    Torch = 50,
-    Fire = 51,
-    WoodenStairs = 53,
-    Chest = 54,
-    DiamondOre = 56,
-    DiamondBlock = 57,
-    CraftingTable = 58,
-    WheatBlock = 59,
-    Farmland = 60,
-    Furnace = 61,
-    LitFurnace = 62,
-    SignPost = 63,
-    WoodenDoorBlock = 64,
-    Ladder = 65,
-    CobblestoneStairs = 67,
-    WallSign = 68,
-    IronDoorBlock = 71,
-    RedstoneOre = 73,
-    GlowingRedstoneOre = 74,
-    Snow = 78,
-    Ice = 79,
-    SnowBlock = 80,
-    Cactus = 81,
-    ClayBlock = 82,
-    SugarcaneBlock = 83,
-    Fence = 85,
-    Pumpkin = 86,
-    Netherrack = 87,
-    SoulSand = 88,
-    GlowStoneBlock = 89,
-    JackOLantern = 91,
-    CakeBlock = 92,
-    Unknown = 95,
-    Trapdoor = 96,
-    StoneBrick = 98,
-    IronBars = 101,
-    GlassPane = 102,
-    MelonBlock = 103,
-    PumpkinStem = 104,
-    MelonStem = 105,
-    FenceGate = 107,
-    BrickStairs = 108,
-    StoneBrickStairs = 109,
-    NetherBrick = 112,
-    NetherBrickStairs = 114,
-    SandstoneStairs = 128,
-    SpruceWoodenStairs = 134,
-    BirchWoodenStairs = 135,
-    JungleWoodenStairs = 136,
-    StoneWall = 139,
-    CarrotBlock = 141,
-    PotatoBlock = 142,
-    QuartzBlock = 155,
-    QuartzStairs = 156,
-    DoubleWoodenSlab = 157,
-    WoodenSlab = 158,
-    HayBale = 170,
-    Carpet = 171,
-    CoalBlock = 173,
-    BeetrootBlock = 244,
-    StoneCutter = 245,
-    GlowingObsidian = 246,
-    NetherReactor = 247,
+use std::fmt;
+
+use crate::{BlockProperties, BLOCK_REGISTRY};
+
+/// Raw block id as stored in chunks. Every value is valid, ids without a name here are
+/// kept as they are so worlds using them round-trip.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct BlockID(pub u8);
+
+#[allow(non_upper_case_globals)]
+impl BlockID {
+    pub const Air: Self = Self(0);
+    pub const Stone: Self = Self(1);
+    pub const Grass: Self = Self(2);
+    pub const Dirt: Self = Self(3);
+    pub const Cobblestone: Self = Self(4);
+    pub const WoodenPlanks: Self = Self(5);
+    pub const Sapling: Self = Self(6);
+    pub const Bedrock: Self = Self(7);
+    pub const Water: Self = Self(8);
+    pub const StillWater: Self = Self(9);
+    pub const Lava: Self = Self(10);
+    pub const StillLava: Self = Self(11);
+    pub const Sand: Self = Self(12);
+    pub const Gravel: Self = Self(13);
+    pub const GoldOre: Self = Self(14);
+    pub const IronOre: Self = Self(15);
+    pub const CoalOre: Self = Self(16);
+    pub const Wood: Self = Self(17);
+    pub const Leaves: Self = Self(18);
+    pub const Sponge: Self = Self(19);
+    pub const Glass: Self = Self(20);
+    pub const LapisOre: Self = Self(21);
+    pub const LapisBlock: Self = Self(22);
+    pub const Sandstone: Self = Self(24);
+    pub const BedBlock: Self = Self(26);
+    pub const Cobweb: Self = Self(30);
+    pub const TallGrass: Self = Self(31);
+    pub const DeadBush: Self = Self(32);
+    pub const Wool: Self = Self(35);
+    pub const Dandelion: Self = Self(37);
+    pub const Flower: Self = Self(38);
+    pub const BrownMushroom: Self = Self(39);
+    pub const RedMushroom: Self = Self(40);
+    pub const GoldBlock: Self = Self(41);
+    pub const IronBlock: Self = Self(42);
+    pub const DoubleSlabs: Self = Self(43);
+    pub const Slab: Self = Self(44);
+    pub const Bricks: Self = Self(45);
+    pub const Tnt: Self = Self(46);
+    pub const Bookshelf: Self = Self(47);
+    pub const MossyStone: Self = Self(48);
+    pub const Obsidian: Self = Self(49);
+    pub const Torch: Self = Self(50);
+    pub const Fire: Self = Self(51);
+    pub const WoodenStairs: Self = Self(53);
+    pub const Chest: Self = Self(54);
+    pub const DiamondOre: Self = Self(56);
+    pub const DiamondBlock: Self = Self(57);
+    pub const CraftingTable: Self = Self(58);
+    pub const WheatBlock: Self = Self(59);
+    pub const Farmland: Self = Self(60);
+    pub const Furnace: Self = Self(61);
+    pub const LitFurnace: Self = Self(62);
+    pub const SignPost: Self = Self(63);
+    pub const WoodenDoorBlock: Self = Self(64);
+    pub const Ladder: Self = Self(65);
+    pub const CobblestoneStairs: Self = Self(67);
+    pub const WallSign: Self = Self(68);
+    pub const IronDoorBlock: Self = Self(71);
+    pub const RedstoneOre: Self = Self(73);
+    pub const GlowingRedstoneOre: Self = Self(74);
+    pub const Snow: Self = Self(78);
+    pub const Ice: Self = Self(79);
+    pub const SnowBlock: Self = Self(80);
+    pub const Cactus: Self = Self(81);
+    pub const ClayBlock: Self = Self(82);
+    pub const SugarcaneBlock: Self = Self(83);
+    pub const Fence: Self = Self(85);
+    pub const Pumpkin: Self = Self(86);
+    pub const Netherrack: Self = Self(87);
+    pub const SoulSand: Self = Self(88);
+    pub const GlowStoneBlock: Self = Self(89);
+    pub const JackOLantern: Self = Self(91);
+    pub const CakeBlock: Self = Self(92);
+    pub const Unknown: Self = Self(95);
+    pub const Trapdoor: Self = Self(96);
+    pub const StoneBrick: Self = Self(98);
+    pub const IronBars: Self = Self(101);
+    pub const GlassPane: Self = Self(102);
+    pub const MelonBlock: Self = Self(103);
+    pub const PumpkinStem: Self = Self(104);
+    pub const MelonStem: Self = Self(105);
+    pub const FenceGate: Self = Self(107);
+    pub const BrickStairs: Self = Self(108);
+    pub const StoneBrickStairs: Self = Self(109);
+    pub const NetherBrick: Self = Self(112);
+    pub const NetherBrickStairs: Self = Self(114);
+    pub const SandstoneStairs: Self = Self(128);
+    pub const SpruceWoodenStairs: Self = Self(134);
+    pub const BirchWoodenStairs: Self = Self(135);
+    pub const JungleWoodenStairs: Self = Self(136);
+    pub const StoneWall: Self = Self(139);
+    pub const CarrotBlock: Self = Self(141);
+    pub const PotatoBlock: Self = Self(142);
+    pub const QuartzBlock: Self = Self(155);
+    pub const QuartzStairs: Self = Self(156);
+    pub const DoubleWoodenSlab: Self = Self(157);
+    pub const WoodenSlab: Self = Self(158);
+    pub const HayBale: Self = Self(170);
+    pub const Carpet: Self = Self(171);
+    pub const CoalBlock: Self = Self(173);
+    pub const BeetrootBlock: Self = Self(244);
+    pub const StoneCutter: Self = Self(245);
+    pub const GlowingObsidian: Self = Self(246);
+    pub const NetherReactor: Self = Self(247);
+}
+
+impl BlockID {
+    pub fn properties(self) -> &'static BlockProperties {
+        BLOCK_REGISTRY.get(self)
+    }
+
+    /// Blocks that a placed block is allowed to overwrite.
+    pub fn is_replaceable(&self) -> bool {
+        self.properties().replaceable
+    }
+}
+
+impl From<u8> for BlockID {
+    fn from(id: u8) -> Self {
+        Self(id)
+    }
+}
+
+impl From<BlockID> for u8 {
+    fn from(id: BlockID) -> Self {
+        id.0
+    }
+}
+
+impl fmt::Debug for BlockID {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}({})", self.properties().name, self.0)
+    }
 }
 
 #[derive(Clone, Copy, Debug, Default)]
@@ -0,0 +1,339 @@
+use std::{
+    collections::{BTreeSet, HashMap},
+    fmt, fs, io,
+    path::Path,
+};
+
+use nbt::{Header, Nbt, Tag, ENTITIES_VERSION};
+
+use crate::{
+    generate_chunks, invalid_data, schematic::tile_entity_position, BlockID, Chunk, ChunkPos,
+    Generator, LevelData, LocationProblem, LocationTable, World, BLOCK_REGISTRY, CHUNK_SIZE_X,
+    CHUNK_SIZE_Y, CHUNK_SIZE_Z, SECTOR_SIZE, WORLD_SIZE_X, WORLD_SIZE_Z,
+};
+
+/// Something wrong with a saved world.
+#[derive(Clone, Debug, PartialEq)]
+pub enum WorldIssue {
+    /// `level.dat` can't be read.
+    BadLevel(String),
+    /// `entities.dat` can't be read, its entities are lost.
+    BadEntities(String),
+    /// `chunks.dat` is missing or too short to hold its location table.
+    BadChunkFile(String),
+    Location(LocationProblem),
+    /// The chunk record can't be read, usually because of a bad header.
+    BadChunk {
+        position: ChunkPos,
+        error: String,
+    },
+    /// The chunk holds blocks the client doesn't know, with how many of each.
+    UnknownBlocks {
+        position: ChunkPos,
+        blocks: Vec<(BlockID, usize)>,
+    },
+    /// An entity is outside of the world or has no valid position, by index in
+    /// `entities.dat`.
+    EntityOutOfRange {
+        index: usize,
+        position: [f64; 3],
+    },
+    TileEntityOutOfRange {
+        index: usize,
+        position: (i32, i32, i32),
+    },
+}
+
+impl fmt::Display for WorldIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::BadLevel(error) => write!(f, "level.dat: {}", error),
+            Self::BadEntities(error) => write!(f, "entities.dat: {}", error),
+            Self::BadChunkFile(error) => write!(f, "chunks.dat: {}", error),
+            Self::Location(problem) => write!(f, "{}", problem),
+            Self::BadChunk { position, error } => {
+                write!(f, "Chunk {}, {}: {}", position.x, position.z, error)
+            }
+            Self::UnknownBlocks { position, blocks } => {
+                write!(f, "Chunk {}, {} has unknown blocks", position.x, position.z)?;
+                for (id, count) in blocks {
+                    write!(f, " {}x{}", count, id.0)?;
+                }
+
+                Ok(())
+            }
+            Self::EntityOutOfRange { index, position } => {
+                write!(f, "Entity {} is out of the world at {:?}", index, position)
+            }
+            Self::TileEntityOutOfRange { index, position } => write!(
+                f,
+                "Tile entity {} is out of the world at {:?}",
+                index, position
+            ),
+        }
+    }
+}
+
+/// The result of scanning a world's files, keeping what could be read for repairs.
+pub struct WorldCheck {
+    pub issues: Vec<WorldIssue>,
+    level: Option<LevelData>,
+    chunks: HashMap<ChunkPos, Chunk>,
+    entities: Vec<Tag>,
+    tile_entities: Vec<Tag>,
+}
+
+fn read_level(path: &Path) -> io::Result<LevelData> {
+    LevelData::from_bytes(fs::read(path.join("level.dat"))?)
+}
+
+fn read_entities(path: &Path) -> io::Result<(Vec<Tag>, Vec<Tag>)> {
+    let (nbt, header) = Nbt::from_pocket_file(fs::read(path.join("entities.dat"))?)?;
+    if header != Header::Entities(ENTITIES_VERSION) {
+        return Err(invalid_data(format!(
+            "entities.dat has header {:?}, expected version {}",
+            header, ENTITIES_VERSION
+        )));
+    }
+
+    let list = |name: &str| nbt.root().get_list(name).cloned().unwrap_or_default();
+    Ok((list("Entities"), list("TileEntities")))
+}
+
+fn entity_position(tag: &Tag) -> Option<[f64; 3]> {
+    let position = tag.get_list("Pos")?;
+    let mut coordinates = [0.0; 3];
+    for (coordinate, tag) in coordinates.iter_mut().zip(position) {
+        *coordinate = match tag {
+            Tag::Float(value) => *value as f64,
+            Tag::Double(value) => *value,
+            _ => return None,
+        };
+    }
+
+    (position.len() == 3).then_some(coordinates)
+}
+
+fn is_entity_in_range([x, y, z]: [f64; 3]) -> bool {
+    (0.0..WORLD_SIZE_X as f64).contains(&x)
+        && (0.0..WORLD_SIZE_Z as f64).contains(&z)
+        // Entities may be above the highest block, but never below the world
+        && y >= 0.0
+}
+
+fn unknown_blocks(chunk: &Chunk) -> Vec<(BlockID, usize)> {
+    let mut counts = HashMap::new();
+    for x in 0..CHUNK_SIZE_X {
+        for z in 0..CHUNK_SIZE_Z {
+            for y in 0..CHUNK_SIZE_Y {
+                let id = chunk.block_id(x, y, z);
+                if !BLOCK_REGISTRY.is_registered(id) {
+                    *counts.entry(id.0).or_insert(0) += 1;
+                }
+            }
+        }
+    }
+
+    let mut blocks: Vec<(BlockID, usize)> = counts
+        .into_iter()
+        .map(|(id, count)| (BlockID(id), count))
+        .collect();
+    blocks.sort_by_key(|(id, _)| id.0);
+    blocks
+}
+
+impl WorldCheck {
+    /// Reads every file of the world at `path`, collecting everything wrong with it.
+    pub fn run(path: &Path) -> Self {
+        let mut check = Self {
+            issues: Vec::new(),
+            level: None,
+            chunks: HashMap::new(),
+            entities: Vec::new(),
+            tile_entities: Vec::new(),
+        };
+
+        match read_level(path) {
+            Ok(level) => check.level = Some(level),
+            Err(error) => check.issues.push(WorldIssue::BadLevel(error.to_string())),
+        }
+
+        match read_entities(path) {
+            Ok((entities, tile_entities)) => {
+                check.entities = entities;
+                check.tile_entities = tile_entities;
+            }
+            Err(error) => check
+                .issues
+                .push(WorldIssue::BadEntities(error.to_string())),
+        }
+
+        for (index, entity) in check.entities.iter().enumerate() {
+            let position = entity_position(entity);
+            if !position.is_some_and(is_entity_in_range) {
+                check.issues.push(WorldIssue::EntityOutOfRange {
+                    index,
+                    position: position.unwrap_or([f64::NAN; 3]),
+                });
+            }
+        }
+
+        for (index, tile_entity) in check.tile_entities.iter().enumerate() {
+            let position = tile_entity_position(tile_entity);
+            if !position.is_some_and(|(x, y, z)| World::is_in_bounds(x, y, z)) {
+                check.issues.push(WorldIssue::TileEntityOutOfRange {
+                    index,
+                    position: position.unwrap_or((i32::MIN, i32::MIN, i32::MIN)),
+                });
+            }
+        }
+
+        match fs::read(path.join("chunks.dat")) {
+            Ok(bytes) if bytes.len() >= SECTOR_SIZE => check.check_chunks(&bytes),
+            Ok(_) => check.issues.push(WorldIssue::BadChunkFile(
+                "Too short to hold the location table".to_string(),
+            )),
+            Err(error) => check
+                .issues
+                .push(WorldIssue::BadChunkFile(error.to_string())),
+        }
+
+        check
+    }
+
+    fn check_chunks(&mut self, bytes: &[u8]) {
+        let table = match LocationTable::read(&bytes[..SECTOR_SIZE], bytes.len()) {
+            Ok(table) => table,
+            Err(error) => {
+                return self
+                    .issues
+                    .push(WorldIssue::BadChunkFile(error.to_string()))
+            }
+        };
+
+        self.issues
+            .extend(table.problems.into_iter().map(WorldIssue::Location));
+
+        for location in table.locations {
+            let record = bytes[location.byte_range()].to_vec();
+            let chunk = match Chunk::from_bytes(record) {
+                Ok(chunk) => chunk,
+                Err(error) => {
+                    self.issues.push(WorldIssue::BadChunk {
+                        position: location.position,
+                        error: error.to_string(),
+                    });
+                    continue;
+                }
+            };
+
+            let blocks = unknown_blocks(&chunk);
+            if !blocks.is_empty() {
+                self.issues.push(WorldIssue::UnknownBlocks {
+                    position: location.position,
+                    blocks,
+                });
+            }
+
+            self.chunks.insert(location.position, chunk);
+        }
+    }
+
+    pub fn is_clean(&self) -> bool {
+        self.issues.is_empty()
+    }
+
+    /// The world's `level.dat`, if it could be read.
+    pub fn level(&self) -> Option<&LevelData> {
+        self.level.as_ref()
+    }
+
+    /// Chunks which are missing or whose data can't be trusted.
+    fn damaged_chunks(&self) -> BTreeSet<ChunkPos> {
+        let mut damaged: BTreeSet<ChunkPos> = self
+            .issues
+            .iter()
+            .filter_map(|issue| match issue {
+                WorldIssue::Location(LocationProblem::Missing(position)) => Some(*position),
+                WorldIssue::Location(LocationProblem::Truncated(location)) => {
+                    Some(location.position)
+                }
+                WorldIssue::Location(LocationProblem::Overlapping { location, .. }) => {
+                    Some(location.position)
+                }
+                WorldIssue::BadChunk { position, .. } => Some(*position),
+                _ => None,
+            })
+            .collect();
+
+        if self.chunks.is_empty() {
+            damaged.extend(ChunkPos::all());
+        }
+
+        damaged
+    }
+
+    /// Builds a world without the issues found: damaged chunks are replaced by the ones
+    /// the generator makes, or by empty chunks without one, unknown blocks become air,
+    /// and entities out of the world are dropped. Fails if `level.dat` is unreadable, as
+    /// the world can't be rebuilt without its seed.
+    pub fn repair(self, generator: Option<&dyn Generator>) -> io::Result<World> {
+        let damaged = self.damaged_chunks();
+        let Self {
+            level,
+            mut chunks,
+            entities,
+            tile_entities,
+            ..
+        } = self;
+
+        let level = level.ok_or_else(|| {
+            invalid_data("level.dat is unreadable, restore it from a backup before repairing")
+        })?;
+
+        // Only the damaged chunks are generated, not the whole world
+        let positions: Vec<ChunkPos> = damaged.iter().copied().filter(ChunkPos::is_valid).collect();
+        let mut generated = match generator {
+            Some(generator) => generate_chunks(level.seed, generator, &positions),
+            None => HashMap::new(),
+        };
+
+        for position in damaged {
+            chunks.remove(&position);
+            if position.is_valid() {
+                let chunk = generated.remove(&position).unwrap_or_else(Chunk::new);
+                chunks.insert(position, chunk);
+            }
+        }
+
+        for chunk in chunks.values_mut() {
+            for x in 0..CHUNK_SIZE_X {
+                for z in 0..CHUNK_SIZE_Z {
+                    for y in 0..CHUNK_SIZE_Y {
+                        if !BLOCK_REGISTRY.is_registered(chunk.block_id(x, y, z)) {
+                            chunk.set_block_id(x, y, z, BlockID::Air);
+                            chunk.set_metadata(x, y, z, 0);
+                        }
+                    }
+                }
+            }
+        }
+
+        let mut world = World::new(level);
+        world.chunks = chunks;
+        world.entities = entities
+            .into_iter()
+            .filter(|entity| entity_position(entity).is_some_and(is_entity_in_range))
+            .collect();
+        world.tile_entities = tile_entities
+            .into_iter()
+            .filter(|tile_entity| {
+                tile_entity_position(tile_entity)
+                    .is_some_and(|(x, y, z)| World::is_in_bounds(x, y, z))
+            })
+            .collect();
+
+        world.calculate_light();
+        Ok(world)
+    }
+}
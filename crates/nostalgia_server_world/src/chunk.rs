@@ -1,101 +1,258 @@
-use byteorder::{LittleEndian, ReadBytesExt};
+use byteorder::{LittleEndian, ReadBytesExt, WriteBytesExt};
 
 use crate::{Block, BlockID};
-use std::io::{Cursor, Error, ErrorKind, Read, Result};
+use std::io::{Cursor, Error, ErrorKind, Result, Write};
 
 pub const CHUNK_SIZE_X: usize = 16;
 pub const CHUNK_SIZE_Z: usize = 16;
 pub const CHUNK_SIZE_Y: usize = 128;
 
+/// Height of a single section in the network chunk payload.
+pub const CHUNK_SECTION_HEIGHT: usize = 16;
+pub const CHUNK_SECTION_COUNT: usize = CHUNK_SIZE_Y / CHUNK_SECTION_HEIGHT;
+
+/// Size of a chunk record in `chunks.dat`, including the length field itself.
+pub const CHUNK_RECORD_SIZE: usize = 82180;
+
+const BLOCK_COUNT: usize = CHUNK_SIZE_X * CHUNK_SIZE_Y * CHUNK_SIZE_Z;
+const DATA_SIZE: usize = CHUNK_RECORD_SIZE - 4;
+
+// Offsets into the chunk data, which is laid out the same way as in `chunks.dat`
+const METADATA_OFFSET: usize = BLOCK_COUNT;
+const SKY_LIGHT_OFFSET: usize = METADATA_OFFSET + BLOCK_COUNT / 2;
+const BLOCK_LIGHT_OFFSET: usize = SKY_LIGHT_OFFSET + BLOCK_COUNT / 2;
+const UPDATE_MAP_OFFSET: usize = BLOCK_LIGHT_OFFSET + BLOCK_COUNT / 2;
+
+/// Size of the block ids followed by the metadata, sky light and block light arrays,
+/// which later formats kept as is.
+pub const CHUNK_TERRAIN_SIZE: usize = UPDATE_MAP_OFFSET;
+
+/// A 16x128x16 column of blocks, stored packed as one byte per block id followed by
+/// nibble arrays for metadata, sky light and block light, with two blocks per byte and
+/// the even index in the low nibble.
 #[derive(Clone, Debug)]
 pub struct Chunk {
-    pub blocks: [Block; CHUNK_SIZE_Y * CHUNK_SIZE_Z * CHUNK_SIZE_X],
+    data: Box<[u8; DATA_SIZE]>,
+    /// Per column height, indexed by `x + z * 16`. Not saved, it's rebuilt on load.
+    height_map: [u8; CHUNK_SIZE_X * CHUNK_SIZE_Z],
 }
 
 impl Chunk {
     pub fn new() -> Self {
         Self {
-            blocks: [Block::new(BlockID::Air); CHUNK_SIZE_Y * CHUNK_SIZE_Z * CHUNK_SIZE_X],
+            data: vec![0u8; DATA_SIZE]
+                .into_boxed_slice()
+                .try_into()
+                .expect("Chunk buffer has the wrong size"),
+            height_map: [0; CHUNK_SIZE_X * CHUNK_SIZE_Z],
         }
     }
 
     pub fn get_block_index(x: usize, y: usize, z: usize) -> usize {
         // +1 in y => offset increases by 1
-        // +1 in z => offset increases by 16
-        // +1 in x => offset increases by 256
-        y + x * CHUNK_SIZE_Y + z * CHUNK_SIZE_Y * CHUNK_SIZE_Z
+        // +1 in z => offset increases by 128
+        // +1 in x => offset increases by 2048
+        y + z * CHUNK_SIZE_Y + x * CHUNK_SIZE_Y * CHUNK_SIZE_Z
     }
 
-    fn decompress_block_metadata(buffer: &[u8], destination: &mut [u8]) {
-        let mut output_buffer = [0u8; 32768];
-        for offset in (0..output_buffer.len()).step_by(2) {
-            let input_byte = buffer[offset / 2];
-            output_buffer[offset] = input_byte & 0x0F;
-            output_buffer[offset + 1] = input_byte >> 4;
+    /// Takes over a whole chunk record as the packed storage, only shifting out the
+    /// length in front of it instead of copying the data into a new buffer.
+    pub fn from_bytes(mut record: Vec<u8>) -> Result<Self> {
+        if record.len() != CHUNK_RECORD_SIZE {
+            return Err(Error::new(
+                ErrorKind::InvalidData,
+                "Invalid chunk record size",
+            ));
         }
 
-        destination.copy_from_slice(&output_buffer);
-    }
-
-    pub fn from_bytes(cursor: &mut Cursor<Vec<u8>>) -> Result<Self> {
-        if cursor.read_u32::<LittleEndian>()? != 82180 {
+        if Cursor::new(&record).read_u32::<LittleEndian>()? != CHUNK_RECORD_SIZE as u32 {
             return Err(Error::new(ErrorKind::InvalidData, "Invalid chunk header"));
         }
 
-        let mut chunk_buffer = [0u8; 82176];
-        cursor.read_exact(&mut chunk_buffer)?;
+        record.drain(..4);
+        let mut chunk = Self {
+            data: record
+                .into_boxed_slice()
+                .try_into()
+                .expect("Chunk record has the checked size"),
+            height_map: [0; CHUNK_SIZE_X * CHUNK_SIZE_Z],
+        };
+        chunk.calculate_height_map();
+        Ok(chunk)
+    }
+
+    pub fn to_bytes(&self, cursor: &mut Cursor<Vec<u8>>) -> Result<()> {
+        cursor.write_u32::<LittleEndian>(CHUNK_RECORD_SIZE as u32)?;
+        cursor.write_all(&self.data[..])
+    }
+
+    /// The block ids, metadata and light, without the update map of `chunks.dat`.
+    pub fn terrain(&self) -> &[u8] {
+        &self.data[..CHUNK_TERRAIN_SIZE]
+    }
+
+    /// Creates a chunk from the arrays returned by [`Chunk::terrain`].
+    pub fn from_terrain(terrain: &[u8]) -> Result<Self> {
+        if terrain.len() != CHUNK_TERRAIN_SIZE {
+            return Err(Error::new(
+                ErrorKind::InvalidData,
+                "Invalid chunk terrain size",
+            ));
+        }
 
         let mut chunk = Self::new();
+        chunk.data[..CHUNK_TERRAIN_SIZE].copy_from_slice(terrain);
+        chunk.calculate_height_map();
+        Ok(chunk)
+    }
 
-        const SLICE_SIZE: usize = CHUNK_SIZE_X * CHUNK_SIZE_Y * CHUNK_SIZE_Z;
-        let mut block_data = [0u8; SLICE_SIZE];
-        let mut block_metadata = [0u8; SLICE_SIZE];
-        let mut sky_light = [0u8; SLICE_SIZE];
-        let mut block_light = [0u8; SLICE_SIZE];
+    /// Encodes the chunk into the column based payload used by `SendChunkData`.
+    ///
+    /// Every one of the 256 columns starts with a byte where each bit marks a 16 block
+    /// high section as present, followed by the section data: 16 block ids, then 8 bytes
+    /// each of metadata, sky light and block light nibbles.
+    pub fn to_network_bytes(&self) -> Vec<u8> {
+        const SECTION_BYTES: usize = CHUNK_SECTION_HEIGHT + CHUNK_SECTION_HEIGHT / 2 * 3;
 
-        block_data.copy_from_slice(&chunk_buffer[0..SLICE_SIZE]);
-        Self::decompress_block_metadata(
-            &chunk_buffer[SLICE_SIZE..(SLICE_SIZE + SLICE_SIZE / 2)],
-            &mut block_metadata,
-        );
-        Self::decompress_block_metadata(
-            &chunk_buffer[(SLICE_SIZE + SLICE_SIZE / 2)..(SLICE_SIZE + SLICE_SIZE)],
-            &mut sky_light,
-        );
-        Self::decompress_block_metadata(
-            &chunk_buffer[(SLICE_SIZE + SLICE_SIZE)..(SLICE_SIZE + SLICE_SIZE + SLICE_SIZE / 2)],
-            &mut block_light,
+        let mut buffer = Vec::with_capacity(
+            CHUNK_SIZE_X * CHUNK_SIZE_Z * (1 + CHUNK_SECTION_COUNT * SECTION_BYTES),
         );
 
-        for x in 0..CHUNK_SIZE_Z {
-            for z in 0..CHUNK_SIZE_X {
-                for y in 0..CHUNK_SIZE_Y {
-                    let block_index = Self::get_block_index(x, y, z);
-                    chunk.blocks[block_index] = Block::existing(
-                        unsafe { std::mem::transmute::<u8, BlockID>(block_data[block_index]) },
-                        sky_light[block_index],
-                        block_light[block_index],
-                        block_metadata[block_index],
+        for column in 0..CHUNK_SIZE_X * CHUNK_SIZE_Z {
+            // Columns are ordered with x varying fastest, matching the client's chunk layout.
+            let column_index = Self::get_block_index(column & 0x0F, 0, column >> 4);
+
+            buffer.push(0xFF);
+            for section in 0..CHUNK_SECTION_COUNT {
+                // Sections are contiguous in every array since y varies fastest
+                let index = column_index + section * CHUNK_SECTION_HEIGHT;
+                buffer.extend_from_slice(&self.data[index..][..CHUNK_SECTION_HEIGHT]);
+                for offset in [METADATA_OFFSET, SKY_LIGHT_OFFSET, BLOCK_LIGHT_OFFSET] {
+                    buffer.extend_from_slice(
+                        &self.data[offset + index / 2..][..CHUNK_SECTION_HEIGHT / 2],
                     );
                 }
             }
         }
 
-        Ok(chunk)
+        buffer
     }
 
-    pub fn get(&self, x: usize, y: usize, z: usize) -> Block {
-        let block_index = Self::get_block_index(x, y, z);
-        self.blocks[block_index]
+    fn nibble(&self, offset: usize, index: usize) -> u8 {
+        let byte = self.data[offset + index / 2];
+        if index.is_multiple_of(2) {
+            byte & 0x0F
+        } else {
+            byte >> 4
+        }
+    }
+
+    fn set_nibble(&mut self, offset: usize, index: usize, value: u8) {
+        let byte = &mut self.data[offset + index / 2];
+        *byte = if index.is_multiple_of(2) {
+            (*byte & 0xF0) | (value & 0x0F)
+        } else {
+            (*byte & 0x0F) | (value << 4)
+        };
+    }
+
+    pub fn block_id(&self, x: usize, y: usize, z: usize) -> BlockID {
+        BlockID(self.data[Self::get_block_index(x, y, z)])
+    }
+
+    pub fn set_block_id(&mut self, x: usize, y: usize, z: usize, id: BlockID) {
+        self.data[Self::get_block_index(x, y, z)] = id.0;
+        self.update_height(x, y, z);
+    }
+
+    /// Height of the column, one above the highest block that dims light, which is also
+    /// the lowest block sky light reaches directly.
+    pub fn height(&self, x: usize, z: usize) -> usize {
+        self.height_map[x + z * CHUNK_SIZE_X] as usize
+    }
+
+    /// Height of the column counting only the blocks below `top`.
+    fn column_height(&self, x: usize, z: usize, top: usize) -> u8 {
+        (0..top)
+            .rev()
+            .find(|&y| self.block_id(x, y, z).properties().light_opacity > 0)
+            .map_or(0, |y| y as u8 + 1)
+    }
+
+    pub fn calculate_height_map(&mut self) {
+        for x in 0..CHUNK_SIZE_X {
+            for z in 0..CHUNK_SIZE_Z {
+                self.height_map[x + z * CHUNK_SIZE_X] = self.column_height(x, z, CHUNK_SIZE_Y);
+            }
+        }
+    }
+
+    /// Keeps the height map up to date after the block at the position changed.
+    fn update_height(&mut self, x: usize, y: usize, z: usize) {
+        let height = self.height(x, z);
+        if self.block_id(x, y, z).properties().light_opacity > 0 {
+            if y >= height {
+                self.height_map[x + z * CHUNK_SIZE_X] = y as u8 + 1;
+            }
+        } else if y + 1 == height {
+            self.height_map[x + z * CHUNK_SIZE_X] = self.column_height(x, z, y);
+        }
+    }
+
+    pub fn metadata(&self, x: usize, y: usize, z: usize) -> u8 {
+        self.nibble(METADATA_OFFSET, Self::get_block_index(x, y, z))
+    }
+
+    pub fn set_metadata(&mut self, x: usize, y: usize, z: usize, metadata: u8) {
+        self.set_nibble(METADATA_OFFSET, Self::get_block_index(x, y, z), metadata);
     }
 
-    pub fn get_ref(&self, x: usize, y: usize, z: usize) -> &Block {
-        let block_index = Self::get_block_index(x, y, z);
-        &self.blocks[block_index]
+    pub fn sky_light(&self, x: usize, y: usize, z: usize) -> u8 {
+        self.nibble(SKY_LIGHT_OFFSET, Self::get_block_index(x, y, z))
     }
 
-    pub fn get_mut(&mut self, x: usize, y: usize, z: usize) -> &mut Block {
-        let block_index = Self::get_block_index(x, y, z);
-        &mut self.blocks[block_index]
+    pub fn set_sky_light(&mut self, x: usize, y: usize, z: usize, light: u8) {
+        self.set_nibble(SKY_LIGHT_OFFSET, Self::get_block_index(x, y, z), light);
+    }
+
+    pub fn block_light(&self, x: usize, y: usize, z: usize) -> u8 {
+        self.nibble(BLOCK_LIGHT_OFFSET, Self::get_block_index(x, y, z))
+    }
+
+    pub fn set_block_light(&mut self, x: usize, y: usize, z: usize, light: u8) {
+        self.set_nibble(BLOCK_LIGHT_OFFSET, Self::get_block_index(x, y, z), light);
+    }
+
+    /// Per column bitmask of the sections the client has modified, kept so saves round-trip.
+    pub fn update_map(&self) -> &[u8] {
+        &self.data[UPDATE_MAP_OFFSET..]
+    }
+
+    pub fn update_map_mut(&mut self) -> &mut [u8] {
+        &mut self.data[UPDATE_MAP_OFFSET..]
+    }
+
+    pub fn get(&self, x: usize, y: usize, z: usize) -> Block {
+        let index = Self::get_block_index(x, y, z);
+        Block::existing(
+            BlockID(self.data[index]),
+            self.nibble(SKY_LIGHT_OFFSET, index),
+            self.nibble(BLOCK_LIGHT_OFFSET, index),
+            self.nibble(METADATA_OFFSET, index),
+        )
+    }
+
+    /// Replaces the block and returns the previous one.
+    pub fn set(&mut self, x: usize, y: usize, z: usize, block: Block) -> Block {
+        let previous = self.get(x, y, z);
+
+        let index = Self::get_block_index(x, y, z);
+        self.data[index] = block.id.0;
+        self.set_nibble(METADATA_OFFSET, index, block.metadata);
+        self.set_nibble(SKY_LIGHT_OFFSET, index, block.sky_light);
+        self.set_nibble(BLOCK_LIGHT_OFFSET, index, block.block_light);
+        self.update_height(x, y, z);
+
+        previous
     }
 }
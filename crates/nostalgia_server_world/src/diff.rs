@@ -0,0 +1,230 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt,
+};
+
+use nbt::Tag;
+
+use crate::{
+    entity_name, schematic::tile_entity_position, BlockID, ChunkPos, World, CHUNK_SIZE_X,
+    CHUNK_SIZE_Y, CHUNK_SIZE_Z,
+};
+
+/// A difference between two saves of a world.
+#[derive(Clone, Debug, PartialEq)]
+pub enum WorldChange {
+    ChunkAdded(ChunkPos),
+    ChunkRemoved(ChunkPos),
+    /// A chunk couldn't be read from one or both saves, with the errors, so its blocks
+    /// weren't compared.
+    ChunkUnreadable {
+        position: ChunkPos,
+        before: Option<String>,
+        after: Option<String>,
+    },
+    /// A block's id or metadata changed, light is ignored.
+    Block {
+        position: (i32, i32, i32),
+        before: (BlockID, u8),
+        after: (BlockID, u8),
+    },
+    EntityAdded(Tag),
+    EntityRemoved(Tag),
+    TileEntityAdded(Tag),
+    TileEntityRemoved(Tag),
+    TileEntityChanged {
+        before: Tag,
+        after: Tag,
+    },
+}
+
+fn block_name((id, metadata): (BlockID, u8)) -> String {
+    format!("{}:{}", id.properties().name, metadata)
+}
+
+fn describe_entity(tag: &Tag) -> String {
+    let position: Vec<String> = match tag.get_list("Pos") {
+        Some(position) => position
+            .iter()
+            .map(|coordinate| match coordinate {
+                Tag::Float(value) => format!("{:.1}", value),
+                Tag::Double(value) => format!("{:.1}", value),
+                _ => "?".to_string(),
+            })
+            .collect(),
+        None => ["x", "y", "z"]
+            .iter()
+            .map(|name| tag.get_int(name).map_or("?".to_string(), i32::to_string))
+            .collect(),
+    };
+
+    format!("{} at {}", entity_name(tag), position.join(" "))
+}
+
+impl fmt::Display for WorldChange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::ChunkAdded(position) => write!(f, "+ chunk {} {}", position.x, position.z),
+            Self::ChunkRemoved(position) => write!(f, "- chunk {} {}", position.x, position.z),
+            Self::ChunkUnreadable {
+                position,
+                before,
+                after,
+            } => {
+                write!(f, "! chunk {} {} is unreadable", position.x, position.z)?;
+                for (save, error) in [("before", before), ("after", after)] {
+                    if let Some(error) = error {
+                        write!(f, ", {}: {}", save, error)?;
+                    }
+                }
+
+                Ok(())
+            }
+            Self::Block {
+                position: (x, y, z),
+                before,
+                after,
+            } => write!(
+                f,
+                "~ block {} {} {}: {} -> {}",
+                x,
+                y,
+                z,
+                block_name(*before),
+                block_name(*after)
+            ),
+            Self::EntityAdded(tag) => write!(f, "+ entity {}", describe_entity(tag)),
+            Self::EntityRemoved(tag) => write!(f, "- entity {}", describe_entity(tag)),
+            Self::TileEntityAdded(tag) => write!(f, "+ tile entity {}", describe_entity(tag)),
+            Self::TileEntityRemoved(tag) => write!(f, "- tile entity {}", describe_entity(tag)),
+            Self::TileEntityChanged { after, .. } => {
+                write!(f, "~ tile entity {}", describe_entity(after))
+            }
+        }
+    }
+}
+
+fn tile_entities_by_position(tags: &[Tag]) -> BTreeMap<Option<(i32, i32, i32)>, Vec<&Tag>> {
+    let mut positions: BTreeMap<_, Vec<&Tag>> = BTreeMap::new();
+    for tag in tags {
+        positions
+            .entry(tile_entity_position(tag))
+            .or_default()
+            .push(tag);
+    }
+
+    positions
+}
+
+/// Lists what changed from `before` to `after`: chunks, then blocks and tile entities
+/// ordered by coordinate, then entities. Entities have no identity of their own, so one
+/// that moved shows up as removed and added again.
+pub fn diff_worlds(before: &World, after: &World) -> Vec<WorldChange> {
+    let mut changes = Vec::new();
+    // Chunks which can't be read are left out by `World::chunks`
+    let positions: BTreeSet<ChunkPos> = before
+        .chunks()
+        .chain(after.chunks())
+        .map(|(position, _)| position)
+        .chain(ChunkPos::all())
+        .collect();
+
+    let mut blocks = Vec::new();
+    for position in positions {
+        let (old, new) = match (
+            before.try_chunk_at(position.x, position.z),
+            after.try_chunk_at(position.x, position.z),
+        ) {
+            (Ok(Some(old)), Ok(Some(new))) => (old, new),
+            (Ok(None), Ok(None)) => continue,
+            (Ok(Some(_)), Ok(None)) => {
+                changes.push(WorldChange::ChunkRemoved(position));
+                continue;
+            }
+            (Ok(None), Ok(Some(_))) => {
+                changes.push(WorldChange::ChunkAdded(position));
+                continue;
+            }
+            (old, new) => {
+                changes.push(WorldChange::ChunkUnreadable {
+                    position,
+                    before: old.err().map(|error| error.to_string()),
+                    after: new.err().map(|error| error.to_string()),
+                });
+                continue;
+            }
+        };
+
+        for x in 0..CHUNK_SIZE_X {
+            for z in 0..CHUNK_SIZE_Z {
+                for y in 0..CHUNK_SIZE_Y {
+                    let old = (old.block_id(x, y, z), old.metadata(x, y, z));
+                    let new = (new.block_id(x, y, z), new.metadata(x, y, z));
+                    if old != new {
+                        let world_x = position.x * CHUNK_SIZE_X as i32 + x as i32;
+                        let world_z = position.z * CHUNK_SIZE_Z as i32 + z as i32;
+                        blocks.push(WorldChange::Block {
+                            position: (world_x, y as i32, world_z),
+                            before: old,
+                            after: new,
+                        });
+                    }
+                }
+            }
+        }
+    }
+
+    blocks.sort_by_key(|change| match change {
+        WorldChange::Block { position, .. } => *position,
+        _ => unreachable!("Only blocks are sorted"),
+    });
+    changes.append(&mut blocks);
+
+    let old_tile_entities = tile_entities_by_position(&before.tile_entities);
+    let new_tile_entities = tile_entities_by_position(&after.tile_entities);
+    let positions: BTreeSet<_> = old_tile_entities
+        .keys()
+        .chain(new_tile_entities.keys())
+        .collect();
+    for position in positions {
+        let old = old_tile_entities
+            .get(position)
+            .map_or(&[][..], Vec::as_slice);
+        let new = new_tile_entities
+            .get(position)
+            .map_or(&[][..], Vec::as_slice);
+        match (old, new) {
+            ([old], [new]) if old != new => changes.push(WorldChange::TileEntityChanged {
+                before: (*old).clone(),
+                after: (*new).clone(),
+            }),
+            _ if old == new => {}
+            _ => {
+                changes.extend(
+                    old.iter()
+                        .map(|&tag| WorldChange::TileEntityRemoved(tag.clone())),
+                );
+                changes.extend(
+                    new.iter()
+                        .map(|&tag| WorldChange::TileEntityAdded(tag.clone())),
+                );
+            }
+        }
+    }
+
+    // Entities are matched up as equal tags, whatever their order
+    let mut added: Vec<&Tag> = after.entities.iter().collect();
+    for entity in &before.entities {
+        match added.iter().position(|&other| other == entity) {
+            Some(index) => {
+                added.swap_remove(index);
+            }
+            None => changes.push(WorldChange::EntityRemoved(entity.clone())),
+        }
+    }
+
+    let mut added: Vec<Tag> = added.into_iter().cloned().collect();
+    added.sort_by_cached_key(describe_entity);
+    changes.extend(added.into_iter().map(WorldChange::EntityAdded));
+    changes
+}
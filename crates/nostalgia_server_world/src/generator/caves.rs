@@ -0,0 +1,147 @@
+use std::f64::consts::PI;
+
+use crate::{BlockID, ChunkNeighbourhood, Decorator, Random, CHUNK_SIZE_X, CHUNK_SIZE_Z};
+
+/// Widest a cave tunnel gets, in blocks from its middle.
+const MAX_CAVE_RADIUS: f64 = 3.5;
+/// Caves are filled with lava below this height.
+const LAVA_LEVEL: i32 = 10;
+
+/// Carves winding tunnels through the ground, starting in a few random chunks.
+#[derive(Clone, Debug)]
+pub struct CaveDecorator {
+    /// Probability of a chunk having caves starting in it.
+    pub chance: f64,
+    /// Most tunnels starting in a single chunk.
+    pub max_tunnels: u32,
+}
+
+impl Default for CaveDecorator {
+    fn default() -> Self {
+        Self {
+            chance: 0.25,
+            max_tunnels: 3,
+        }
+    }
+}
+
+impl CaveDecorator {
+    fn is_carvable(id: BlockID) -> bool {
+        matches!(
+            id,
+            BlockID::Stone | BlockID::Dirt | BlockID::Grass | BlockID::Gravel
+        )
+    }
+
+    fn is_water(id: Option<BlockID>) -> bool {
+        matches!(id, Some(BlockID::Water | BlockID::StillWater))
+    }
+
+    /// Carves an ellipsoid which is flatter than it is wide, leaving anything next to
+    /// water alone so lakes and oceans don't drain into the cave.
+    fn carve(neighbourhood: &mut ChunkNeighbourhood, (x, y, z): (f64, f64, f64), radius: f64) {
+        let height = radius * 0.7;
+        for bx in (x - radius).floor() as i32..=(x + radius).floor() as i32 {
+            for bz in (z - radius).floor() as i32..=(z + radius).floor() as i32 {
+                for by in ((y - height).floor() as i32).max(1)..=(y + height).floor() as i32 {
+                    let distance = ((bx as f64 + 0.5 - x) / radius).powi(2)
+                        + ((by as f64 + 0.5 - y) / height).powi(2)
+                        + ((bz as f64 + 0.5 - z) / radius).powi(2);
+                    if distance >= 1.0 {
+                        continue;
+                    }
+
+                    if !neighbourhood
+                        .block_id(bx, by, bz)
+                        .is_some_and(Self::is_carvable)
+                        || Self::is_water(neighbourhood.block_id(bx, by + 1, bz))
+                        || Self::is_water(neighbourhood.block_id(bx - 1, by, bz))
+                        || Self::is_water(neighbourhood.block_id(bx + 1, by, bz))
+                        || Self::is_water(neighbourhood.block_id(bx, by, bz - 1))
+                        || Self::is_water(neighbourhood.block_id(bx, by, bz + 1))
+                    {
+                        continue;
+                    }
+
+                    let id = if by < LAVA_LEVEL {
+                        BlockID::StillLava
+                    } else {
+                        BlockID::Air
+                    };
+
+                    neighbourhood.set_block(bx, by, bz, id, 0);
+                }
+            }
+        }
+    }
+
+    /// Moves through the ground from the given position, turning a little at every step
+    /// and carving as it goes. Stops before reaching past the neighbouring chunks.
+    fn carve_tunnel(
+        neighbourhood: &mut ChunkNeighbourhood,
+        random: &mut Random,
+        (mut x, mut y, mut z): (f64, f64, f64),
+    ) {
+        let (origin_x, origin_z) = neighbourhood.origin();
+        let reach = CHUNK_SIZE_X as f64 - MAX_CAVE_RADIUS - 1.0;
+        let (min_x, max_x) = (
+            origin_x as f64 - reach,
+            (origin_x + CHUNK_SIZE_X as i32) as f64 + reach,
+        );
+        let (min_z, max_z) = (
+            origin_z as f64 - reach,
+            (origin_z + CHUNK_SIZE_Z as i32) as f64 + reach,
+        );
+
+        let mut yaw = random.next_f64() * PI * 2.0;
+        let mut pitch = (random.next_f64() - 0.5) / 4.0;
+        let width = random.next_f64() * 1.5 + 1.0;
+        let length = random.next_between(40, 80);
+
+        for step in 0..length {
+            let t = step as f64 / length as f64;
+            let radius = (1.0 + (t * PI).sin() * width).min(MAX_CAVE_RADIUS);
+
+            x += yaw.cos() * pitch.cos();
+            y += pitch.sin();
+            z += yaw.sin() * pitch.cos();
+            if !(min_x..max_x).contains(&x) || !(min_z..max_z).contains(&z) || y < 4.0 {
+                break;
+            }
+
+            Self::carve(neighbourhood, (x, y, z), radius);
+
+            yaw += (random.next_f64() - random.next_f64()) * 0.5;
+            pitch = pitch * 0.7 + (random.next_f64() - random.next_f64()) * 0.3;
+        }
+    }
+}
+
+impl Decorator for CaveDecorator {
+    fn decorate(&self, neighbourhood: &mut ChunkNeighbourhood, random: &mut Random) {
+        if !random.chance(self.chance) {
+            return;
+        }
+
+        let (origin_x, origin_z) = neighbourhood.origin();
+        let tunnels = match self.max_tunnels {
+            0 => 0,
+            max_tunnels => 1 + random.next_below(max_tunnels),
+        };
+        for _ in 0..tunnels {
+            let x = origin_x + random.next_below(CHUNK_SIZE_X as u32) as i32;
+            let z = origin_z + random.next_below(CHUNK_SIZE_Z as u32) as i32;
+            let surface = neighbourhood.height(x, z).unwrap_or(0);
+            if surface <= 12 {
+                continue;
+            }
+
+            let y = random.next_between(8, surface - 4);
+            Self::carve_tunnel(
+                neighbourhood,
+                random,
+                (x as f64 + 0.5, y as f64 + 0.5, z as f64 + 0.5),
+            );
+        }
+    }
+}
@@ -0,0 +1,128 @@
+use std::{
+    io,
+    num::{IntErrorKind, ParseIntError},
+};
+
+use crate::{
+    BlockID, Chunk, ChunkPos, Generator, BLOCK_REGISTRY, CHUNK_SIZE_X, CHUNK_SIZE_Z, WORLD_SIZE_Y,
+};
+
+/// A layer of the same block across the whole world.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct FlatLayer {
+    pub block: BlockID,
+    pub metadata: u8,
+    /// How many blocks thick the layer is.
+    pub count: u8,
+}
+
+impl FlatLayer {
+    /// Parses a layer such as `grass`, `2*dirt`, `35:14` or `3*wool:4`. Blocks are given by
+    /// their name or their id.
+    pub fn parse(layer: &str) -> io::Result<Self> {
+        let (count, block) = match layer.split_once('*') {
+            Some((count, block)) => (parse_number(count, "count", layer)?, block),
+            None => (1, layer),
+        };
+
+        let (block, metadata) = match block.split_once(':') {
+            Some((block, metadata)) => (block, parse_number(metadata, "metadata", layer)?),
+            None => (block, 0),
+        };
+
+        let block = match block.trim().parse::<u8>() {
+            Ok(id) if BLOCK_REGISTRY.is_registered(BlockID(id)) => BlockID(id),
+            _ => BLOCK_REGISTRY
+                .find(block.trim())
+                .ok_or_else(|| invalid_layer(layer, "unknown block"))?,
+        };
+
+        if metadata > 15 {
+            return Err(invalid_layer(layer, "metadata is above 15"));
+        }
+
+        Ok(Self {
+            block,
+            metadata,
+            count,
+        })
+    }
+}
+
+fn parse_number(number: &str, name: &str, layer: &str) -> io::Result<u8> {
+    number.trim().parse().map_err(|error: ParseIntError| {
+        let reason = match error.kind() {
+            IntErrorKind::PosOverflow => "is too large",
+            _ => "is not a number",
+        };
+        invalid_layer(layer, &format!("{name} {reason}"))
+    })
+}
+
+fn invalid_layer(layer: &str, reason: &str) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidInput,
+        format!("Invalid flat world layer '{layer}': {reason}"),
+    )
+}
+
+/// The same layers of blocks everywhere, without any features.
+#[derive(Clone, Debug)]
+pub struct FlatGenerator {
+    layers: Vec<FlatLayer>,
+}
+
+impl FlatGenerator {
+    pub const DEFAULT_LAYERS: &'static str = "bedrock,2*dirt,grass";
+
+    /// Parses the layers of the world from the bottom up, separated by commas, for
+    /// example `bedrock,2*dirt,grass`.
+    pub fn from_layers(layers: &str) -> io::Result<Self> {
+        let layers = layers
+            .split(',')
+            .filter(|layer| !layer.trim().is_empty())
+            .map(FlatLayer::parse)
+            .collect::<io::Result<Vec<_>>>()?;
+
+        let height: i32 = layers.iter().map(|layer| layer.count as i32).sum();
+        if height > WORLD_SIZE_Y {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("Flat world layers are {height} blocks high, more than the world"),
+            ));
+        }
+
+        Ok(Self { layers })
+    }
+
+    pub fn layers(&self) -> &[FlatLayer] {
+        &self.layers
+    }
+}
+
+impl Default for FlatGenerator {
+    fn default() -> Self {
+        Self::from_layers(Self::DEFAULT_LAYERS).expect("The default layers are valid")
+    }
+}
+
+impl Generator for FlatGenerator {
+    fn generate_chunk(&self, _position: ChunkPos) -> Chunk {
+        let mut chunk = Chunk::new();
+        let mut y = 0;
+        for layer in &self.layers {
+            for _ in 0..layer.count {
+                for x in 0..CHUNK_SIZE_X {
+                    for z in 0..CHUNK_SIZE_Z {
+                        chunk.set_block_id(x, y, z, layer.block);
+                        chunk.set_metadata(x, y, z, layer.metadata);
+                    }
+                }
+
+                y += 1;
+            }
+        }
+
+        chunk
+    }
+}
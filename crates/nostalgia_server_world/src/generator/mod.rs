@@ -0,0 +1,100 @@
+mod caves;
+mod flat;
+mod neighbourhood;
+mod noise;
+mod ores;
+mod plants;
+mod terrain;
+mod trees;
+mod void;
+
+pub use caves::*;
+pub use flat::*;
+pub use neighbourhood::*;
+pub use noise::*;
+pub use ores::*;
+pub use plants::*;
+pub use terrain::*;
+pub use trees::*;
+pub use void::*;
+
+use std::collections::HashMap;
+
+use crate::{Chunk, ChunkPos, LevelData, World};
+
+/// Salt for the random numbers given to decorators, offset by their index.
+const DECORATOR_SALT: u64 = 0x6465_636F_7261_7465;
+
+/// Builds the blocks of new chunks.
+pub trait Generator {
+    fn generate_chunk(&self, position: ChunkPos) -> Chunk;
+
+    /// Decorators placing features into the chunks once all of them are generated, in
+    /// the order they run in.
+    fn decorators(&self) -> &[Box<dyn Decorator>] {
+        &[]
+    }
+}
+
+/// Places features such as ores and trees into a chunk after the terrain of every chunk
+/// was generated. Decorators only get randomness from `random`, so the same seed always
+/// produces the same features.
+pub trait Decorator {
+    fn decorate(&self, neighbourhood: &mut ChunkNeighbourhood, random: &mut Random);
+}
+
+/// Builds and decorates the chunks at `positions` without generating the rest of the
+/// world. The chunks around them are decorated too, so features reaching in from
+/// neighbours aren't lost, and only features depending on ones placed further away can
+/// come out differently than in a whole generated world. Light is left dark.
+pub fn generate_chunks(
+    seed: i64,
+    generator: &dyn Generator,
+    positions: &[ChunkPos],
+) -> HashMap<ChunkPos, Chunk> {
+    let near = |position: &ChunkPos, distance: i32| {
+        positions.iter().any(|other| {
+            (other.x - position.x).abs() <= distance && (other.z - position.z).abs() <= distance
+        })
+    };
+
+    // Decorating a chunk needs the terrain of the chunks around it
+    let mut chunks: HashMap<ChunkPos, Chunk> = ChunkPos::all()
+        .filter(|position| near(position, 2))
+        .map(|position| (position, generator.generate_chunk(position)))
+        .collect();
+
+    // Every decorator runs over all of the chunks before the next one, so trees don't
+    // end up floating above caves carved afterwards.
+    for (index, decorator) in generator.decorators().iter().enumerate() {
+        let salt = DECORATOR_SALT.wrapping_add(index as u64);
+        for position in ChunkPos::all().filter(|position| near(position, 1)) {
+            let mut random = Random::for_position(seed, salt, position.x, position.z);
+            let mut neighbourhood = ChunkNeighbourhood::new(position, &mut chunks);
+            decorator.decorate(&mut neighbourhood, &mut random);
+        }
+    }
+
+    chunks.retain(|position, _| positions.contains(position));
+    chunks
+}
+
+impl World {
+    /// Creates a complete legacy world with every chunk built and decorated by the
+    /// generator, lit, and with the spawn moved onto the ground.
+    pub fn generate(level: LevelData, generator: &dyn Generator) -> Self {
+        let positions: Vec<ChunkPos> = ChunkPos::all().collect();
+        let chunks = generate_chunks(level.seed, generator, &positions);
+        let mut world = Self::new(level);
+        for (position, chunk) in chunks {
+            world.insert_chunk(position, chunk);
+        }
+
+        world.calculate_light();
+        if let Some(spawn) = world.find_safe_spawn() {
+            world.level.set_spawn_position(spawn);
+        }
+
+        world
+    }
+}
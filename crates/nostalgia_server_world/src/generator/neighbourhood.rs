@@ -0,0 +1,66 @@
+use std::collections::HashMap;
+
+use crate::{to_local_position, BlockID, Chunk, ChunkPos, CHUNK_SIZE_X, CHUNK_SIZE_Z};
+
+/// A chunk being decorated together with the eight chunks around it. Features start in
+/// the center chunk but may reach up to a chunk into its neighbours.
+pub struct ChunkNeighbourhood<'a> {
+    center: ChunkPos,
+    chunks: &'a mut HashMap<ChunkPos, Chunk>,
+}
+
+impl<'a> ChunkNeighbourhood<'a> {
+    pub fn new(center: ChunkPos, chunks: &'a mut HashMap<ChunkPos, Chunk>) -> Self {
+        Self { center, chunks }
+    }
+
+    pub fn center(&self) -> ChunkPos {
+        self.center
+    }
+
+    /// World coordinates of the corner of the center chunk closest to the origin.
+    pub fn origin(&self) -> (i32, i32) {
+        (
+            self.center.x * CHUNK_SIZE_X as i32,
+            self.center.z * CHUNK_SIZE_Z as i32,
+        )
+    }
+
+    fn locate(&self, x: i32, y: i32, z: i32) -> Option<(ChunkPos, usize, usize, usize)> {
+        let (position, x, y, z) = to_local_position(x, y, z)?;
+        if (position.x - self.center.x).abs() > 1 || (position.z - self.center.z).abs() > 1 {
+            return None;
+        }
+
+        Some((position, x, y, z))
+    }
+
+    /// Id of the block at the given world coordinates, or `None` if it is outside of the
+    /// neighbourhood.
+    pub fn block_id(&self, x: i32, y: i32, z: i32) -> Option<BlockID> {
+        let (position, x, y, z) = self.locate(x, y, z)?;
+        Some(self.chunks.get(&position)?.block_id(x, y, z))
+    }
+
+    /// Replaces the block at the given world coordinates. Returns `false` without
+    /// modifying anything if the position is outside of the neighbourhood.
+    pub fn set_block(&mut self, x: i32, y: i32, z: i32, id: BlockID, metadata: u8) -> bool {
+        let Some((position, x, y, z)) = self.locate(x, y, z) else {
+            return false;
+        };
+
+        let Some(chunk) = self.chunks.get_mut(&position) else {
+            return false;
+        };
+
+        chunk.set_block_id(x, y, z, id);
+        chunk.set_metadata(x, y, z, metadata);
+        true
+    }
+
+    /// Height of the column at the given world coordinates, see [`Chunk::height`].
+    pub fn height(&self, x: i32, z: i32) -> Option<i32> {
+        let (position, x, _, z) = self.locate(x, 0, z)?;
+        Some(self.chunks.get(&position)?.height(x, z) as i32)
+    }
+}
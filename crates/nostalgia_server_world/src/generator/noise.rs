@@ -0,0 +1,181 @@
+/// Small deterministic random number generator (SplitMix64), so the same seed always
+/// produces the same world no matter which version of any dependency is used.
+#[derive(Clone, Debug)]
+pub struct Random {
+    state: u64,
+}
+
+impl Random {
+    pub fn new(seed: i64) -> Self {
+        Self { state: seed as u64 }
+    }
+
+    /// Generator for a specific purpose and position, independent of the order things
+    /// are generated in.
+    pub fn for_position(seed: i64, salt: u64, x: i32, z: i32) -> Self {
+        let mut random = Self::new(seed ^ salt as i64);
+        let mixed = random.next_u64()
+            ^ (x as u64).wrapping_mul(0x9E37_79B9_7F4A_7C15)
+            ^ (z as u64).wrapping_mul(0xC2B2_AE3D_27D4_EB4F);
+        Self::new(mixed as i64)
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut value = self.state;
+        value = (value ^ (value >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        value = (value ^ (value >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        value ^ (value >> 31)
+    }
+
+    /// Random number in `0..bound`.
+    pub fn next_below(&mut self, bound: u32) -> u32 {
+        (((self.next_u64() >> 32) * bound as u64) >> 32) as u32
+    }
+
+    /// Random number in `min..=max`, or `min` if the range is empty.
+    pub fn next_between(&mut self, min: i32, max: i32) -> i32 {
+        if max < min {
+            return min;
+        }
+
+        min + self.next_below((max - min) as u32 + 1) as i32
+    }
+
+    /// Random number in `0.0..1.0`.
+    pub fn next_f64(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    pub fn chance(&mut self, probability: f64) -> bool {
+        self.next_f64() < probability
+    }
+}
+
+/// Improved Perlin noise with a permutation table shuffled from the seed.
+#[derive(Clone, Debug)]
+pub struct PerlinNoise {
+    permutation: [u8; 512],
+    offset: (f64, f64, f64),
+}
+
+impl PerlinNoise {
+    pub fn new(random: &mut Random) -> Self {
+        let mut table: [u8; 256] = std::array::from_fn(|index| index as u8);
+        for index in (1..table.len()).rev() {
+            table.swap(index, random.next_below(index as u32 + 1) as usize);
+        }
+
+        Self {
+            permutation: std::array::from_fn(|index| table[index & 0xFF]),
+            offset: (
+                random.next_f64() * 256.0,
+                random.next_f64() * 256.0,
+                random.next_f64() * 256.0,
+            ),
+        }
+    }
+
+    fn fade(t: f64) -> f64 {
+        t * t * t * (t * (t * 6.0 - 15.0) + 10.0)
+    }
+
+    fn lerp(t: f64, a: f64, b: f64) -> f64 {
+        a + t * (b - a)
+    }
+
+    fn gradient(hash: u8, x: f64, y: f64, z: f64) -> f64 {
+        let h = hash & 15;
+        let u = if h < 8 { x } else { y };
+        let v = match h {
+            0..=3 => y,
+            12 | 14 => x,
+            _ => z,
+        };
+
+        (if h & 1 == 0 { u } else { -u }) + (if h & 2 == 0 { v } else { -v })
+    }
+
+    /// Samples the noise, roughly in `-1.0..1.0`.
+    pub fn sample(&self, x: f64, y: f64, z: f64) -> f64 {
+        let (x, y, z) = (x + self.offset.0, y + self.offset.1, z + self.offset.2);
+        let (xi, yi, zi) = (
+            x.floor() as i64 as usize & 0xFF,
+            y.floor() as i64 as usize & 0xFF,
+            z.floor() as i64 as usize & 0xFF,
+        );
+        let (x, y, z) = (x - x.floor(), y - y.floor(), z - z.floor());
+        let (u, v, w) = (Self::fade(x), Self::fade(y), Self::fade(z));
+
+        let p = &self.permutation;
+        let a = p[xi] as usize + yi;
+        let (aa, ab) = (p[a] as usize + zi, p[a + 1] as usize + zi);
+        let b = p[xi + 1] as usize + yi;
+        let (ba, bb) = (p[b] as usize + zi, p[b + 1] as usize + zi);
+
+        Self::lerp(
+            w,
+            Self::lerp(
+                v,
+                Self::lerp(
+                    u,
+                    Self::gradient(p[aa], x, y, z),
+                    Self::gradient(p[ba], x - 1.0, y, z),
+                ),
+                Self::lerp(
+                    u,
+                    Self::gradient(p[ab], x, y - 1.0, z),
+                    Self::gradient(p[bb], x - 1.0, y - 1.0, z),
+                ),
+            ),
+            Self::lerp(
+                v,
+                Self::lerp(
+                    u,
+                    Self::gradient(p[aa + 1], x, y, z - 1.0),
+                    Self::gradient(p[ba + 1], x - 1.0, y, z - 1.0),
+                ),
+                Self::lerp(
+                    u,
+                    Self::gradient(p[ab + 1], x, y - 1.0, z - 1.0),
+                    Self::gradient(p[bb + 1], x - 1.0, y - 1.0, z - 1.0),
+                ),
+            ),
+        )
+    }
+}
+
+/// Several layers of Perlin noise, each at twice the frequency and half the amplitude
+/// of the previous one.
+#[derive(Clone, Debug)]
+pub struct OctaveNoise {
+    octaves: Vec<PerlinNoise>,
+}
+
+impl OctaveNoise {
+    pub fn new(random: &mut Random, octaves: usize) -> Self {
+        Self {
+            octaves: (0..octaves).map(|_| PerlinNoise::new(random)).collect(),
+        }
+    }
+
+    /// Samples the noise, roughly in `-1.0..1.0`.
+    pub fn sample(&self, x: f64, y: f64, z: f64) -> f64 {
+        let mut value = 0.0;
+        let mut frequency = 1.0;
+        let mut amplitude = 1.0;
+        let mut total = 0.0;
+        for octave in &self.octaves {
+            value += octave.sample(x * frequency, y * frequency, z * frequency) * amplitude;
+            total += amplitude;
+            frequency *= 2.0;
+            amplitude /= 2.0;
+        }
+
+        value / total
+    }
+
+    pub fn sample_2d(&self, x: f64, z: f64) -> f64 {
+        self.sample(x, 0.0, z)
+    }
+}
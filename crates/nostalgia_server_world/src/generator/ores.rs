@@ -0,0 +1,103 @@
+use std::f64::consts::PI;
+
+use crate::{BlockID, ChunkNeighbourhood, Decorator, Random, CHUNK_SIZE_X, CHUNK_SIZE_Z};
+
+/// Places veins of an ore into the stone of every chunk.
+#[derive(Clone, Debug)]
+pub struct OreDecorator {
+    pub ore: BlockID,
+    /// Roughly how many blocks a vein has.
+    pub vein_size: u32,
+    pub veins_per_chunk: u32,
+    /// Veins start below this height.
+    pub max_height: i32,
+}
+
+impl OreDecorator {
+    pub const fn new(ore: BlockID, vein_size: u32, veins_per_chunk: u32, max_height: i32) -> Self {
+        Self {
+            ore,
+            vein_size,
+            veins_per_chunk,
+            max_height,
+        }
+    }
+
+    /// The ores of a vanilla world, from the common ones to the rare ones.
+    pub fn vanilla() -> Vec<Self> {
+        vec![
+            Self::new(BlockID::CoalOre, 16, 20, 128),
+            Self::new(BlockID::IronOre, 8, 20, 64),
+            Self::new(BlockID::GoldOre, 8, 2, 32),
+            Self::new(BlockID::RedstoneOre, 7, 8, 16),
+            Self::new(BlockID::DiamondOre, 7, 1, 16),
+            Self::new(BlockID::LapisOre, 6, 1, 32),
+        ]
+    }
+
+    /// Fills a row of blobs along a random line through the given position, which grow
+    /// bigger towards the middle of the line.
+    fn place_vein(
+        &self,
+        neighbourhood: &mut ChunkNeighbourhood,
+        random: &mut Random,
+        (x, y, z): (i32, i32, i32),
+    ) {
+        let size = self.vein_size as f64;
+        let angle = random.next_f64() * PI;
+        let (dx, dz) = (angle.sin() * size / 8.0, angle.cos() * size / 8.0);
+        let start = (
+            x as f64 + dx,
+            (y + random.next_between(-2, 0)) as f64,
+            z as f64 + dz,
+        );
+        let end = (
+            x as f64 - dx,
+            (y + random.next_between(-2, 0)) as f64,
+            z as f64 - dz,
+        );
+
+        for step in 0..=self.vein_size {
+            let t = step as f64 / size;
+            let center = (
+                start.0 + (end.0 - start.0) * t,
+                start.1 + (end.1 - start.1) * t,
+                start.2 + (end.2 - start.2) * t,
+            );
+            let radius = (((t * PI).sin() + 1.0) * random.next_f64() * size / 16.0 + 1.0) / 2.0;
+
+            let min = |value: f64| (value - radius).floor() as i32;
+            let max = |value: f64| (value + radius).floor() as i32;
+            for bx in min(center.0)..=max(center.0) {
+                for by in min(center.1)..=max(center.1) {
+                    for bz in min(center.2)..=max(center.2) {
+                        let distance = ((bx as f64 + 0.5 - center.0) / radius).powi(2)
+                            + ((by as f64 + 0.5 - center.1) / radius).powi(2)
+                            + ((bz as f64 + 0.5 - center.2) / radius).powi(2);
+
+                        if distance < 1.0
+                            && neighbourhood.block_id(bx, by, bz) == Some(BlockID::Stone)
+                        {
+                            neighbourhood.set_block(bx, by, bz, self.ore, 0);
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+impl Decorator for OreDecorator {
+    fn decorate(&self, neighbourhood: &mut ChunkNeighbourhood, random: &mut Random) {
+        let (origin_x, origin_z) = neighbourhood.origin();
+        for _ in 0..self.veins_per_chunk {
+            let position = (
+                origin_x + random.next_below(CHUNK_SIZE_X as u32) as i32,
+                random.next_below(self.max_height as u32) as i32,
+                origin_z + random.next_below(CHUNK_SIZE_Z as u32) as i32,
+            );
+
+            self.place_vein(neighbourhood, random, position);
+        }
+    }
+}
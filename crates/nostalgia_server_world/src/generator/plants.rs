@@ -0,0 +1,136 @@
+use crate::{BlockID, ChunkNeighbourhood, Decorator, Random, CHUNK_SIZE_X, CHUNK_SIZE_Z};
+
+/// How far from the middle of a patch its plants are spread.
+const PATCH_SPREAD: i32 = 7;
+
+/// Scatters a plant around in small patches, wherever it is able to grow.
+#[derive(Clone, Debug)]
+pub struct PlantDecorator {
+    pub plant: BlockID,
+    pub metadata: u8,
+    pub patches_per_chunk: u32,
+    /// How many times a patch tries to place the plant.
+    pub patch_size: u32,
+}
+
+impl PlantDecorator {
+    pub const fn new(
+        plant: BlockID,
+        metadata: u8,
+        patches_per_chunk: u32,
+        patch_size: u32,
+    ) -> Self {
+        Self {
+            plant,
+            metadata,
+            patches_per_chunk,
+            patch_size,
+        }
+    }
+
+    /// The plants of a vanilla world.
+    pub fn vanilla() -> Vec<Self> {
+        vec![
+            Self::new(BlockID::TallGrass, 1, 4, 32),
+            Self::new(BlockID::Dandelion, 0, 1, 16),
+            Self::new(BlockID::Flower, 0, 1, 8),
+            Self::new(BlockID::BrownMushroom, 0, 2, 16),
+            Self::new(BlockID::RedMushroom, 0, 1, 16),
+            Self::new(BlockID::SugarcaneBlock, 0, 4, 16),
+            Self::new(BlockID::Cactus, 0, 2, 8),
+        ]
+    }
+
+    fn is_mushroom(&self) -> bool {
+        matches!(self.plant, BlockID::BrownMushroom | BlockID::RedMushroom)
+    }
+
+    /// Cactus and sugar cane grow up to three blocks tall.
+    fn max_height(&self) -> i32 {
+        match self.plant {
+            BlockID::Cactus | BlockID::SugarcaneBlock => 3,
+            _ => 1,
+        }
+    }
+
+    fn is_water(id: Option<BlockID>) -> bool {
+        matches!(id, Some(BlockID::Water | BlockID::StillWater))
+    }
+
+    fn can_grow(&self, neighbourhood: &ChunkNeighbourhood, x: i32, y: i32, z: i32) -> bool {
+        if neighbourhood.block_id(x, y, z) != Some(BlockID::Air) {
+            return false;
+        }
+
+        let Some(ground) = neighbourhood.block_id(x, y - 1, z) else {
+            return false;
+        };
+
+        let sides = [(x - 1, z), (x + 1, z), (x, z - 1), (x, z + 1)];
+        match self.plant {
+            // Mushrooms only grow in the shade
+            BlockID::BrownMushroom | BlockID::RedMushroom => {
+                matches!(ground, BlockID::Stone | BlockID::Dirt | BlockID::Grass)
+                    && neighbourhood
+                        .height(x, z)
+                        .is_some_and(|height| height > y + 1)
+            }
+            BlockID::Cactus => {
+                matches!(ground, BlockID::Sand | BlockID::Cactus)
+                    && sides.iter().all(|&(side_x, side_z)| {
+                        neighbourhood.block_id(side_x, y, side_z) == Some(BlockID::Air)
+                    })
+            }
+            BlockID::SugarcaneBlock => {
+                ground == BlockID::SugarcaneBlock
+                    || matches!(ground, BlockID::Grass | BlockID::Dirt | BlockID::Sand)
+                        && sides.iter().any(|&(side_x, side_z)| {
+                            Self::is_water(neighbourhood.block_id(side_x, y - 1, side_z))
+                        })
+            }
+            _ => ground == BlockID::Grass,
+        }
+    }
+
+    fn place(
+        &self,
+        neighbourhood: &mut ChunkNeighbourhood,
+        random: &mut Random,
+        (x, z): (i32, i32),
+    ) {
+        let Some(height) = neighbourhood.height(x, z) else {
+            return;
+        };
+
+        let y = if self.is_mushroom() {
+            random.next_between(1, height.max(1))
+        } else {
+            height
+        };
+
+        let stack = random.next_between(1, self.max_height());
+        for plant_y in y..y + stack {
+            if !self.can_grow(neighbourhood, x, plant_y, z) {
+                break;
+            }
+
+            neighbourhood.set_block(x, plant_y, z, self.plant, self.metadata);
+        }
+    }
+}
+
+impl Decorator for PlantDecorator {
+    fn decorate(&self, neighbourhood: &mut ChunkNeighbourhood, random: &mut Random) {
+        let (origin_x, origin_z) = neighbourhood.origin();
+        for _ in 0..self.patches_per_chunk {
+            let center_x = origin_x + random.next_below(CHUNK_SIZE_X as u32) as i32;
+            let center_z = origin_z + random.next_below(CHUNK_SIZE_Z as u32) as i32;
+
+            for _ in 0..self.patch_size {
+                let x = center_x + random.next_between(-PATCH_SPREAD, PATCH_SPREAD);
+                let z = center_z + random.next_between(-PATCH_SPREAD, PATCH_SPREAD);
+                self.place(neighbourhood, random, (x, z));
+            }
+        }
+    }
+}
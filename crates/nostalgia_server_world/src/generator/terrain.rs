@@ -0,0 +1,124 @@
+use crate::{
+    BlockID, CaveDecorator, Chunk, ChunkPos, Decorator, Generator, OctaveNoise, OreDecorator,
+    PlantDecorator, Random, TreeDecorator, CHUNK_SIZE_X, CHUNK_SIZE_Z, WORLD_SIZE_Y,
+};
+
+/// Water fills every block below this height that isn't ground.
+pub const WATER_LEVEL: i32 = 64;
+
+/// Salt for the random numbers deciding the shape of the bedrock floor.
+const BEDROCK_SALT: u64 = 0x0062_6564_726F_636B;
+/// Bedrock is solid at the bottom and gets patchier up to this height.
+const BEDROCK_HEIGHT: i32 = 4;
+
+/// Rolling hills and oceans with sand beaches along the shore.
+pub struct TerrainGenerator {
+    seed: i64,
+    height: OctaveNoise,
+    roughness: OctaveNoise,
+    beach: OctaveNoise,
+
+    /// Features placed after the terrain, starting out with caves, ores, trees and
+    /// plants.
+    pub decorators: Vec<Box<dyn Decorator>>,
+}
+
+impl TerrainGenerator {
+    pub fn new(seed: i64) -> Self {
+        let mut random = Random::new(seed);
+        Self {
+            seed,
+            height: OctaveNoise::new(&mut random, 6),
+            roughness: OctaveNoise::new(&mut random, 4),
+            beach: OctaveNoise::new(&mut random, 3),
+            decorators: Self::default_decorators(),
+        }
+    }
+
+    pub fn default_decorators() -> Vec<Box<dyn Decorator>> {
+        let mut decorators: Vec<Box<dyn Decorator>> = vec![Box::new(CaveDecorator::default())];
+        for ore in OreDecorator::vanilla() {
+            decorators.push(Box::new(ore));
+        }
+
+        decorators.push(Box::new(TreeDecorator::default()));
+        for plant in PlantDecorator::vanilla() {
+            decorators.push(Box::new(plant));
+        }
+
+        decorators
+    }
+
+    /// Height of the topmost ground block at the given world coordinates.
+    pub fn surface_height(&self, x: i32, z: i32) -> i32 {
+        let (x, z) = (x as f64, z as f64);
+        let base = self.height.sample_2d(x / 192.0, z / 192.0);
+        let roughness = (self.roughness.sample_2d(x / 96.0, z / 96.0) + 1.0) / 2.0;
+
+        let height = WATER_LEVEL as f64 + 4.0 + base * 64.0 * (0.4 + roughness);
+        (height as i32).clamp(BEDROCK_HEIGHT + 1, WORLD_SIZE_Y - 16)
+    }
+
+    /// Top and filler blocks of a column with its ground at the given height.
+    fn surface_blocks(&self, x: i32, z: i32, surface: i32) -> (BlockID, BlockID) {
+        let beach = self.beach.sample_2d(x as f64 / 48.0, z as f64 / 48.0);
+
+        if surface >= WATER_LEVEL - 1 {
+            if surface <= WATER_LEVEL + 1 && beach > -0.1 {
+                (BlockID::Sand, BlockID::Sand)
+            } else {
+                (BlockID::Grass, BlockID::Dirt)
+            }
+        } else if surface >= WATER_LEVEL - 5 && beach > -0.1 {
+            (BlockID::Sand, BlockID::Sand)
+        } else if beach < -0.3 {
+            (BlockID::Gravel, BlockID::Gravel)
+        } else {
+            (BlockID::Dirt, BlockID::Dirt)
+        }
+    }
+}
+
+impl Generator for TerrainGenerator {
+    fn generate_chunk(&self, position: ChunkPos) -> Chunk {
+        let mut chunk = Chunk::new();
+
+        for local_x in 0..CHUNK_SIZE_X {
+            for local_z in 0..CHUNK_SIZE_Z {
+                let x = position.x * CHUNK_SIZE_X as i32 + local_x as i32;
+                let z = position.z * CHUNK_SIZE_Z as i32 + local_z as i32;
+
+                let surface = self.surface_height(x, z);
+                let (top, filler) = self.surface_blocks(x, z, surface);
+                let mut random = Random::for_position(self.seed, BEDROCK_SALT, x, z);
+
+                for y in 0..WORLD_SIZE_Y {
+                    let id = if y == 0
+                        || (y < BEDROCK_HEIGHT
+                            && random.next_below(BEDROCK_HEIGHT as u32) >= y as u32)
+                    {
+                        BlockID::Bedrock
+                    } else if y < surface - 3 {
+                        BlockID::Stone
+                    } else if y < surface {
+                        filler
+                    } else if y == surface {
+                        top
+                    } else if y < WATER_LEVEL {
+                        BlockID::StillWater
+                    } else {
+                        break;
+                    };
+
+                    chunk.set_block_id(local_x, y as usize, local_z, id);
+                }
+            }
+        }
+
+        chunk
+    }
+
+    fn decorators(&self) -> &[Box<dyn Decorator>] {
+        &self.decorators
+    }
+}
@@ -0,0 +1,142 @@
+use crate::{
+    BlockID, ChunkNeighbourhood, Decorator, Random, CHUNK_SIZE_X, CHUNK_SIZE_Z, WORLD_SIZE_Y,
+};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TreeKind {
+    Oak,
+    Birch,
+    Spruce,
+}
+
+impl TreeKind {
+    /// Metadata of the wood and leaves of the tree.
+    pub fn metadata(self) -> u8 {
+        match self {
+            TreeKind::Oak => 0,
+            TreeKind::Spruce => 1,
+            TreeKind::Birch => 2,
+        }
+    }
+
+    fn trunk_height(self, random: &mut Random) -> i32 {
+        match self {
+            TreeKind::Oak => random.next_between(4, 6),
+            TreeKind::Birch => random.next_between(5, 7),
+            TreeKind::Spruce => random.next_between(6, 9),
+        }
+    }
+
+    /// Leaf radius of every layer from the bottom of the crown to the top, and the height
+    /// of the bottom layer relative to the top of the trunk.
+    fn crown(self, random: &mut Random, trunk_height: i32) -> (Vec<i32>, i32) {
+        match self {
+            TreeKind::Oak | TreeKind::Birch => (vec![2, 2, 1, 1], -2),
+            TreeKind::Spruce => {
+                let layers = trunk_height - random.next_between(1, 2);
+                let mut radii: Vec<i32> = (0..layers)
+                    .map(|layer| if layer % 2 == 0 { 1 } else { 2 })
+                    .collect();
+                radii.reverse();
+                radii.push(0);
+                (radii, 1 - layers)
+            }
+        }
+    }
+}
+
+/// Grows trees on the grass of the chunks. Their crowns reach into the neighbouring
+/// chunks.
+#[derive(Clone, Debug)]
+pub struct TreeDecorator {
+    pub max_trees_per_chunk: u32,
+}
+
+impl Default for TreeDecorator {
+    fn default() -> Self {
+        Self {
+            max_trees_per_chunk: 4,
+        }
+    }
+}
+
+impl TreeDecorator {
+    fn pick_kind(random: &mut Random) -> TreeKind {
+        match random.next_below(20) {
+            0..=11 => TreeKind::Oak,
+            12..=16 => TreeKind::Birch,
+            _ => TreeKind::Spruce,
+        }
+    }
+
+    fn is_free(neighbourhood: &ChunkNeighbourhood, x: i32, y: i32, z: i32) -> bool {
+        neighbourhood
+            .block_id(x, y, z)
+            .is_some_and(|id| id == BlockID::Air || id == BlockID::Leaves || id.is_replaceable())
+    }
+
+    /// Grows a tree with its trunk starting at the given position, if there's room for it.
+    pub fn grow(
+        neighbourhood: &mut ChunkNeighbourhood,
+        random: &mut Random,
+        kind: TreeKind,
+        (x, y, z): (i32, i32, i32),
+    ) -> bool {
+        let trunk_height = kind.trunk_height(random);
+        let (radii, crown_offset) = kind.crown(random, trunk_height);
+        let top = y + trunk_height - 1;
+        let crown_bottom = top + crown_offset;
+
+        if crown_bottom + radii.len() as i32 > WORLD_SIZE_Y
+            || !matches!(
+                neighbourhood.block_id(x, y - 1, z),
+                Some(BlockID::Grass | BlockID::Dirt)
+            )
+            || !(y..=top).all(|trunk_y| Self::is_free(neighbourhood, x, trunk_y, z))
+        {
+            return false;
+        }
+
+        let metadata = kind.metadata();
+        for (layer, &radius) in radii.iter().enumerate() {
+            let leaf_y = crown_bottom + layer as i32;
+            let is_top = layer + 1 == radii.len();
+            for dx in -radius..=radius {
+                for dz in -radius..=radius {
+                    // Rounds the corners off, sometimes leaving one
+                    let corner = radius > 0 && dx.abs() == radius && dz.abs() == radius;
+                    if corner && (is_top || kind == TreeKind::Spruce || random.chance(0.5)) {
+                        continue;
+                    }
+
+                    if neighbourhood.block_id(x + dx, leaf_y, z + dz) == Some(BlockID::Air) {
+                        neighbourhood.set_block(x + dx, leaf_y, z + dz, BlockID::Leaves, metadata);
+                    }
+                }
+            }
+        }
+
+        neighbourhood.set_block(x, y - 1, z, BlockID::Dirt, 0);
+        for trunk_y in y..=top {
+            neighbourhood.set_block(x, trunk_y, z, BlockID::Wood, metadata);
+        }
+
+        true
+    }
+}
+
+impl Decorator for TreeDecorator {
+    fn decorate(&self, neighbourhood: &mut ChunkNeighbourhood, random: &mut Random) {
+        let (origin_x, origin_z) = neighbourhood.origin();
+        for _ in 0..random.next_below(self.max_trees_per_chunk + 1) {
+            let x = origin_x + random.next_below(CHUNK_SIZE_X as u32) as i32;
+            let z = origin_z + random.next_below(CHUNK_SIZE_Z as u32) as i32;
+            let Some(y) = neighbourhood.height(x, z) else {
+                continue;
+            };
+
+            let kind = Self::pick_kind(random);
+            Self::grow(neighbourhood, random, kind, (x, y, z));
+        }
+    }
+}
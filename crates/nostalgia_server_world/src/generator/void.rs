@@ -0,0 +1,44 @@
+use crate::{to_local_position, BlockID, Chunk, ChunkPos, Generator, CHUNK_SIZE_X, CHUNK_SIZE_Z};
+
+/// How far the spawn platform reaches from its middle, in blocks.
+const PLATFORM_RADIUS: i32 = 2;
+
+/// An empty world with only a small platform to spawn on.
+#[derive(Clone, Debug)]
+pub struct VoidGenerator {
+    platform: (i32, i32, i32),
+}
+
+impl VoidGenerator {
+    /// Creates a generator which puts the platform right below the given spawn position.
+    pub fn new((x, y, z): (i32, i32, i32)) -> Self {
+        Self {
+            platform: (x, y - 1, z),
+        }
+    }
+}
+
+impl Generator for VoidGenerator {
+    fn generate_chunk(&self, position: ChunkPos) -> Chunk {
+        let mut chunk = Chunk::new();
+        let (platform_x, platform_y, platform_z) = self.platform;
+
+        for local_x in 0..CHUNK_SIZE_X {
+            for local_z in 0..CHUNK_SIZE_Z {
+                let x = position.x * CHUNK_SIZE_X as i32 + local_x as i32;
+                let z = position.z * CHUNK_SIZE_Z as i32 + local_z as i32;
+                if (x - platform_x).abs() > PLATFORM_RADIUS
+                    || (z - platform_z).abs() > PLATFORM_RADIUS
+                {
+                    continue;
+                }
+
+                if let Some((_, _, y, _)) = to_local_position(x, platform_y, z) {
+                    chunk.set_block_id(local_x, y, local_z, BlockID::Stone);
+                }
+            }
+        }
+
+        chunk
+    }
+}
@@ -0,0 +1,98 @@
+use crate::{BlockID, BLOCK_REGISTRY};
+
+/// Java block ids which the 0.8 client uses for something else.
+const CONFLICTING_IDS: [u16; 3] = [
+    95,  // Stained glass, invisible bedrock on the client
+    157, // Activator rail, double wooden slab on the client
+    158, // Dropper, wooden slab on the client
+];
+
+/// Redstone, rails and other small or technical Java blocks, which are left out.
+const REMOVED_IDS: [u16; 37] = [
+    27, 28, 34, 36, 55, 66, 69, 70, 72, 77, 90, 93, 94, 106, 111, 115, 117, 118, 119, 127, 131,
+    132, 140, 143, 144, 147, 148, 149, 150, 154, 157, 165, 166, 176, 177, 209, 217,
+];
+
+/// Closest block the client has for Java blocks it doesn't know, as the legacy id and
+/// metadata. Blocks missing from here and the client become stone, so unknown blocks
+/// never leave holes in the ground.
+fn fallback(id: u16, data: u8) -> (BlockID, u8) {
+    match id {
+        id if REMOVED_IDS.contains(&id) => (BlockID::Air, 0),
+        23 | 158 => (BlockID::Furnace, data & 7),
+        25 | 84 => (BlockID::WoodenPlanks, 0),
+        29 | 33 => (BlockID::Cobblestone, 0),
+        52 => (BlockID::MossyStone, 0),
+        75 | 76 => (BlockID::Torch, data),
+        95 => (BlockID::Glass, 0),
+        97 => (BlockID::Stone, 0),
+        99 => (BlockID::Wool, 12),
+        100 => (BlockID::Wool, 14),
+        110 | 208 => (BlockID::Grass, 0),
+        113 => (BlockID::Fence, 0),
+        116 | 122 => (BlockID::Obsidian, 0),
+        120 => (BlockID::Stone, 0),
+        121 | 206 => (BlockID::Sandstone, 0),
+        123 | 124 | 169 => (BlockID::GlowStoneBlock, 0),
+        125 => (BlockID::DoubleWoodenSlab, data & 7),
+        126 => (BlockID::WoodenSlab, data),
+        129 => (BlockID::DiamondOre, 0),
+        130 | 146 => (BlockID::Chest, data),
+        133 => (BlockID::DiamondBlock, 0),
+        137 | 145 => (BlockID::IronBlock, 0),
+        138 => (BlockID::Glass, 0),
+        151 | 178 => (BlockID::WoodenSlab, 0),
+        152 => (BlockID::Wool, 14),
+        153 => (BlockID::Netherrack, 0),
+        159 => (BlockID::Wool, data),
+        160 => (BlockID::GlassPane, 0),
+        161 => (BlockID::Leaves, 0),
+        162 => (BlockID::Wood, 0),
+        163 | 164 => (BlockID::WoodenStairs, data),
+        167 => (BlockID::Trapdoor, data),
+        168 => (BlockID::StoneBrick, 0),
+        172 => (BlockID::ClayBlock, 0),
+        174 => (BlockID::Ice, 0),
+        // Only the lower half of double plants is kept, as tall grass
+        175 if data < 8 => (BlockID::TallGrass, 1),
+        175 => (BlockID::Air, 0),
+        179 => (BlockID::Sandstone, 0),
+        180 => (BlockID::SandstoneStairs, data),
+        181 => (BlockID::DoubleSlabs, 1),
+        182 => (BlockID::Slab, 1 | (data & 8)),
+        183..=187 => (BlockID::FenceGate, data),
+        188..=192 => (BlockID::Fence, 0),
+        193..=197 => (BlockID::WoodenDoorBlock, data),
+        207 => (BlockID::BeetrootBlock, data),
+        212 => (BlockID::Ice, 0),
+        219..=234 => (BlockID::Wool, (id - 219) as u8),
+        235..=250 => (BlockID::Wool, (id - 235) as u8),
+        251 | 252 => (BlockID::Wool, data),
+        _ => (BlockID::Stone, 0),
+    }
+}
+
+/// Maps a Java Edition block id and data value from before 1.13 to the legacy block
+/// and metadata closest to it.
+pub fn java_to_legacy(id: u16, data: u8) -> (BlockID, u8) {
+    let known = !CONFLICTING_IDS.contains(&id)
+        // Ids past the original Java ones are Pocket Edition only, and mean something
+        // else on Java
+        && id < 235
+        && BLOCK_REGISTRY.is_registered(BlockID(id as u8));
+
+    if !known {
+        return fallback(id, data);
+    }
+
+    let block = BlockID(id as u8);
+    let data = match block {
+        // Variants added after the 0.8 client
+        BlockID::Stone | BlockID::Dirt | BlockID::Sand => 0,
+        // Logs keep their direction and leaves their decay flags in the upper bits
+        BlockID::Wood | BlockID::Leaves => data & 3,
+        _ => data,
+    };
+
+    (block, data)
+}
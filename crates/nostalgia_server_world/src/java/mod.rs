@@ -0,0 +1,263 @@
+//! Importing Java Edition worlds from before 1.13, which still stored numeric block ids.
+
+mod blocks;
+mod region;
+
+pub use blocks::*;
+pub use region::*;
+
+use std::{
+    collections::HashMap,
+    io,
+    path::{Path, PathBuf},
+};
+
+use nbt::{BigEndian, Nbt, NbtPath, Tag};
+
+use crate::{
+    invalid_data, Chunk, ChunkPos, LevelData, World, CHUNK_SIZE_X, CHUNK_SIZE_Y, CHUNK_SIZE_Z,
+    WORLD_SIZE_CHUNKS, WORLD_SIZE_X, WORLD_SIZE_Y, WORLD_SIZE_Z,
+};
+
+/// First data version using block names and palettes instead of ids, from 1.13.
+const FLATTENING_DATA_VERSION: i32 = 1451;
+/// Height of the sections Anvil chunks are split into.
+const SECTION_HEIGHT: i32 = 16;
+/// Number of blocks in an Anvil section.
+const SECTION_BLOCKS: usize = CHUNK_SIZE_X * SECTION_HEIGHT as usize * CHUNK_SIZE_Z;
+/// Number of blocks in an MCRegion chunk, which is as high as a legacy one.
+const MCREGION_BLOCKS: usize = CHUNK_SIZE_X * CHUNK_SIZE_Y * CHUNK_SIZE_Z;
+
+#[derive(Clone, Debug, Default)]
+pub struct JavaImportOptions {
+    /// Java chunk that becomes the first chunk of the legacy world, by default the area
+    /// is centered on the Java spawn.
+    pub origin: Option<ChunkPos>,
+    /// Java height that becomes the bottom of the legacy world, which is only half as
+    /// high.
+    pub min_y: i32,
+}
+
+/// Reads the `Blocks`, `Add` and `Data` arrays of a chunk or section, calling `set` with
+/// the Java id and data of every block. `Blocks` has to hold exactly `expected` ids.
+fn read_blocks(
+    blocks: &[u8],
+    expected: usize,
+    add: Option<&Vec<u8>>,
+    data: Option<&Vec<u8>>,
+    mut set: impl FnMut(usize, u16, u8),
+) -> io::Result<()> {
+    if blocks.len() != expected {
+        return Err(invalid_data(format!(
+            "Blocks holds {} ids instead of {}",
+            blocks.len(),
+            expected
+        )));
+    }
+
+    let nibble = |array: Option<&Vec<u8>>, index: usize| {
+        let byte = array.and_then(|array| array.get(index / 2)).copied();
+        let byte = byte.unwrap_or_default();
+        if index.is_multiple_of(2) {
+            byte & 0x0F
+        } else {
+            byte >> 4
+        }
+    };
+
+    for (index, &id) in blocks.iter().enumerate() {
+        let id = id as u16 | (nibble(add, index) as u16) << 8;
+        set(index, id, nibble(data, index));
+    }
+
+    Ok(())
+}
+
+/// Converts a chunk, keeping the Java heights from `min_y` up to the height of the
+/// legacy world. Light is left dark and has to be calculated afterwards.
+pub fn convert_chunk(root: &Tag, min_y: i32) -> io::Result<Chunk> {
+    let mut chunk = Chunk::new();
+    let mut place = |x: usize, y: i32, z: usize, id: u16, data: u8| {
+        let y = y - min_y;
+        if (0..WORLD_SIZE_Y).contains(&y) {
+            let (block, metadata) = java_to_legacy(id, data);
+            chunk.set_block_id(x, y as usize, z, block);
+            chunk.set_metadata(x, y as usize, z, metadata);
+        }
+    };
+
+    if let Some(sections) = root.get_list("Level.Sections") {
+        // Anvil, in sections of 16x16x16 blocks ordered by y, z, x
+        for section in sections {
+            // Sections below 0 have a negative Y
+            let base = section.get_byte("Y").map_or(0, |&y| y as i8 as i32) * SECTION_HEIGHT;
+            let blocks = section.get_byte_array("Blocks").ok_or_else(|| {
+                invalid_data(
+                    "Section has no block ids, worlds from 1.13 on aren't supported".to_string(),
+                )
+            })?;
+
+            read_blocks(
+                blocks,
+                SECTION_BLOCKS,
+                section.get_byte_array("Add"),
+                section.get_byte_array("Data"),
+                |index, id, data| {
+                    let (x, z, y) = (index % 16, index / 16 % 16, index / 256);
+                    place(x, base + y as i32, z, id, data);
+                },
+            )?;
+        }
+    } else if let Some(blocks) = root.get_byte_array("Level.Blocks") {
+        // MCRegion, which is laid out just like legacy chunks
+        read_blocks(
+            blocks,
+            MCREGION_BLOCKS,
+            None,
+            root.get_byte_array("Level.Data"),
+            |index, id, data| {
+                let (x, z, y) = (
+                    index / (CHUNK_SIZE_Y * CHUNK_SIZE_Z),
+                    index / CHUNK_SIZE_Y % CHUNK_SIZE_Z,
+                    index % CHUNK_SIZE_Y,
+                );
+                place(x, y as i32, z, id, data);
+            },
+        )?;
+    } else {
+        return Err(invalid_data(
+            "Chunk has neither sections nor blocks, worlds from 1.13 on aren't supported"
+                .to_string(),
+        ));
+    }
+
+    Ok(chunk)
+}
+
+/// Reads the Java `level.dat`, which is gzipped big-endian NBT with everything inside
+/// of `Data`.
+fn read_java_level(path: &Path) -> io::Result<LevelData> {
+    let (nbt, _) = Nbt::from_compressed::<BigEndian>(std::fs::read(path)?)?;
+    let data = nbt
+        .root()
+        .get_at(&"Data".parse::<NbtPath>()?)
+        .ok_or_else(|| invalid_data("level.dat has no Data".to_string()))?;
+
+    if let Some(&version) = data.get_int("DataVersion") {
+        if version >= FLATTENING_DATA_VERSION {
+            return Err(invalid_data(format!(
+                "World has data version {version}, worlds from 1.13 on aren't supported"
+            )));
+        }
+    }
+
+    let defaults = LevelData::default();
+    Ok(LevelData {
+        name: data
+            .get_string("LevelName")
+            .cloned()
+            .unwrap_or(defaults.name),
+        seed: data.get_long("RandomSeed").copied().unwrap_or_default(),
+        // Adventure and spectator aren't supported by the client
+        game_type: data
+            .get_int("GameType")
+            .map_or(0, |&game_type| (game_type == 1) as i32),
+        time: data.get_long("Time").copied().unwrap_or_default(),
+        spawn_x: data.get_int("SpawnX").copied().unwrap_or_default(),
+        spawn_y: data.get_int("SpawnY").copied().unwrap_or(64),
+        spawn_z: data.get_int("SpawnZ").copied().unwrap_or_default(),
+        ..defaults
+    })
+}
+
+/// Opens region files as chunks from them are needed, preferring Anvil over MCRegion
+/// when a world has both.
+struct RegionCache {
+    directory: PathBuf,
+    regions: HashMap<(i32, i32), Option<RegionFile>>,
+}
+
+impl RegionCache {
+    fn region(&mut self, x: i32, z: i32) -> io::Result<Option<&RegionFile>> {
+        if !self.regions.contains_key(&(x, z)) {
+            let region = ["mca", "mcr"]
+                .iter()
+                .map(|extension| self.directory.join(format!("r.{x}.{z}.{extension}")))
+                .find(|path| path.exists())
+                .map(|path| RegionFile::open(&path))
+                .transpose()?;
+
+            self.regions.insert((x, z), region);
+        }
+
+        Ok(self.regions[&(x, z)].as_ref())
+    }
+
+    fn chunk(&mut self, position: ChunkPos) -> io::Result<Option<Nbt>> {
+        let region = self.region(
+            position.x.div_euclid(REGION_SIZE),
+            position.z.div_euclid(REGION_SIZE),
+        )?;
+
+        match region {
+            Some(region) => region.chunk(position.x, position.z),
+            None => Ok(None),
+        }
+    }
+}
+
+impl World {
+    /// Imports the 256x256 area of a Java Edition world from before 1.13 starting at the
+    /// origin chunk. Chunks missing from the Java world are left empty, and entities and
+    /// tile entities aren't imported.
+    pub fn import_java(path: &Path, options: &JavaImportOptions) -> io::Result<Self> {
+        let level_path = path.join("level.dat");
+        let mut level = if level_path.exists() {
+            read_java_level(&level_path)?
+        } else {
+            LevelData {
+                spawn_x: 0,
+                spawn_z: 0,
+                ..Default::default()
+            }
+        };
+
+        let origin = options.origin.unwrap_or_else(|| {
+            let spawn = ChunkPos::from_block(level.spawn_x, level.spawn_z);
+            ChunkPos::new(
+                spawn.x - WORLD_SIZE_CHUNKS / 2,
+                spawn.z - WORLD_SIZE_CHUNKS / 2,
+            )
+        });
+
+        let mut regions = RegionCache {
+            directory: path.join("region"),
+            regions: HashMap::new(),
+        };
+
+        let mut world = World::new(LevelData::default());
+        for position in ChunkPos::all() {
+            let java = ChunkPos::new(origin.x + position.x, origin.z + position.z);
+            let chunk = match regions.chunk(java)? {
+                Some(nbt) => convert_chunk(nbt.root(), options.min_y).map_err(|error| {
+                    invalid_data(format!("Chunk {}, {}: {}", java.x, java.z, error))
+                })?,
+                None => Chunk::new(),
+            };
+
+            world.insert_chunk(position, chunk);
+        }
+
+        level.spawn_x = (level.spawn_x - origin.x * CHUNK_SIZE_X as i32).clamp(0, WORLD_SIZE_X - 1);
+        level.spawn_y = (level.spawn_y - options.min_y).clamp(1, WORLD_SIZE_Y - 1);
+        level.spawn_z = (level.spawn_z - origin.z * CHUNK_SIZE_Z as i32).clamp(0, WORLD_SIZE_Z - 1);
+        world.level = level;
+
+        world.calculate_light();
+        if let Some(spawn) = world.find_safe_spawn() {
+            world.level.set_spawn_position(spawn);
+        }
+
+        Ok(world)
+    }
+}
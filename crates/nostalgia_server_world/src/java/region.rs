@@ -0,0 +1,72 @@
+use std::{
+    io::{self, Cursor},
+    path::Path,
+};
+
+use byteorder::{BigEndian, ReadBytesExt};
+use nbt::{Compression, Nbt};
+
+use crate::invalid_data;
+
+const SECTOR_SIZE: usize = 4096;
+/// Regions are 32x32 chunks.
+pub const REGION_SIZE: i32 = 32;
+
+/// A Java Edition region file, either Anvil (`.mca`) or the older MCRegion (`.mcr`),
+/// which share the same layout and only differ in what the chunks contain.
+pub struct RegionFile {
+    bytes: Vec<u8>,
+}
+
+impl RegionFile {
+    pub fn from_bytes(bytes: Vec<u8>) -> io::Result<Self> {
+        if bytes.len() < SECTOR_SIZE * 2 {
+            return Err(invalid_data("Region file is shorter than its header"));
+        }
+
+        Ok(Self { bytes })
+    }
+
+    pub fn open(path: &Path) -> io::Result<Self> {
+        Self::from_bytes(std::fs::read(path)?)
+    }
+
+    /// Reads the chunk at the given position inside the region, `None` if the chunk was
+    /// never generated.
+    pub fn chunk(&self, x: i32, z: i32) -> io::Result<Option<Nbt>> {
+        let index = (x.rem_euclid(REGION_SIZE) + z.rem_euclid(REGION_SIZE) * REGION_SIZE) as usize;
+        let mut header = Cursor::new(&self.bytes[index * 4..index * 4 + 4]);
+        let location = header.read_u32::<BigEndian>()?;
+        let (sector, sectors) = ((location >> 8) as usize, (location & 0xFF) as usize);
+        if sector == 0 || sectors == 0 {
+            return Ok(None);
+        }
+
+        let start = sector * SECTOR_SIZE;
+        let invalid = || invalid_data(format!("Chunk {x}, {z} points outside of the region file"));
+
+        let mut cursor = Cursor::new(self.bytes.get(start..start + 5).ok_or_else(invalid)?);
+        let length = cursor.read_u32::<BigEndian>()? as usize;
+        let compression = match cursor.read_u8()? {
+            1 => Compression::Gzip,
+            2 => Compression::Zlib,
+            3 => Compression::None,
+            other => {
+                return Err(invalid_data(format!(
+                    "Chunk {x}, {z} uses unknown compression {other}"
+                )))
+            }
+        };
+
+        // The length includes the compression byte
+        let data = self
+            .bytes
+            .get(start + 5..start + 4 + length)
+            .ok_or_else(invalid)?;
+
+        let data = compression.decompress(data.to_vec())?;
+        Ok(Some(Nbt::from_bytes_with::<BigEndian>(&mut Cursor::new(
+            data,
+        ))?))
+    }
+}
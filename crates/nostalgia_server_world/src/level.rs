@@ -0,0 +1,151 @@
+use std::io;
+
+use nbt::{Compound, Header, Nbt, Tag};
+use serde::{Deserialize, Serialize};
+
+use crate::invalid_data;
+
+/// Version of the `level.dat` layout, stored both in the file header and as `StorageVersion`.
+pub use nbt::LEVEL_STORAGE_VERSION;
+/// Version of `level.dat` next to a LevelDB store, see [`World::save_leveldb`](crate::World::save_leveldb).
+pub const LEVELDB_STORAGE_VERSION: i32 = 4;
+
+/// The contents of `level.dat`. Keys missing from the file fall back to their defaults,
+/// and keys this doesn't know about are kept in `unknown` so they survive a save.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LevelData {
+    #[serde(rename = "LevelName")]
+    pub name: String,
+    #[serde(rename = "RandomSeed")]
+    pub seed: i64,
+    #[serde(rename = "GameType")]
+    pub game_type: i32,
+    #[serde(rename = "Platform")]
+    pub platform: i32,
+    #[serde(rename = "StorageVersion")]
+    pub storage_version: i32,
+    #[serde(rename = "Time")]
+    pub time: i64,
+    #[serde(rename = "LastPlayed")]
+    pub last_played: i64,
+    /// Time of day the day cycle is frozen at, or -1 if it runs normally.
+    #[serde(rename = "dayCycleStopTime")]
+    pub day_cycle_stop_time: i64,
+    #[serde(rename = "spawnMobs", with = "byte_bool")]
+    pub spawn_mobs: bool,
+    #[serde(rename = "SpawnX")]
+    pub spawn_x: i32,
+    #[serde(rename = "SpawnY")]
+    pub spawn_y: i32,
+    #[serde(rename = "SpawnZ")]
+    pub spawn_z: i32,
+
+    /// Copied straight from and to the root compound instead of going through serde,
+    /// which can't tell the element type of empty lists.
+    #[serde(skip)]
+    pub unknown: Compound,
+}
+
+impl Default for LevelData {
+    fn default() -> Self {
+        Self {
+            name: "World".to_string(),
+            seed: 0,
+            game_type: 0,
+            platform: 2,
+            storage_version: LEVEL_STORAGE_VERSION,
+            time: 0,
+            last_played: 0,
+            day_cycle_stop_time: -1,
+            spawn_mobs: true,
+            spawn_x: 128,
+            spawn_y: 64,
+            spawn_z: 128,
+            unknown: Compound::new(),
+        }
+    }
+}
+
+/// Booleans are stored as bytes, so they're read and written through `u8`.
+mod byte_bool {
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(value: &bool, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_u8(*value as u8)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<bool, D::Error> {
+        Ok(u8::deserialize(deserializer)? != 0)
+    }
+}
+
+impl LevelData {
+    pub fn spawn_position(&self) -> (i32, i32, i32) {
+        (self.spawn_x, self.spawn_y, self.spawn_z)
+    }
+
+    pub fn set_spawn_position(&mut self, (x, y, z): (i32, i32, i32)) {
+        self.spawn_x = x;
+        self.spawn_y = y;
+        self.spawn_z = z;
+    }
+
+    /// Reads `level.dat`, rejecting storage versions other than the one this understands.
+    pub fn from_bytes(bytes: Vec<u8>) -> io::Result<Self> {
+        Self::from_bytes_with_version(bytes, LEVEL_STORAGE_VERSION)
+    }
+
+    /// Reads `level.dat`, rejecting storage versions other than `expected`.
+    pub fn from_bytes_with_version(bytes: Vec<u8>, expected: i32) -> io::Result<Self> {
+        let (nbt, header) = Nbt::from_pocket_file(bytes)?;
+        let Header::Level(version) = header else {
+            return Err(invalid_data("level.dat is missing its header".to_string()));
+        };
+
+        if version != expected {
+            return Err(invalid_data(format!(
+                "Unsupported level.dat storage version {}, expected {}",
+                version, expected
+            )));
+        }
+
+        let mut level: LevelData = nbt::from_tag(nbt.root().clone())?;
+        if let (Tag::Compound(tags), Tag::Compound(known)) = (nbt.root(), nbt::to_tag(&level)?) {
+            level.unknown = tags
+                .iter()
+                .filter(|(name, _)| !known.contains_key(*name))
+                .map(|(name, tag)| (name.clone(), tag.clone()))
+                .collect();
+        }
+
+        if level.storage_version != expected {
+            return Err(invalid_data(format!(
+                "Unsupported StorageVersion {}, expected {}",
+                level.storage_version, expected
+            )));
+        }
+
+        Ok(level)
+    }
+
+    /// Writes `level.dat` with its header, keys sorted the same way the original client does.
+    pub fn to_bytes(&self) -> io::Result<Vec<u8>> {
+        self.to_bytes_with_version(LEVEL_STORAGE_VERSION)
+    }
+
+    /// Writes `level.dat` with the given storage version, in the header and as
+    /// `StorageVersion` whatever `storage_version` is.
+    pub fn to_bytes_with_version(&self, version: i32) -> io::Result<Vec<u8>> {
+        let Tag::Compound(mut tags) = nbt::to_tag(self)? else {
+            unreachable!("Structs always serialize to compounds");
+        };
+        for (name, tag) in &self.unknown {
+            tags.entry(name.clone()).or_insert_with(|| tag.clone());
+        }
+        tags.insert("StorageVersion".to_string(), Tag::Int(version));
+        tags.sort_keys();
+
+        Ok(Nbt::new(tags).to_pocket_file(Header::Level(version))?)
+    }
+}
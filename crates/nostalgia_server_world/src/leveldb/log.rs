@@ -0,0 +1,223 @@
+use std::io;
+
+use crate::invalid_data;
+
+/// Logs are split into blocks, and records never straddle a block header.
+const BLOCK_SIZE: usize = 32768;
+const HEADER_SIZE: usize = 7;
+
+const FULL: u8 = 1;
+const FIRST: u8 = 2;
+const MIDDLE: u8 = 3;
+const LAST: u8 = 4;
+
+const CRC32C_TABLE: [u32; 256] = {
+    let mut table = [0u32; 256];
+    let mut index = 0;
+    while index < 256 {
+        let mut crc = index as u32;
+        let mut bit = 0;
+        while bit < 8 {
+            crc = if crc & 1 != 0 {
+                (crc >> 1) ^ 0x82F6_3B78
+            } else {
+                crc >> 1
+            };
+            bit += 1;
+        }
+
+        table[index] = crc;
+        index += 1;
+    }
+
+    table
+};
+
+fn crc32c(parts: &[&[u8]]) -> u32 {
+    let mut crc = !0u32;
+    for &byte in parts.iter().flat_map(|part| part.iter()) {
+        crc = CRC32C_TABLE[((crc ^ byte as u32) & 0xFF) as usize] ^ (crc >> 8);
+    }
+
+    !crc
+}
+
+/// Checksums are masked, as checksums of data holding checksums are weak otherwise.
+fn masked_crc(kind: u8, data: &[u8]) -> u32 {
+    let crc = crc32c(&[&[kind], data]);
+    crc.rotate_right(15).wrapping_add(0xA282_EAD8)
+}
+
+/// Writes records in the LevelDB log format, used by both the write-ahead log and the
+/// manifest.
+#[derive(Default)]
+pub struct LogWriter {
+    buffer: Vec<u8>,
+}
+
+impl LogWriter {
+    pub fn add_record(&mut self, mut record: &[u8]) {
+        let mut first = true;
+        loop {
+            let left_in_block = BLOCK_SIZE - self.buffer.len() % BLOCK_SIZE;
+            if left_in_block < HEADER_SIZE {
+                self.buffer.resize(self.buffer.len() + left_in_block, 0);
+                continue;
+            }
+
+            let length = record.len().min(left_in_block - HEADER_SIZE);
+            let last = length == record.len();
+            let kind = match (first, last) {
+                (true, true) => FULL,
+                (true, false) => FIRST,
+                (false, false) => MIDDLE,
+                (false, true) => LAST,
+            };
+
+            let (fragment, rest) = record.split_at(length);
+            self.buffer
+                .extend_from_slice(&masked_crc(kind, fragment).to_le_bytes());
+            self.buffer
+                .extend_from_slice(&(length as u16).to_le_bytes());
+            self.buffer.push(kind);
+            self.buffer.extend_from_slice(fragment);
+
+            if last {
+                return;
+            }
+
+            record = rest;
+            first = false;
+        }
+    }
+
+    pub fn into_bytes(self) -> Vec<u8> {
+        self.buffer
+    }
+}
+
+/// Splits a log back into records, failing on corrupted ones.
+pub fn read_records(bytes: &[u8]) -> io::Result<Vec<Vec<u8>>> {
+    let mut records = Vec::new();
+    let mut record: Option<Vec<u8>> = None;
+    let mut offset = 0;
+    while offset + HEADER_SIZE <= bytes.len() {
+        let left_in_block = BLOCK_SIZE - offset % BLOCK_SIZE;
+        if left_in_block < HEADER_SIZE {
+            offset += left_in_block;
+            continue;
+        }
+
+        let header = &bytes[offset..offset + HEADER_SIZE];
+        let crc = u32::from_le_bytes(header[..4].try_into().unwrap());
+        let length = u16::from_le_bytes(header[4..6].try_into().unwrap()) as usize;
+        let kind = header[6];
+        // Writers may preallocate the log with zeroes
+        if kind == 0 && length == 0 {
+            break;
+        }
+
+        let fragment = bytes
+            .get(offset + HEADER_SIZE..offset + HEADER_SIZE + length)
+            .ok_or_else(|| invalid_data("Log record is cut off"))?;
+        if masked_crc(kind, fragment) != crc {
+            return Err(invalid_data("Log record has a bad checksum"));
+        }
+
+        offset += HEADER_SIZE + length;
+        match (kind, record.as_mut()) {
+            (FULL, None) => records.push(fragment.to_vec()),
+            (FIRST, None) => record = Some(fragment.to_vec()),
+            (MIDDLE, Some(record)) => record.extend_from_slice(fragment),
+            (LAST, Some(_)) => {
+                let mut record = record.take().unwrap();
+                record.extend_from_slice(fragment);
+                records.push(record);
+            }
+            _ => return Err(invalid_data("Log record fragments are out of order")),
+        }
+    }
+
+    Ok(records)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn crc32c_matches_the_check_value() {
+        assert_eq!(crc32c(&[b"123456789"]), 0xE306_9283);
+        assert_eq!(crc32c(&[b"1234", b"56789"]), 0xE306_9283);
+        assert_eq!(crc32c(&[]), 0);
+    }
+
+    #[test]
+    fn records_read_back_the_same() {
+        let records = [vec![1, 2, 3], vec![], vec![0xAB; 100]];
+        let mut writer = LogWriter::default();
+        for record in &records {
+            writer.add_record(record);
+        }
+
+        assert_eq!(read_records(&writer.into_bytes()).unwrap(), records);
+    }
+
+    #[test]
+    fn large_records_are_split_into_fragments() {
+        let record: Vec<u8> = (0..BLOCK_SIZE * 2 + 100).map(|i| i as u8).collect();
+        let mut writer = LogWriter::default();
+        writer.add_record(&[7; 10]);
+        writer.add_record(&record);
+        let bytes = writer.into_bytes();
+
+        // First, middle and last fragments, each behind a header of its own
+        assert_eq!(bytes[HEADER_SIZE + 10 + 6], FIRST);
+        assert_eq!(bytes[BLOCK_SIZE + 6], MIDDLE);
+        assert_eq!(bytes[BLOCK_SIZE * 2 + 6], LAST);
+        assert_eq!(bytes.len(), record.len() + 10 + HEADER_SIZE * 4);
+
+        assert_eq!(read_records(&bytes).unwrap(), vec![vec![7; 10], record]);
+    }
+
+    #[test]
+    fn block_ends_too_short_for_a_header_are_skipped() {
+        // Leaves 3 bytes at the end of the first block
+        let first = vec![1; BLOCK_SIZE - HEADER_SIZE - 3];
+        let mut writer = LogWriter::default();
+        writer.add_record(&first);
+        writer.add_record(&[2, 2]);
+        let bytes = writer.into_bytes();
+
+        assert_eq!(bytes[BLOCK_SIZE - 3..BLOCK_SIZE], [0, 0, 0]);
+        assert_eq!(read_records(&bytes).unwrap(), vec![first, vec![2, 2]]);
+    }
+
+    #[test]
+    fn corrupted_logs_are_rejected() {
+        let mut writer = LogWriter::default();
+        writer.add_record(&[1, 2, 3, 4]);
+        let bytes = writer.into_bytes();
+
+        let mut flipped = bytes.clone();
+        flipped[HEADER_SIZE] ^= 1;
+        assert!(read_records(&flipped).is_err());
+        assert!(read_records(&bytes[..bytes.len() - 1]).is_err());
+
+        // A middle fragment without a first one
+        let mut orphan = bytes.clone();
+        orphan[6] = MIDDLE;
+        orphan[..4].copy_from_slice(&masked_crc(MIDDLE, &[1, 2, 3, 4]).to_le_bytes());
+        assert!(read_records(&orphan).is_err());
+    }
+
+    #[test]
+    fn trailing_zeroes_end_the_log() {
+        let mut writer = LogWriter::default();
+        writer.add_record(&[5]);
+        let mut bytes = writer.into_bytes();
+        bytes.resize(BLOCK_SIZE, 0);
+
+        assert_eq!(read_records(&bytes).unwrap(), vec![vec![5]]);
+    }
+}
@@ -0,0 +1,211 @@
+mod log;
+mod store;
+
+use std::{
+    collections::HashMap,
+    io::{self, Cursor},
+    path::Path,
+};
+
+use nbt::{Nbt, Tag};
+
+pub use store::*;
+
+use crate::{
+    invalid_data, Chunk, ChunkPos, LevelData, World, CHUNK_SIZE_X, CHUNK_SIZE_Z,
+    CHUNK_TERRAIN_SIZE, LEVELDB_STORAGE_VERSION, LEVEL_STORAGE_VERSION,
+};
+
+// Last byte of the keys holding a chunk's records, after its x and z
+const TERRAIN_KEY: u8 = b'0';
+const TILE_ENTITIES_KEY: u8 = b'1';
+const ENTITIES_KEY: u8 = b'2';
+const VERSION_KEY: u8 = b'v';
+
+/// Version of the chunk records, the last one storing terrain as a single record.
+const CHUNK_VERSION: u8 = 2;
+
+const COLUMN_COUNT: usize = CHUNK_SIZE_X * CHUNK_SIZE_Z;
+/// Terrain records end with a height map and the biome and grass color of every column.
+const TERRAIN_RECORD_SIZE: usize = CHUNK_TERRAIN_SIZE + COLUMN_COUNT + COLUMN_COUNT * 4;
+/// Plains, with their grass color.
+const DEFAULT_BIOME: [u8; 4] = [1, 0x7F, 0xB2, 0x38];
+
+fn chunk_key(position: ChunkPos, kind: u8) -> Vec<u8> {
+    let mut key = Vec::with_capacity(9);
+    key.extend_from_slice(&position.x.to_le_bytes());
+    key.extend_from_slice(&position.z.to_le_bytes());
+    key.push(kind);
+    key
+}
+
+fn parse_chunk_key(key: &[u8]) -> Option<(ChunkPos, u8)> {
+    let [x0, x1, x2, x3, z0, z1, z2, z3, kind] = *key else {
+        return None;
+    };
+
+    let x = i32::from_le_bytes([x0, x1, x2, x3]);
+    let z = i32::from_le_bytes([z0, z1, z2, z3]);
+    Some((ChunkPos::new(x, z), kind))
+}
+
+fn terrain_record(chunk: &Chunk) -> Vec<u8> {
+    let mut record = Vec::with_capacity(TERRAIN_RECORD_SIZE);
+    record.extend_from_slice(chunk.terrain());
+    for column in 0..COLUMN_COUNT {
+        record.push(chunk.height(column % CHUNK_SIZE_X, column / CHUNK_SIZE_X) as u8);
+    }
+
+    for _ in 0..COLUMN_COUNT {
+        record.extend_from_slice(&DEFAULT_BIOME);
+    }
+
+    record
+}
+
+/// Chunk a tile entity or entity is in, from its `x`/`z` tags or its `Pos` list.
+fn tag_chunk(tag: &Tag) -> Option<ChunkPos> {
+    let (x, z) = match (tag.get_int("x"), tag.get_int("z")) {
+        (Some(&x), Some(&z)) => (x, z),
+        _ => {
+            let position = tag.get_list("Pos")?;
+            let coordinate = |tag: Option<&Tag>| match tag? {
+                Tag::Float(value) => Some(value.floor() as i32),
+                Tag::Double(value) => Some(value.floor() as i32),
+                _ => None,
+            };
+
+            (coordinate(position.first())?, coordinate(position.get(2))?)
+        }
+    };
+
+    Some(ChunkPos::new(
+        x.div_euclid(CHUNK_SIZE_X as i32),
+        z.div_euclid(CHUNK_SIZE_Z as i32),
+    ))
+}
+
+/// Concatenates the tags of every chunk into one record per chunk.
+fn group_by_chunk(tags: &[Tag]) -> io::Result<HashMap<ChunkPos, Vec<u8>>> {
+    let mut records: HashMap<ChunkPos, Cursor<Vec<u8>>> = HashMap::new();
+    for tag in tags {
+        let Tag::Compound(tags) = tag else {
+            continue;
+        };
+
+        // Tags without a position are kept with the first chunk instead of being lost
+        let position = tag_chunk(tag).unwrap_or(ChunkPos::new(0, 0));
+        let record = records.entry(position).or_default();
+        Nbt::new(tags.clone()).to_bytes(record)?;
+    }
+
+    Ok(records
+        .into_iter()
+        .map(|(position, record)| (position, record.into_inner()))
+        .collect())
+}
+
+fn read_tags(record: &[u8]) -> io::Result<Vec<Tag>> {
+    let mut cursor = Cursor::new(record.to_vec());
+    let mut tags = Vec::new();
+    while (cursor.position() as usize) < record.len() {
+        tags.push(Nbt::from_bytes(&mut cursor)?.root().clone());
+    }
+
+    Ok(tags)
+}
+
+impl World {
+    /// Puts the chunks, entities and tile entities into a store laid out the way newer
+    /// Pocket Edition clients expect.
+    pub fn to_leveldb(&self) -> io::Result<LevelDb> {
+        let mut store = LevelDb::new();
+        for (position, chunk) in self.chunks() {
+            store.put(chunk_key(position, VERSION_KEY), vec![CHUNK_VERSION]);
+            store.put(chunk_key(position, TERRAIN_KEY), terrain_record(chunk));
+        }
+
+        for (tags, kind) in [
+            (&self.entities, ENTITIES_KEY),
+            (&self.tile_entities, TILE_ENTITIES_KEY),
+        ] {
+            for (position, record) in group_by_chunk(tags)? {
+                store.put(chunk_key(position, kind), record);
+            }
+        }
+
+        Ok(store)
+    }
+
+    /// Reads the chunks, entities and tile entities back from a store. Chunks outside of
+    /// the legacy world limits are left out, and missing ones are filled in empty.
+    /// Entities and tile entities are kept wherever they are, just like in legacy saves.
+    pub fn from_leveldb(level: LevelData, store: &LevelDb) -> io::Result<Self> {
+        let mut world = World::new(level);
+        for (key, value) in store.iter() {
+            let Some((position, kind)) = parse_chunk_key(key) else {
+                continue;
+            };
+
+            match kind {
+                TERRAIN_KEY if !position.is_valid() => {}
+                TERRAIN_KEY => {
+                    if value.len() != TERRAIN_RECORD_SIZE {
+                        return Err(invalid_data(format!(
+                            "Chunk {}, {} has {} bytes of terrain, expected {}",
+                            position.x,
+                            position.z,
+                            value.len(),
+                            TERRAIN_RECORD_SIZE
+                        )));
+                    }
+
+                    let chunk = Chunk::from_terrain(&value[..CHUNK_TERRAIN_SIZE])?;
+                    world.insert_chunk(position, chunk);
+                }
+                ENTITIES_KEY => world.entities.extend(read_tags(value)?),
+                TILE_ENTITIES_KEY => world.tile_entities.extend(read_tags(value)?),
+                _ => {}
+            }
+        }
+
+        for position in ChunkPos::all() {
+            if world.chunk_at(position.x, position.z).is_none() {
+                world.insert_chunk(position, Chunk::new());
+            }
+        }
+
+        Ok(world)
+    }
+
+    /// Writes the world the way newer clients store it: `level.dat` at storage version 4,
+    /// `levelname.txt`, and the chunks in a LevelDB store under `db`.
+    pub fn save_leveldb(&self, path: &Path) -> io::Result<()> {
+        std::fs::create_dir_all(path)?;
+
+        let mut level = self.level.clone();
+        // Newer clients generate infinite worlds unless told this is a limited one
+        level
+            .unknown
+            .entry("Generator".to_string())
+            .or_insert(Tag::Int(0));
+
+        std::fs::write(
+            path.join("level.dat"),
+            level.to_bytes_with_version(LEVELDB_STORAGE_VERSION)?,
+        )?;
+        std::fs::write(path.join("levelname.txt"), &level.name)?;
+        self.to_leveldb()?.save(&path.join("db"))
+    }
+
+    /// Reads a world written by [`World::save_leveldb`], or by a client as long as
+    /// LevelDB hasn't compacted its store yet.
+    pub fn from_leveldb_file(path: &Path) -> io::Result<Self> {
+        let level = std::fs::read(path.join("level.dat"))?;
+        let mut level = LevelData::from_bytes_with_version(level, LEVELDB_STORAGE_VERSION)?;
+        level.storage_version = LEVEL_STORAGE_VERSION;
+
+        let store = LevelDb::open(&path.join("db"))?;
+        World::from_leveldb(level, &store)
+    }
+}
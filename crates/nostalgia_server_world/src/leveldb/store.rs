@@ -0,0 +1,294 @@
+use std::{collections::BTreeMap, fs, io, path::Path};
+
+use super::log::{read_records, LogWriter};
+use crate::invalid_data;
+
+const COMPARATOR: &str = "leveldb.BytewiseComparator";
+
+const MANIFEST_NUMBER: u64 = 2;
+const LOG_NUMBER: u64 = 3;
+
+// Version edit fields, stored in the manifest
+const EDIT_COMPARATOR: u64 = 1;
+const EDIT_LOG_NUMBER: u64 = 2;
+const EDIT_NEXT_FILE_NUMBER: u64 = 3;
+const EDIT_LAST_SEQUENCE: u64 = 4;
+const EDIT_COMPACT_POINTER: u64 = 5;
+const EDIT_DELETED_FILE: u64 = 6;
+const EDIT_NEW_FILE: u64 = 7;
+const EDIT_PREV_LOG_NUMBER: u64 = 9;
+
+// Entry kinds in a write batch
+const DELETION: u8 = 0;
+const VALUE: u8 = 1;
+
+fn write_varint(buffer: &mut Vec<u8>, mut value: u64) {
+    while value >= 0x80 {
+        buffer.push(value as u8 | 0x80);
+        value >>= 7;
+    }
+
+    buffer.push(value as u8);
+}
+
+fn write_slice(buffer: &mut Vec<u8>, slice: &[u8]) {
+    write_varint(buffer, slice.len() as u64);
+    buffer.extend_from_slice(slice);
+}
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    fn is_empty(&self) -> bool {
+        self.bytes.is_empty()
+    }
+
+    fn bytes(&mut self, length: usize) -> io::Result<&'a [u8]> {
+        if length > self.bytes.len() {
+            return Err(invalid_data("Record is cut off".to_string()));
+        }
+
+        let (bytes, rest) = self.bytes.split_at(length);
+        self.bytes = rest;
+        Ok(bytes)
+    }
+
+    fn byte(&mut self) -> io::Result<u8> {
+        Ok(self.bytes(1)?[0])
+    }
+
+    fn varint(&mut self) -> io::Result<u64> {
+        let mut value = 0;
+        for shift in (0..64).step_by(7) {
+            let byte = self.byte()?;
+            value |= ((byte & 0x7F) as u64) << shift;
+            if byte & 0x80 == 0 {
+                return Ok(value);
+            }
+        }
+
+        Err(invalid_data("Varint is too long".to_string()))
+    }
+
+    fn slice(&mut self) -> io::Result<&'a [u8]> {
+        let length = self.varint()? as usize;
+        self.bytes(length)
+    }
+}
+
+/// A key/value store in the LevelDB layout newer Pocket Edition clients keep worlds in.
+///
+/// Everything is kept in memory and saved as a single write-ahead log, which LevelDB
+/// turns into tables the next time it opens the store. Stores LevelDB has already
+/// compacted into tables can't be read.
+#[derive(Clone, Debug, Default)]
+pub struct LevelDb {
+    entries: BTreeMap<Vec<u8>, Vec<u8>>,
+}
+
+impl LevelDb {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn get(&self, key: &[u8]) -> Option<&[u8]> {
+        self.entries.get(key).map(Vec::as_slice)
+    }
+
+    pub fn put(&mut self, key: Vec<u8>, value: Vec<u8>) {
+        self.entries.insert(key, value);
+    }
+
+    pub fn delete(&mut self, key: &[u8]) -> Option<Vec<u8>> {
+        self.entries.remove(key)
+    }
+
+    /// Entries in key order.
+    pub fn iter(&self) -> impl Iterator<Item = (&[u8], &[u8])> {
+        self.entries
+            .iter()
+            .map(|(key, value)| (key.as_slice(), value.as_slice()))
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Reads the manifest `CURRENT` points to and replays the logs it lists.
+    pub fn open(path: &Path) -> io::Result<Self> {
+        let current = fs::read_to_string(path.join("CURRENT"))?;
+        let manifest = fs::read(path.join(current.trim_end()))?;
+
+        let mut log_number = 0;
+        let mut prev_log_number = 0;
+        let mut tables = Vec::new();
+        for record in read_records(&manifest)? {
+            let mut reader = Reader { bytes: &record };
+            while !reader.is_empty() {
+                match reader.varint()? {
+                    EDIT_COMPARATOR => {
+                        let comparator = reader.slice()?;
+                        if comparator != COMPARATOR.as_bytes() {
+                            return Err(invalid_data(format!(
+                                "Unsupported comparator {}",
+                                String::from_utf8_lossy(comparator)
+                            )));
+                        }
+                    }
+                    EDIT_LOG_NUMBER => log_number = reader.varint()?,
+                    EDIT_PREV_LOG_NUMBER => prev_log_number = reader.varint()?,
+                    EDIT_NEXT_FILE_NUMBER | EDIT_LAST_SEQUENCE => {
+                        reader.varint()?;
+                    }
+                    EDIT_COMPACT_POINTER => {
+                        reader.varint()?;
+                        reader.slice()?;
+                    }
+                    EDIT_DELETED_FILE => {
+                        reader.varint()?;
+                        let number = reader.varint()?;
+                        tables.retain(|&table| table != number);
+                    }
+                    EDIT_NEW_FILE => {
+                        reader.varint()?;
+                        tables.push(reader.varint()?);
+                        reader.varint()?;
+                        reader.slice()?;
+                        reader.slice()?;
+                    }
+                    tag => {
+                        return Err(invalid_data(format!("Unknown manifest field {tag}")));
+                    }
+                }
+            }
+        }
+
+        if !tables.is_empty() {
+            return Err(invalid_data(format!(
+                "Store has {} compacted tables, which aren't supported",
+                tables.len()
+            )));
+        }
+
+        // Logs at or past the manifest's log number still hold writes
+        let mut logs = Vec::new();
+        for entry in fs::read_dir(path)? {
+            let name = entry?.file_name();
+            let Some(number) = name
+                .to_str()
+                .and_then(|name| name.strip_suffix(".log"))
+                .and_then(|number| number.parse::<u64>().ok())
+            else {
+                continue;
+            };
+
+            if number >= log_number || (prev_log_number != 0 && number == prev_log_number) {
+                logs.push(number);
+            }
+        }
+        logs.sort_unstable();
+
+        let mut store = Self::new();
+        for number in logs {
+            let log = fs::read(path.join(format!("{number:06}.log")))?;
+            for batch in read_records(&log)? {
+                store.apply_batch(&batch)?;
+            }
+        }
+
+        Ok(store)
+    }
+
+    fn apply_batch(&mut self, batch: &[u8]) -> io::Result<()> {
+        let mut reader = Reader { bytes: batch };
+        reader.bytes(8)?; // Sequence number
+        let count = u32::from_le_bytes(reader.bytes(4)?.try_into().unwrap());
+        for _ in 0..count {
+            match reader.byte()? {
+                VALUE => {
+                    let key = reader.slice()?.to_vec();
+                    let value = reader.slice()?.to_vec();
+                    self.entries.insert(key, value);
+                }
+                DELETION => {
+                    self.entries.remove(reader.slice()?);
+                }
+                kind => return Err(invalid_data(format!("Unknown batch entry kind {kind}"))),
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Writes the store to the directory, replacing what was in it. The new store is
+    /// written next to it first and only swapped in once complete, so an interrupted
+    /// save leaves the old store behind instead of a partial one.
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        let name = path
+            .file_name()
+            .ok_or_else(|| {
+                io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!("{} isn't a directory", path.display()),
+                )
+            })?
+            .to_string_lossy();
+        let temporary_path = path.with_file_name(format!("{name}.tmp"));
+        let old_path = path.with_file_name(format!("{name}.old"));
+        for path in [&temporary_path, &old_path] {
+            if path.exists() {
+                fs::remove_dir_all(path)?;
+            }
+        }
+
+        fs::create_dir_all(&temporary_path)?;
+        self.write_files(&temporary_path)?;
+
+        if path.exists() {
+            fs::rename(path, &old_path)?;
+        }
+        fs::rename(&temporary_path, path)?;
+        if old_path.exists() {
+            fs::remove_dir_all(&old_path)?;
+        }
+
+        Ok(())
+    }
+
+    fn write_files(&self, path: &Path) -> io::Result<()> {
+        // One batch per entry keeps every record small
+        let mut log = LogWriter::default();
+        for (sequence, (key, value)) in self.entries.iter().enumerate() {
+            let mut batch = Vec::with_capacity(key.len() + value.len() + 24);
+            batch.extend_from_slice(&(sequence as u64 + 1).to_le_bytes());
+            batch.extend_from_slice(&1u32.to_le_bytes());
+            batch.push(VALUE);
+            write_slice(&mut batch, key);
+            write_slice(&mut batch, value);
+            log.add_record(&batch);
+        }
+
+        let mut edit = Vec::new();
+        write_varint(&mut edit, EDIT_COMPARATOR);
+        write_slice(&mut edit, COMPARATOR.as_bytes());
+        write_varint(&mut edit, EDIT_LOG_NUMBER);
+        write_varint(&mut edit, LOG_NUMBER);
+        write_varint(&mut edit, EDIT_NEXT_FILE_NUMBER);
+        write_varint(&mut edit, LOG_NUMBER + 1);
+        write_varint(&mut edit, EDIT_LAST_SEQUENCE);
+        write_varint(&mut edit, 0);
+        let mut manifest = LogWriter::default();
+        manifest.add_record(&edit);
+
+        let manifest_name = format!("MANIFEST-{MANIFEST_NUMBER:06}");
+        fs::write(path.join(format!("{LOG_NUMBER:06}.log")), log.into_bytes())?;
+        fs::write(path.join(&manifest_name), manifest.into_bytes())?;
+        fs::write(path.join("CURRENT"), format!("{manifest_name}\n"))
+    }
+}
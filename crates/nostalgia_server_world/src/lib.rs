@@ -1,71 +1,301 @@
 mod block;
+mod check;
 mod chunk;
+mod diff;
+mod generator;
+mod java;
+mod level;
+mod leveldb;
+mod light;
+mod location;
+mod position;
+mod registry;
+mod schematic;
+mod spawn;
+mod stats;
 
 use std::{
-    io::{self, Cursor, Error, Seek},
-    path::PathBuf,
+    collections::HashMap,
+    io::{self, Cursor, Error, Write},
+    path::{Path, PathBuf},
 };
 
 pub use block::*;
+pub use check::*;
 pub use chunk::*;
-use nbt::{Nbt, Tag};
+pub use diff::*;
+pub use generator::*;
+pub use java::*;
+pub use level::*;
+pub use leveldb::*;
+pub use light::*;
+pub use location::*;
+use nbt::{Compound, Header, Nbt, Tag, ENTITIES_MAGIC, ENTITIES_VERSION};
+pub use position::*;
+pub use registry::*;
+pub use schematic::*;
+pub use stats::*;
 
-use byteorder::{LittleEndian, ReadBytesExt};
+use byteorder::{LittleEndian, WriteBytesExt};
+
+const SECTOR_SIZE: usize = 4096;
+const CHUNK_SECTORS: usize = CHUNK_RECORD_SIZE.div_ceil(SECTOR_SIZE);
+/// The location table covers a 32x32 area, of which legacy worlds only use 16x16.
+const LOCATION_TABLE_WIDTH: i32 = 32;
+
+/// Error for files which were read fine but don't contain what they should.
+pub fn invalid_data(message: impl Into<String>) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message.into())
+}
+
+/// The contents of a world's legacy files, serialized but not written yet.
+pub struct WorldFiles {
+    pub chunks: Vec<u8>,
+    pub level: Vec<u8>,
+    pub entities: Vec<u8>,
+}
+
+impl WorldFiles {
+    /// Writes every file next to its final name and then renames it over the old one,
+    /// so an interrupted save never leaves a file half written. Chunks that weren't
+    /// needed yet keep being read from the replaced `chunks.dat`.
+    pub fn write(&self, path: &Path) -> io::Result<()> {
+        std::fs::create_dir_all(path)?;
+
+        for (name, contents) in [
+            ("chunks.dat", &self.chunks),
+            ("level.dat", &self.level),
+            ("entities.dat", &self.entities),
+        ] {
+            let temporary_path = path.join(format!("{}.tmp", name));
+            std::fs::write(&temporary_path, contents)?;
+            std::fs::rename(temporary_path, path.join(name))?;
+        }
+
+        Ok(())
+    }
+}
 
 pub struct World {
-    pub name: String,
-    pub spawn_mobs: bool,
-    pub seed: i64,
-    pub time: i64,
-    pub spawn_position: (i32, i32, i32),
-    pub platform: i32,
-    pub game_type: i32,
-    pub storage_version: i32,
-    pub day_cycle_stop_time: i64,
-    pub last_played: i64,
-
-    pub chunks: Vec<Chunk>,
+    pub level: LevelData,
+
+    chunks: HashMap<ChunkPos, Chunk>,
+    /// Chunks of the file the world was loaded from which weren't needed yet.
+    unloaded: Option<LazyChunks>,
+    chunk_problems: Vec<LocationProblem>,
     pub entities: Vec<Tag>,
+    pub tile_entities: Vec<Tag>,
 }
 
 impl World {
-    fn read_chunk_metadata(cursor: &mut Cursor<Vec<u8>>) -> io::Result<Vec<Vec<u32>>> {
-        let mut metadata = vec![vec![0u32; 16]; 16];
-        for offset in (0..4096).step_by(4) {
-            let chunk_metadata = cursor.read_u32::<LittleEndian>()?;
-            if chunk_metadata == 0 {
-                continue;
-            }
+    /// Creates a world without any chunks.
+    pub fn new(level: LevelData) -> Self {
+        Self {
+            level,
+            chunks: HashMap::new(),
+            unloaded: None,
+            chunk_problems: Vec::new(),
+            entities: Vec::new(),
+            tile_entities: Vec::new(),
+        }
+    }
+
+    /// Returns the chunk, reading it from the world's `chunks.dat` the first time it's
+    /// needed. Chunks which can't be read are treated as missing, see
+    /// [`World::try_chunk_at`] for the reason.
+    pub fn chunk_at(&self, x: i32, z: i32) -> Option<&Chunk> {
+        self.try_chunk_at(x, z).ok().flatten()
+    }
+
+    pub fn try_chunk_at(&self, x: i32, z: i32) -> io::Result<Option<&Chunk>> {
+        let position = ChunkPos::new(x, z);
+        match (self.chunks.get(&position), &self.unloaded) {
+            (Some(chunk), _) => Ok(Some(chunk)),
+            (None, Some(unloaded)) => unloaded.get(position),
+            (None, None) => Ok(None),
+        }
+    }
+
+    pub fn chunk_at_mut(&mut self, x: i32, z: i32) -> Option<&mut Chunk> {
+        let position = ChunkPos::new(x, z);
+        self.load_chunk(position);
+        self.chunks.get_mut(&position)
+    }
+
+    /// Moves a chunk that wasn't needed yet out of the file, so it can be modified.
+    fn load_chunk(&mut self, position: ChunkPos) {
+        if self.chunks.contains_key(&position) {
+            return;
+        }
 
-            let x = (offset >> 2) % 32;
-            let z = (offset >> 2) / 32;
-            metadata[x as usize][z as usize] = (chunk_metadata >> 8) * 4096;
+        let Some(unloaded) = &mut self.unloaded else {
+            return;
+        };
+
+        // Chunks which can't be read stay in the file, so saving copies them unchanged
+        if let Ok(Some(chunk)) = unloaded.take(position) {
+            self.chunks.insert(position, chunk);
+        }
+    }
+
+    /// Iterates over every chunk, reading the ones that weren't needed yet.
+    pub fn chunks(&self) -> impl Iterator<Item = (ChunkPos, &Chunk)> {
+        let unloaded = self.unloaded.iter().flat_map(|unloaded| {
+            unloaded
+                .positions()
+                .filter_map(|position| Some((position, unloaded.get(position).ok()??)))
+        });
+
+        self.chunks
+            .iter()
+            .map(|(position, chunk)| (*position, chunk))
+            .chain(unloaded)
+    }
+
+    pub fn chunks_mut(&mut self) -> impl Iterator<Item = (ChunkPos, &mut Chunk)> {
+        let positions: Vec<ChunkPos> = self
+            .unloaded
+            .iter()
+            .flat_map(LazyChunks::positions)
+            .collect();
+        for position in positions {
+            self.load_chunk(position);
         }
 
-        Ok(metadata)
+        self.chunks
+            .iter_mut()
+            .map(|(position, chunk)| (*position, chunk))
+    }
+
+    /// Problems found in the location table of `chunks.dat` when the world was loaded.
+    pub fn chunk_problems(&self) -> &[LocationProblem] {
+        &self.chunk_problems
     }
 
-    fn read_level_data(buffer: Vec<u8>) -> io::Result<Nbt> {
-        let mut cursor = Cursor::new(buffer);
-        if cursor.read_i32::<LittleEndian>()? != 3 {
-            return Err(Error::new(
-                io::ErrorKind::InvalidData,
-                "Invalid level.dat version",
-            ));
+    /// Inserts a chunk, returning the one previously stored at that position.
+    /// Positions outside of the legacy world limits are rejected.
+    pub fn insert_chunk(&mut self, position: ChunkPos, chunk: Chunk) -> Option<Chunk> {
+        if !position.is_valid() {
+            return None;
         }
 
-        // File length
-        cursor.read_i32::<LittleEndian>()?;
+        // The chunk in the file is replaced even if it can't be read
+        let previous = self
+            .unloaded
+            .as_mut()
+            .and_then(|unloaded| unloaded.remove(position));
+        self.chunks.insert(position, chunk).or(previous)
+    }
+
+    pub fn is_in_bounds(x: i32, y: i32, z: i32) -> bool {
+        to_local_position(x, y, z).is_some()
+    }
+
+    pub fn get_block(&self, x: i32, y: i32, z: i32) -> Option<Block> {
+        let (position, x, y, z) = to_local_position(x, y, z)?;
+        let chunk = self.chunk_at(position.x, position.z)?;
+        Some(chunk.get(x, y, z))
+    }
+
+    /// Replaces the block at the given world coordinates and returns the previous one.
+    /// Returns `None` without modifying anything if the position is out of bounds or
+    /// its chunk isn't loaded.
+    ///
+    /// The light of `block` is ignored, light around the block is recalculated instead.
+    pub fn set_block(&mut self, x: i32, y: i32, z: i32, block: Block) -> Option<Block> {
+        let (position, local_x, local_y, local_z) = to_local_position(x, y, z)?;
+        let chunk = self.chunk_at_mut(position.x, position.z)?;
+        let previous = chunk.get(local_x, local_y, local_z);
+        chunk.set(
+            local_x,
+            local_y,
+            local_z,
+            Block {
+                sky_light: previous.sky_light,
+                block_light: previous.block_light,
+                ..block
+            },
+        );
+
+        self.update_light(x, y, z);
+        Some(previous)
+    }
+
+    fn write_chunk_data(&self) -> io::Result<Vec<u8>> {
+        let mut location_table = Cursor::new(Vec::with_capacity(SECTOR_SIZE));
+        let mut sectors = Cursor::new(Vec::new());
+
+        // Sector 0 holds the location table, chunks follow in table order
+        let mut next_sector = 1;
+        for index in 0..LOCATION_TABLE_WIDTH * LOCATION_TABLE_WIDTH {
+            let position =
+                ChunkPos::new(index % LOCATION_TABLE_WIDTH, index / LOCATION_TABLE_WIDTH);
+            match self.try_chunk_at(position.x, position.z) {
+                Ok(Some(chunk)) => chunk.to_bytes(&mut sectors)?,
+                Ok(None) => {
+                    location_table.write_u32::<LittleEndian>(0)?;
+                    continue;
+                }
+                // Chunks which can't be parsed are copied as they are instead of being
+                // lost, errors reading the file itself fail the save
+                Err(error) => {
+                    let record = self
+                        .unloaded
+                        .as_ref()
+                        .map(|unloaded| unloaded.raw_record(position))
+                        .transpose()?
+                        .flatten()
+                        .ok_or(error)?;
+                    sectors.write_all(&record)?;
+                }
+            }
+
+            location_table
+                .write_u32::<LittleEndian>((next_sector << 8) as u32 | CHUNK_SECTORS as u32)?;
+            // Records are padded to whole sectors, the next one starts after the padding
+            next_sector += CHUNK_SECTORS;
+            sectors.get_mut().resize((next_sector - 1) * SECTOR_SIZE, 0);
+            sectors.set_position(sectors.get_ref().len() as u64);
+        }
 
-        Nbt::from_bytes(&mut cursor)
+        let mut buffer = location_table.into_inner();
+        buffer.append(sectors.get_mut());
+        Ok(buffer)
     }
 
-    fn read_entity_data(buffer: Vec<u8>) -> io::Result<Nbt> {
-        let mut cursor = Cursor::new(buffer);
+    fn write_entity_data(&self) -> io::Result<Vec<u8>> {
+        let tags = Compound::from([
+            ("Entities".to_string(), Tag::list(self.entities.clone())),
+            (
+                "TileEntities".to_string(),
+                Tag::list(self.tile_entities.clone()),
+            ),
+        ]);
+
+        let mut nbt = Cursor::new(Vec::new());
+        Nbt::new(tags).to_bytes(&mut nbt)?;
+
+        let mut cursor = Cursor::new(Vec::new());
+        cursor.write_all(ENTITIES_MAGIC)?;
+        cursor.write_u32::<LittleEndian>(ENTITIES_VERSION)?;
+        cursor.write_u32::<LittleEndian>(nbt.get_ref().len() as u32)?;
+        cursor.write_all(nbt.get_ref())?;
+        Ok(cursor.into_inner())
+    }
 
-        cursor.seek(io::SeekFrom::Start(12))?; // TODO, header
+    /// Serializes the world into the contents of its legacy files, to be written with
+    /// [`WorldFiles::write`].
+    pub fn to_files(&self) -> io::Result<WorldFiles> {
+        Ok(WorldFiles {
+            chunks: self.write_chunk_data()?,
+            level: self.level.to_bytes()?,
+            entities: self.write_entity_data()?,
+        })
+    }
 
-        Nbt::from_bytes(&mut cursor)
+    /// Writes the world in the legacy `chunks.dat`, `level.dat` and `entities.dat` format.
+    pub fn save(&self, path: PathBuf) -> io::Result<()> {
+        self.to_files()?.write(&path)
     }
 
     pub fn from_file(path: PathBuf) -> io::Result<Self> {
@@ -81,76 +311,33 @@ impl World {
         let level = std::fs::read(path.join("level.dat"))?;
         let entities = std::fs::read(path.join("entities.dat"))?;
 
-        let level = Self::read_level_data(level)?;
-        let entities = Self::read_entity_data(entities)?;
+        let level = LevelData::from_bytes(level)?;
+        let (entities, header) = Nbt::from_pocket_file(entities)?;
+        if header != Header::Entities(ENTITIES_VERSION) {
+            return Err(invalid_data(format!(
+                "entities.dat has header {:?}, expected version {}",
+                header, ENTITIES_VERSION
+            )));
+        }
 
-        let level_root = level.root();
         let entities_root = entities.root();
 
-        let chunks = std::fs::read(path.join("chunks.dat"))?;
-        let mut chunks = Cursor::new(chunks);
-        let chunk_metadata = Self::read_chunk_metadata(&mut chunks)?;
-
-        let mut chunk_list = Vec::new();
-        for x in 0..16 {
-            for z in 0..16 {
-                let offset = chunk_metadata[x][z] as usize;
-                if offset == 0 {
-                    continue;
-                }
-
-                chunks.seek(io::SeekFrom::Start(offset as u64))?;
-                let chunk = Chunk::from_bytes(&mut chunks)?;
-                chunk_list.push(chunk);
-            }
-        }
+        // Chunks are only read once they're needed
+        let (chunks, chunk_problems) = LazyChunks::open(&path.join("chunks.dat"))?;
 
         Ok(Self {
-            name: level_root
-                .get_string("LevelName")
-                .ok_or_else(|| not_found!(LevelName))?
-                .clone(),
-            spawn_mobs: *level_root
-                .get_byte("spawnMobs")
-                .ok_or_else(|| not_found!(SpawnMobs))?
-                != 0,
-            seed: *level_root
-                .get_long("RandomSeed")
-                .ok_or_else(|| not_found!(RandomSeed))?,
-            time: *level_root
-                .get_long("Time")
-                .ok_or_else(|| not_found!(Time))?,
-            spawn_position: (
-                *level_root
-                    .get_int("SpawnX")
-                    .ok_or_else(|| not_found!(SpawnX))?,
-                *level_root
-                    .get_int("SpawnY")
-                    .ok_or_else(|| not_found!(SpawnY))?,
-                *level_root
-                    .get_int("SpawnZ")
-                    .ok_or_else(|| not_found!(SpawnZ))?,
-            ),
-            platform: *level_root
-                .get_int("Platform")
-                .ok_or_else(|| not_found!(Platform))?,
-            game_type: *level_root
-                .get_int("GameType")
-                .ok_or_else(|| not_found!(GameType))?,
-            storage_version: *level_root
-                .get_int("StorageVersion")
-                .ok_or_else(|| not_found!(StorageVersion))?,
-            day_cycle_stop_time: *level_root
-                .get_long("dayCycleStopTime")
-                .ok_or_else(|| not_found!(dayCycleStopTime))?,
-            last_played: *level_root
-                .get_long("LastPlayed")
-                .ok_or_else(|| not_found!(LastPlayed))?,
-            chunks: chunk_list,
+            level,
+            chunks: HashMap::new(),
+            unloaded: Some(chunks),
+            chunk_problems,
             entities: entities_root
                 .get_list("Entities")
                 .ok_or_else(|| not_found!(Entities))?
                 .clone(),
+            tile_entities: entities_root
+                .get_list("TileEntities")
+                .cloned()
+                .unwrap_or_default(),
         })
     }
 }
@@ -0,0 +1,277 @@
+use std::collections::VecDeque;
+
+use crate::{
+    to_local_position, Block, Chunk, ChunkPos, World, CHUNK_SIZE_X, CHUNK_SIZE_Y, CHUNK_SIZE_Z,
+    WORLD_SIZE_Y,
+};
+
+pub const MAX_LIGHT: u8 = 15;
+
+/// Offsets of the six neighbours of a block, starting with the one below.
+const NEIGHBOURS: [(i32, i32, i32); 6] = [
+    (0, -1, 0),
+    (0, 1, 0),
+    (-1, 0, 0),
+    (1, 0, 0),
+    (0, 0, -1),
+    (0, 0, 1),
+];
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LightKind {
+    /// Light from the sky, which travels straight down without getting dimmer.
+    Sky,
+    /// Light from emitting blocks such as torches and lava.
+    Block,
+}
+
+impl Block {
+    pub fn light(&self, kind: LightKind) -> u8 {
+        match kind {
+            LightKind::Sky => self.sky_light,
+            LightKind::Block => self.block_light,
+        }
+    }
+}
+
+impl Chunk {
+    pub fn light(&self, kind: LightKind, x: usize, y: usize, z: usize) -> u8 {
+        match kind {
+            LightKind::Sky => self.sky_light(x, y, z),
+            LightKind::Block => self.block_light(x, y, z),
+        }
+    }
+
+    pub fn set_light(&mut self, kind: LightKind, x: usize, y: usize, z: usize, light: u8) {
+        match kind {
+            LightKind::Sky => self.set_sky_light(x, y, z, light),
+            LightKind::Block => self.set_block_light(x, y, z, light),
+        }
+    }
+
+    /// Lights every column from the sky down to the first block that stops light, and
+    /// sets emitting blocks to their own light.
+    fn light_columns(&mut self) {
+        for x in 0..CHUNK_SIZE_X {
+            for z in 0..CHUNK_SIZE_Z {
+                let height = self.height(x, z);
+                let mut light = MAX_LIGHT;
+                for y in (0..CHUNK_SIZE_Y).rev() {
+                    let properties = self.block_id(x, y, z).properties();
+                    if y < height {
+                        light = light.saturating_sub(properties.light_opacity.max(1));
+                    }
+
+                    self.set_sky_light(x, y, z, light);
+                    self.set_block_light(x, y, z, properties.light_emission);
+                }
+            }
+        }
+    }
+}
+
+impl World {
+    pub fn get_light(&self, kind: LightKind, x: i32, y: i32, z: i32) -> Option<u8> {
+        let (position, x, y, z) = to_local_position(x, y, z)?;
+        Some(self.chunk_at(position.x, position.z)?.light(kind, x, y, z))
+    }
+
+    fn set_light(&mut self, kind: LightKind, x: i32, y: i32, z: i32, light: u8) {
+        if let Some((position, x, y, z)) = to_local_position(x, y, z) {
+            if let Some(chunk) = self.chunk_at_mut(position.x, position.z) {
+                chunk.set_light(kind, x, y, z, light);
+            }
+        }
+    }
+
+    /// Light a block receives from the neighbour at the given offset, which has `light`.
+    /// Sky light at full strength keeps going down through blocks that don't stop light.
+    fn received_light(kind: LightKind, offset: (i32, i32, i32), light: u8, opacity: u8) -> u8 {
+        if kind == LightKind::Sky && offset.1 == 1 && light == MAX_LIGHT && opacity == 0 {
+            return MAX_LIGHT;
+        }
+
+        light.saturating_sub(opacity.max(1))
+    }
+
+    /// Calculates the light of a block from what it emits and what its neighbours give it.
+    fn light_from_neighbours(&self, kind: LightKind, x: i32, y: i32, z: i32) -> u8 {
+        let Some(block) = self.get_block(x, y, z) else {
+            return 0;
+        };
+
+        let properties = block.id.properties();
+        let mut light = match kind {
+            LightKind::Sky => 0,
+            LightKind::Block => properties.light_emission,
+        };
+
+        if properties.light_opacity >= MAX_LIGHT {
+            return light;
+        }
+
+        for offset @ (dx, dy, dz) in NEIGHBOURS {
+            let neighbour = match kind {
+                // Everything above the world is open sky
+                LightKind::Sky if y + dy == WORLD_SIZE_Y => MAX_LIGHT,
+                _ => self.get_light(kind, x + dx, y + dy, z + dz).unwrap_or(0),
+            };
+
+            light = light.max(Self::received_light(
+                kind,
+                offset,
+                neighbour,
+                properties.light_opacity,
+            ));
+        }
+
+        light
+    }
+
+    /// Spreads light outwards from every queued block until it stops getting brighter.
+    fn spread_light(&mut self, kind: LightKind, mut queue: VecDeque<(i32, i32, i32)>) {
+        while let Some((x, y, z)) = queue.pop_front() {
+            let Some(light) = self.get_light(kind, x, y, z) else {
+                continue;
+            };
+
+            for (dx, dy, dz) in NEIGHBOURS {
+                let (nx, ny, nz) = (x + dx, y + dy, z + dz);
+                let Some(block) = self.get_block(nx, ny, nz) else {
+                    continue;
+                };
+
+                let opacity = block.id.properties().light_opacity;
+                if opacity >= MAX_LIGHT {
+                    continue;
+                }
+
+                // The neighbour is receiving light, so the offset is flipped
+                let received = Self::received_light(kind, (-dx, -dy, -dz), light, opacity);
+                if received > block.light(kind) {
+                    self.set_light(kind, nx, ny, nz, received);
+                    queue.push_back((nx, ny, nz));
+                }
+            }
+        }
+    }
+
+    /// Darkens every block that got its light from the given one, and returns the blocks
+    /// bordering the darkened area that light has to be spread from again.
+    fn remove_light(
+        &mut self,
+        kind: LightKind,
+        (x, y, z): (i32, i32, i32),
+        light: u8,
+    ) -> VecDeque<(i32, i32, i32)> {
+        let mut sources = VecDeque::new();
+        let mut queue = VecDeque::from([(x, y, z, light)]);
+        self.set_light(kind, x, y, z, 0);
+
+        while let Some((x, y, z, light)) = queue.pop_front() {
+            for (dx, dy, dz) in NEIGHBOURS {
+                let (nx, ny, nz) = (x + dx, y + dy, z + dz);
+                let Some(block) = self.get_block(nx, ny, nz) else {
+                    continue;
+                };
+
+                let neighbour = block.light(kind);
+                if neighbour == 0 {
+                    continue;
+                }
+
+                let direct_sky =
+                    kind == LightKind::Sky && dy == -1 && light == MAX_LIGHT && neighbour == light;
+                if neighbour < light || direct_sky {
+                    self.set_light(kind, nx, ny, nz, 0);
+                    queue.push_back((nx, ny, nz, neighbour));
+
+                    // Emitters keep their own light and have to spread it again
+                    let emission = block.id.properties().light_emission;
+                    if kind == LightKind::Block && emission > 0 {
+                        self.set_light(kind, nx, ny, nz, emission);
+                        sources.push_back((nx, ny, nz));
+                    }
+                } else {
+                    sources.push_back((nx, ny, nz));
+                }
+            }
+        }
+
+        sources
+    }
+
+    /// Recalculates the light around a block after it changed.
+    pub(crate) fn update_light(&mut self, x: i32, y: i32, z: i32) {
+        for kind in [LightKind::Sky, LightKind::Block] {
+            let previous = self.get_light(kind, x, y, z).unwrap_or(0);
+            let mut queue = match previous {
+                0 => VecDeque::new(),
+                light => self.remove_light(kind, (x, y, z), light),
+            };
+
+            let light = self.light_from_neighbours(kind, x, y, z);
+            self.set_light(kind, x, y, z, light);
+            queue.push_back((x, y, z));
+
+            self.spread_light(kind, queue);
+        }
+    }
+
+    /// Recalculates the sky and block light of every loaded chunk from scratch.
+    pub fn calculate_light(&mut self) {
+        let mut block_sources = VecDeque::new();
+        for (position, chunk) in self.chunks_mut() {
+            chunk.light_columns();
+
+            for x in 0..CHUNK_SIZE_X {
+                for z in 0..CHUNK_SIZE_Z {
+                    for y in 0..CHUNK_SIZE_Y {
+                        if chunk.block_light(x, y, z) > 0 {
+                            block_sources.push_back(world_position(position, x, y, z));
+                        }
+                    }
+                }
+            }
+        }
+
+        let height_at = |x: i32, z: i32| {
+            let (position, x, _, z) = to_local_position(x, 0, z)?;
+            Some(self.chunk_at(position.x, position.z)?.height(x, z))
+        };
+
+        // Sky light only spreads sideways into columns that are shaded further up, so
+        // only the parts of a column below its neighbours' direct sky light are sources.
+        let mut sky_sources = VecDeque::new();
+        for (position, chunk) in self.chunks() {
+            for x in 0..CHUNK_SIZE_X {
+                for z in 0..CHUNK_SIZE_Z {
+                    let (wx, _, wz) = world_position(position, x, 0, z);
+                    let top = NEIGHBOURS[2..]
+                        .iter()
+                        .filter_map(|(dx, _, dz)| height_at(wx + dx, wz + dz))
+                        .max()
+                        .unwrap_or(0)
+                        .max(chunk.height(x, z));
+
+                    for y in 0..top.min(CHUNK_SIZE_Y) {
+                        if chunk.sky_light(x, y, z) > 1 {
+                            sky_sources.push_back(world_position(position, x, y, z));
+                        }
+                    }
+                }
+            }
+        }
+
+        self.spread_light(LightKind::Sky, sky_sources);
+        self.spread_light(LightKind::Block, block_sources);
+    }
+}
+
+fn world_position(position: ChunkPos, x: usize, y: usize, z: usize) -> (i32, i32, i32) {
+    (
+        position.x * CHUNK_SIZE_X as i32 + x as i32,
+        y as i32,
+        position.z * CHUNK_SIZE_Z as i32 + z as i32,
+    )
+}
@@ -0,0 +1,235 @@
+use std::{
+    collections::HashMap,
+    fmt,
+    fs::File,
+    io::{self, Cursor, Read, Seek},
+    path::Path,
+    sync::{Mutex, OnceLock},
+};
+
+use byteorder::{LittleEndian, ReadBytesExt};
+
+use crate::{Chunk, ChunkPos, CHUNK_RECORD_SIZE, LOCATION_TABLE_WIDTH, SECTOR_SIZE};
+
+/// Where a chunk is stored in `chunks.dat`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ChunkLocation {
+    pub position: ChunkPos,
+    /// First sector of the record, sector 0 being the location table.
+    pub sector: usize,
+    pub sector_count: usize,
+}
+
+impl ChunkLocation {
+    /// Byte range of the record. Records always have the same size, whatever their
+    /// sector count says.
+    pub fn byte_range(&self) -> std::ops::Range<usize> {
+        let start = self.sector * SECTOR_SIZE;
+        start..start + CHUNK_RECORD_SIZE
+    }
+}
+
+/// Something wrong with the location table of `chunks.dat`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LocationProblem {
+    /// A chunk of the legacy world has no entry in the table.
+    Missing(ChunkPos),
+    /// The record runs past the end of the file, the chunk can't be loaded.
+    Truncated(ChunkLocation),
+    /// The record shares sectors with an earlier chunk, or with the table if `other`
+    /// is `None`.
+    Overlapping {
+        location: ChunkLocation,
+        other: Option<ChunkPos>,
+    },
+}
+
+impl fmt::Display for LocationProblem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Missing(position) => {
+                write!(f, "Chunk {}, {} is missing", position.x, position.z)
+            }
+            Self::Truncated(location) => write!(
+                f,
+                "Chunk {}, {} at sector {} runs past the end of the file",
+                location.position.x, location.position.z, location.sector
+            ),
+            Self::Overlapping { location, other } => {
+                write!(
+                    f,
+                    "Chunk {}, {} at sector {} overlaps ",
+                    location.position.x, location.position.z, location.sector
+                )?;
+                match other {
+                    Some(other) => write!(f, "chunk {}, {}", other.x, other.z),
+                    None => write!(f, "the location table"),
+                }
+            }
+        }
+    }
+}
+
+/// The location table at the start of `chunks.dat`, a 32x32 grid of little-endian
+/// entries holding the first sector of a chunk shifted left by 8 and its sector count.
+#[derive(Clone, Debug, Default)]
+pub struct LocationTable {
+    /// Chunks that can be read, in table order.
+    pub locations: Vec<ChunkLocation>,
+    pub problems: Vec<LocationProblem>,
+}
+
+impl LocationTable {
+    /// Reads the table from the first sector of a `chunks.dat` of `file_length` bytes.
+    pub fn read(table: &[u8], file_length: usize) -> io::Result<Self> {
+        let mut cursor = Cursor::new(table);
+        let mut table = Self::default();
+        for index in 0..LOCATION_TABLE_WIDTH * LOCATION_TABLE_WIDTH {
+            let entry = cursor.read_u32::<LittleEndian>()?;
+            let position =
+                ChunkPos::new(index % LOCATION_TABLE_WIDTH, index / LOCATION_TABLE_WIDTH);
+            if entry == 0 {
+                if position.is_valid() {
+                    table.problems.push(LocationProblem::Missing(position));
+                }
+
+                continue;
+            }
+
+            let location = ChunkLocation {
+                position,
+                sector: (entry >> 8) as usize,
+                sector_count: (entry & 0xFF) as usize,
+            };
+
+            if location.byte_range().end > file_length {
+                table.problems.push(LocationProblem::Truncated(location));
+                continue;
+            }
+
+            let range = location.byte_range();
+            let overlaps = |other: &ChunkLocation| {
+                let other = other.byte_range();
+                range.start < other.end && other.start < range.end
+            };
+
+            if location.sector == 0 {
+                table.problems.push(LocationProblem::Overlapping {
+                    location,
+                    other: None,
+                });
+            } else if let Some(other) = table.locations.iter().find(|other| overlaps(other)) {
+                table.problems.push(LocationProblem::Overlapping {
+                    location,
+                    other: Some(other.position),
+                });
+            }
+
+            table.locations.push(location);
+        }
+
+        Ok(table)
+    }
+}
+
+/// Chunks of a `chunks.dat` which are read the first time they're needed.
+pub(crate) struct LazyChunks {
+    file: Mutex<File>,
+    chunks: HashMap<ChunkPos, (ChunkLocation, OnceLock<Chunk>)>,
+}
+
+impl LazyChunks {
+    /// Opens `chunks.dat` and reads its location table, without reading any chunk yet.
+    pub fn open(path: &Path) -> io::Result<(Self, Vec<LocationProblem>)> {
+        let mut file = File::open(path)?;
+        let file_length = file.metadata()?.len() as usize;
+
+        let mut table = vec![0; SECTOR_SIZE];
+        file.read_exact(&mut table)?;
+        let table = LocationTable::read(&table, file_length)?;
+
+        let chunks = table
+            .locations
+            .into_iter()
+            .map(|location| (location.position, (location, OnceLock::new())))
+            .collect();
+
+        Ok((
+            Self {
+                file: Mutex::new(file),
+                chunks,
+            },
+            table.problems,
+        ))
+    }
+
+    fn read_record(&self, location: &ChunkLocation) -> io::Result<Vec<u8>> {
+        let mut record = vec![0; CHUNK_RECORD_SIZE];
+        let mut file = self.file.lock().unwrap_or_else(|error| error.into_inner());
+        file.seek(io::SeekFrom::Start(location.byte_range().start as u64))?;
+        file.read_exact(&mut record)?;
+        Ok(record)
+    }
+
+    fn read(&self, location: &ChunkLocation) -> io::Result<Chunk> {
+        let record = self.read_record(location)?;
+        Chunk::from_bytes(record).map_err(|error| {
+            io::Error::new(
+                error.kind(),
+                format!(
+                    "Chunk {}, {}: {}",
+                    location.position.x, location.position.z, error
+                ),
+            )
+        })
+    }
+
+    /// Returns the chunk, reading it if this is the first time it's needed.
+    pub fn get(&self, position: ChunkPos) -> io::Result<Option<&Chunk>> {
+        let Some((location, chunk)) = self.chunks.get(&position) else {
+            return Ok(None);
+        };
+
+        if let Some(chunk) = chunk.get() {
+            return Ok(Some(chunk));
+        }
+
+        let loaded = self.read(location)?;
+        Ok(Some(chunk.get_or_init(|| loaded)))
+    }
+
+    /// Removes the chunk to be stored elsewhere, reading it if it wasn't read yet. The
+    /// chunk stays if it can't be read.
+    pub fn take(&mut self, position: ChunkPos) -> io::Result<Option<Chunk>> {
+        let Some((location, chunk)) = self.chunks.get_mut(&position) else {
+            return Ok(None);
+        };
+
+        let (location, loaded) = (*location, chunk.take());
+        let chunk = match loaded {
+            Some(chunk) => chunk,
+            None => self.read(&location)?,
+        };
+
+        self.chunks.remove(&position);
+        Ok(Some(chunk))
+    }
+
+    /// Removes the chunk because it's being replaced, returning it if it could be read.
+    pub fn remove(&mut self, position: ChunkPos) -> Option<Chunk> {
+        let (location, chunk) = self.chunks.remove(&position)?;
+        chunk.into_inner().or_else(|| self.read(&location).ok())
+    }
+
+    /// The record of a chunk as it is in the file, for chunks which can't be parsed.
+    pub fn raw_record(&self, position: ChunkPos) -> io::Result<Option<Vec<u8>>> {
+        self.chunks
+            .get(&position)
+            .map(|(location, _)| self.read_record(location))
+            .transpose()
+    }
+
+    pub fn positions(&self) -> impl Iterator<Item = ChunkPos> + '_ {
+        self.chunks.keys().copied()
+    }
+}
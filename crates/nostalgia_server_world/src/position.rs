@@ -0,0 +1,55 @@
+use crate::{CHUNK_SIZE_X, CHUNK_SIZE_Y, CHUNK_SIZE_Z};
+
+/// Legacy worlds are a fixed 16x16 chunk square.
+pub const WORLD_SIZE_CHUNKS: i32 = 16;
+pub const WORLD_SIZE_X: i32 = WORLD_SIZE_CHUNKS * CHUNK_SIZE_X as i32;
+pub const WORLD_SIZE_Z: i32 = WORLD_SIZE_CHUNKS * CHUNK_SIZE_Z as i32;
+pub const WORLD_SIZE_Y: i32 = CHUNK_SIZE_Y as i32;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct ChunkPos {
+    pub x: i32,
+    pub z: i32,
+}
+
+impl ChunkPos {
+    pub fn new(x: i32, z: i32) -> Self {
+        Self { x, z }
+    }
+
+    /// Returns the position of the chunk containing the given block coordinates.
+    pub fn from_block(x: i32, z: i32) -> Self {
+        Self {
+            x: x.div_euclid(CHUNK_SIZE_X as i32),
+            z: z.div_euclid(CHUNK_SIZE_Z as i32),
+        }
+    }
+
+    pub fn is_valid(&self) -> bool {
+        (0..WORLD_SIZE_CHUNKS).contains(&self.x) && (0..WORLD_SIZE_CHUNKS).contains(&self.z)
+    }
+
+    /// Iterates over every chunk position of a legacy world.
+    pub fn all() -> impl Iterator<Item = ChunkPos> {
+        (0..WORLD_SIZE_CHUNKS)
+            .flat_map(|z| (0..WORLD_SIZE_CHUNKS).map(move |x| ChunkPos::new(x, z)))
+    }
+}
+
+/// Converts world coordinates into the chunk position and the coordinates inside of it,
+/// or `None` if they are outside of the legacy world limits.
+pub fn to_local_position(x: i32, y: i32, z: i32) -> Option<(ChunkPos, usize, usize, usize)> {
+    if !(0..WORLD_SIZE_X).contains(&x)
+        || !(0..WORLD_SIZE_Y).contains(&y)
+        || !(0..WORLD_SIZE_Z).contains(&z)
+    {
+        return None;
+    }
+
+    Some((
+        ChunkPos::from_block(x, z),
+        x as usize % CHUNK_SIZE_X,
+        y as usize,
+        z as usize % CHUNK_SIZE_Z,
+    ))
+}
@@ -0,0 +1,481 @@
+use crate::BlockID;
+
+/// What a block leaves behind when it's broken.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum BlockDrop {
+    Nothing,
+    /// The block itself, keeping its metadata.
+    Itself,
+    Item {
+        id: u16,
+        metadata: u16,
+        count: u8,
+    },
+}
+
+impl BlockDrop {
+    pub const fn block(id: BlockID, count: u8) -> Self {
+        Self::Item {
+            id: id.0 as u16,
+            metadata: 0,
+            count,
+        }
+    }
+
+    pub const fn item(id: u16, count: u8) -> Self {
+        Self::Item {
+            id,
+            metadata: 0,
+            count,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct BlockProperties {
+    pub name: &'static str,
+    /// How long the block takes to break, negative for blocks that can't be broken.
+    pub hardness: f32,
+    /// Whether faces of neighbouring blocks can be seen through this one.
+    pub transparent: bool,
+    pub light_emission: u8,
+    /// How much light is lost passing through the block, 15 stops it completely.
+    pub light_opacity: u8,
+    /// Whether entities collide with the block.
+    pub solid: bool,
+    /// Whether placing a block here overwrites this one.
+    pub replaceable: bool,
+    pub drop: BlockDrop,
+}
+
+impl BlockProperties {
+    /// A full, solid cube that blocks light.
+    pub const fn opaque(name: &'static str, hardness: f32) -> Self {
+        Self {
+            name,
+            hardness,
+            transparent: false,
+            light_emission: 0,
+            light_opacity: 15,
+            solid: true,
+            replaceable: false,
+            drop: BlockDrop::Itself,
+        }
+    }
+
+    /// A solid block that doesn't fill its whole cube or can be seen through.
+    pub const fn transparent(name: &'static str, hardness: f32) -> Self {
+        Self {
+            transparent: true,
+            light_opacity: 0,
+            ..Self::opaque(name, hardness)
+        }
+    }
+
+    /// A block entities walk through, such as flowers and torches.
+    pub const fn plant(name: &'static str) -> Self {
+        Self {
+            solid: false,
+            ..Self::transparent(name, 0.0)
+        }
+    }
+
+    pub const fn liquid(name: &'static str) -> Self {
+        Self {
+            hardness: 100.0,
+            light_opacity: 3,
+            replaceable: true,
+            drop: BlockDrop::Nothing,
+            ..Self::plant(name)
+        }
+    }
+
+    pub const fn light_opacity(self, light_opacity: u8) -> Self {
+        Self {
+            light_opacity,
+            ..self
+        }
+    }
+
+    pub const fn light_emission(self, light_emission: u8) -> Self {
+        Self {
+            light_emission,
+            ..self
+        }
+    }
+
+    pub const fn replaceable(self) -> Self {
+        Self {
+            replaceable: true,
+            ..self
+        }
+    }
+
+    pub const fn drops(self, drop: BlockDrop) -> Self {
+        Self { drop, ..self }
+    }
+}
+
+/// Properties of every block id. Ids nothing is registered for are treated as solid,
+/// opaque blocks that drop nothing, so unknown blocks never turn into holes.
+pub struct BlockRegistry {
+    blocks: [Option<BlockProperties>; 256],
+}
+
+/// The blocks of the 0.8 client.
+pub static BLOCK_REGISTRY: BlockRegistry = BlockRegistry::vanilla();
+
+impl BlockRegistry {
+    const UNKNOWN: BlockProperties =
+        BlockProperties::opaque("unknown", 1.0).drops(BlockDrop::Nothing);
+
+    pub const fn empty() -> Self {
+        Self {
+            blocks: [None; 256],
+        }
+    }
+
+    pub const fn register(&mut self, id: BlockID, properties: BlockProperties) {
+        self.blocks[id.0 as usize] = Some(properties);
+    }
+
+    pub fn get(&self, id: BlockID) -> &BlockProperties {
+        self.blocks[id.0 as usize]
+            .as_ref()
+            .unwrap_or(&Self::UNKNOWN)
+    }
+
+    pub fn is_registered(&self, id: BlockID) -> bool {
+        self.blocks[id.0 as usize].is_some()
+    }
+
+    /// Looks a block up by its name, such as `stone` or `red_flower`.
+    pub fn find(&self, name: &str) -> Option<BlockID> {
+        self.iter()
+            .find(|(_, properties)| properties.name == name)
+            .map(|(id, _)| id)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (BlockID, &BlockProperties)> {
+        self.blocks
+            .iter()
+            .enumerate()
+            .filter_map(|(id, properties)| Some((BlockID(id as u8), properties.as_ref()?)))
+    }
+
+    pub const fn vanilla() -> Self {
+        use BlockProperties as P;
+
+        let mut registry = Self::empty();
+        let r = &mut registry;
+
+        r.register(
+            BlockID::Air,
+            P::plant("air").replaceable().drops(BlockDrop::Nothing),
+        );
+        r.register(
+            BlockID::Stone,
+            P::opaque("stone", 1.5).drops(BlockDrop::block(BlockID::Cobblestone, 1)),
+        );
+        r.register(
+            BlockID::Grass,
+            P::opaque("grass", 0.6).drops(BlockDrop::block(BlockID::Dirt, 1)),
+        );
+        r.register(BlockID::Dirt, P::opaque("dirt", 0.5));
+        r.register(BlockID::Cobblestone, P::opaque("cobblestone", 2.0));
+        r.register(BlockID::WoodenPlanks, P::opaque("planks", 2.0));
+        r.register(BlockID::Sapling, P::plant("sapling"));
+        r.register(
+            BlockID::Bedrock,
+            P::opaque("bedrock", -1.0).drops(BlockDrop::Nothing),
+        );
+        r.register(BlockID::Water, P::liquid("flowing_water"));
+        r.register(BlockID::StillWater, P::liquid("water"));
+        r.register(
+            BlockID::Lava,
+            P::liquid("flowing_lava")
+                .light_opacity(15)
+                .light_emission(15),
+        );
+        r.register(
+            BlockID::StillLava,
+            P::liquid("lava").light_opacity(15).light_emission(15),
+        );
+        r.register(BlockID::Sand, P::opaque("sand", 0.5));
+        r.register(BlockID::Gravel, P::opaque("gravel", 0.6));
+        r.register(BlockID::GoldOre, P::opaque("gold_ore", 3.0));
+        r.register(BlockID::IronOre, P::opaque("iron_ore", 3.0));
+        r.register(
+            BlockID::CoalOre,
+            P::opaque("coal_ore", 3.0).drops(BlockDrop::item(263, 1)),
+        );
+        r.register(BlockID::Wood, P::opaque("log", 2.0));
+        r.register(
+            BlockID::Leaves,
+            P::transparent("leaves", 0.2)
+                .light_opacity(1)
+                .drops(BlockDrop::Nothing),
+        );
+        r.register(BlockID::Sponge, P::opaque("sponge", 0.6));
+        r.register(
+            BlockID::Glass,
+            P::transparent("glass", 0.3).drops(BlockDrop::Nothing),
+        );
+        r.register(
+            BlockID::LapisOre,
+            P::opaque("lapis_ore", 3.0).drops(BlockDrop::Item {
+                id: 351,
+                metadata: 4,
+                count: 4,
+            }),
+        );
+        r.register(BlockID::LapisBlock, P::opaque("lapis_block", 3.0));
+        r.register(BlockID::Sandstone, P::opaque("sandstone", 0.8));
+        r.register(
+            BlockID::BedBlock,
+            P::transparent("bed", 0.2).drops(BlockDrop::item(355, 1)),
+        );
+        r.register(
+            BlockID::Cobweb,
+            P::plant("web")
+                .light_opacity(1)
+                .drops(BlockDrop::item(287, 1)),
+        );
+        r.register(
+            BlockID::TallGrass,
+            P::plant("tallgrass")
+                .replaceable()
+                .drops(BlockDrop::Nothing),
+        );
+        r.register(
+            BlockID::DeadBush,
+            P::plant("deadbush").replaceable().drops(BlockDrop::Nothing),
+        );
+        r.register(BlockID::Wool, P::opaque("wool", 0.8));
+        r.register(BlockID::Dandelion, P::plant("yellow_flower"));
+        r.register(BlockID::Flower, P::plant("red_flower"));
+        r.register(
+            BlockID::BrownMushroom,
+            P::plant("brown_mushroom").light_emission(1),
+        );
+        r.register(BlockID::RedMushroom, P::plant("red_mushroom"));
+        r.register(BlockID::GoldBlock, P::opaque("gold_block", 3.0));
+        r.register(BlockID::IronBlock, P::opaque("iron_block", 5.0));
+        r.register(
+            BlockID::DoubleSlabs,
+            P::opaque("double_stone_slab", 2.0).drops(BlockDrop::block(BlockID::Slab, 2)),
+        );
+        r.register(
+            BlockID::Slab,
+            P::transparent("stone_slab", 2.0).light_opacity(15),
+        );
+        r.register(BlockID::Bricks, P::opaque("brick_block", 2.0));
+        r.register(BlockID::Tnt, P::opaque("tnt", 0.0));
+        r.register(
+            BlockID::Bookshelf,
+            P::opaque("bookshelf", 1.5).drops(BlockDrop::item(340, 3)),
+        );
+        r.register(BlockID::MossyStone, P::opaque("mossy_cobblestone", 2.0));
+        r.register(BlockID::Obsidian, P::opaque("obsidian", 50.0));
+        r.register(BlockID::Torch, P::plant("torch").light_emission(14));
+        r.register(
+            BlockID::Fire,
+            P::plant("fire")
+                .light_emission(15)
+                .replaceable()
+                .drops(BlockDrop::Nothing),
+        );
+        r.register(
+            BlockID::WoodenStairs,
+            P::transparent("oak_stairs", 2.0).light_opacity(15),
+        );
+        r.register(BlockID::Chest, P::transparent("chest", 2.5));
+        r.register(
+            BlockID::DiamondOre,
+            P::opaque("diamond_ore", 3.0).drops(BlockDrop::item(264, 1)),
+        );
+        r.register(BlockID::DiamondBlock, P::opaque("diamond_block", 5.0));
+        r.register(BlockID::CraftingTable, P::opaque("crafting_table", 2.5));
+        r.register(
+            BlockID::WheatBlock,
+            P::plant("wheat").drops(BlockDrop::item(295, 1)),
+        );
+        r.register(
+            BlockID::Farmland,
+            P::transparent("farmland", 0.6)
+                .light_opacity(15)
+                .drops(BlockDrop::block(BlockID::Dirt, 1)),
+        );
+        r.register(BlockID::Furnace, P::opaque("furnace", 3.5));
+        r.register(
+            BlockID::LitFurnace,
+            P::opaque("lit_furnace", 3.5)
+                .light_emission(13)
+                .drops(BlockDrop::block(BlockID::Furnace, 1)),
+        );
+        r.register(
+            BlockID::SignPost,
+            P::plant("standing_sign").drops(BlockDrop::item(323, 1)),
+        );
+        r.register(
+            BlockID::WoodenDoorBlock,
+            P::transparent("wooden_door", 3.0).drops(BlockDrop::item(324, 1)),
+        );
+        r.register(BlockID::Ladder, P::plant("ladder"));
+        r.register(
+            BlockID::CobblestoneStairs,
+            P::transparent("stone_stairs", 2.0).light_opacity(15),
+        );
+        r.register(
+            BlockID::WallSign,
+            P::plant("wall_sign").drops(BlockDrop::item(323, 1)),
+        );
+        r.register(
+            BlockID::IronDoorBlock,
+            P::transparent("iron_door", 5.0).drops(BlockDrop::item(330, 1)),
+        );
+        r.register(
+            BlockID::RedstoneOre,
+            P::opaque("redstone_ore", 3.0).drops(BlockDrop::item(331, 4)),
+        );
+        r.register(
+            BlockID::GlowingRedstoneOre,
+            P::opaque("lit_redstone_ore", 3.0)
+                .light_emission(9)
+                .drops(BlockDrop::item(331, 4)),
+        );
+        r.register(
+            BlockID::Snow,
+            P::plant("snow_layer")
+                .replaceable()
+                .drops(BlockDrop::item(332, 1)),
+        );
+        r.register(
+            BlockID::Ice,
+            P::transparent("ice", 0.5)
+                .light_opacity(3)
+                .drops(BlockDrop::Nothing),
+        );
+        r.register(
+            BlockID::SnowBlock,
+            P::opaque("snow", 0.2).drops(BlockDrop::item(332, 4)),
+        );
+        r.register(BlockID::Cactus, P::transparent("cactus", 0.4));
+        r.register(
+            BlockID::ClayBlock,
+            P::opaque("clay", 0.6).drops(BlockDrop::item(337, 4)),
+        );
+        r.register(
+            BlockID::SugarcaneBlock,
+            P::plant("reeds").drops(BlockDrop::item(338, 1)),
+        );
+        r.register(BlockID::Fence, P::transparent("fence", 2.0));
+        r.register(BlockID::Pumpkin, P::opaque("pumpkin", 1.0));
+        r.register(BlockID::Netherrack, P::opaque("netherrack", 0.4));
+        r.register(BlockID::SoulSand, P::opaque("soul_sand", 0.5));
+        r.register(
+            BlockID::GlowStoneBlock,
+            P::opaque("glowstone", 0.3)
+                .light_emission(15)
+                .drops(BlockDrop::item(348, 3)),
+        );
+        r.register(
+            BlockID::JackOLantern,
+            P::opaque("lit_pumpkin", 1.0).light_emission(15),
+        );
+        r.register(
+            BlockID::CakeBlock,
+            P::transparent("cake", 0.5).drops(BlockDrop::Nothing),
+        );
+        r.register(
+            BlockID::Unknown,
+            P::transparent("invisible_bedrock", -1.0).drops(BlockDrop::Nothing),
+        );
+        r.register(BlockID::Trapdoor, P::transparent("trapdoor", 3.0));
+        r.register(BlockID::StoneBrick, P::opaque("stonebrick", 1.5));
+        r.register(BlockID::IronBars, P::transparent("iron_bars", 5.0));
+        r.register(
+            BlockID::GlassPane,
+            P::transparent("glass_pane", 0.3).drops(BlockDrop::Nothing),
+        );
+        r.register(
+            BlockID::MelonBlock,
+            P::opaque("melon_block", 1.0).drops(BlockDrop::item(360, 3)),
+        );
+        r.register(
+            BlockID::PumpkinStem,
+            P::plant("pumpkin_stem").drops(BlockDrop::item(361, 1)),
+        );
+        r.register(
+            BlockID::MelonStem,
+            P::plant("melon_stem").drops(BlockDrop::item(362, 1)),
+        );
+        r.register(BlockID::FenceGate, P::transparent("fence_gate", 2.0));
+        r.register(
+            BlockID::BrickStairs,
+            P::transparent("brick_stairs", 2.0).light_opacity(15),
+        );
+        r.register(
+            BlockID::StoneBrickStairs,
+            P::transparent("stone_brick_stairs", 1.5).light_opacity(15),
+        );
+        r.register(BlockID::NetherBrick, P::opaque("nether_brick", 2.0));
+        r.register(
+            BlockID::NetherBrickStairs,
+            P::transparent("nether_brick_stairs", 2.0).light_opacity(15),
+        );
+        r.register(
+            BlockID::SandstoneStairs,
+            P::transparent("sandstone_stairs", 0.8).light_opacity(15),
+        );
+        r.register(
+            BlockID::SpruceWoodenStairs,
+            P::transparent("spruce_stairs", 2.0).light_opacity(15),
+        );
+        r.register(
+            BlockID::BirchWoodenStairs,
+            P::transparent("birch_stairs", 2.0).light_opacity(15),
+        );
+        r.register(
+            BlockID::JungleWoodenStairs,
+            P::transparent("jungle_stairs", 2.0).light_opacity(15),
+        );
+        r.register(BlockID::StoneWall, P::transparent("cobblestone_wall", 2.0));
+        r.register(
+            BlockID::CarrotBlock,
+            P::plant("carrots").drops(BlockDrop::item(391, 1)),
+        );
+        r.register(
+            BlockID::PotatoBlock,
+            P::plant("potatoes").drops(BlockDrop::item(392, 1)),
+        );
+        r.register(BlockID::QuartzBlock, P::opaque("quartz_block", 0.8));
+        r.register(
+            BlockID::QuartzStairs,
+            P::transparent("quartz_stairs", 0.8).light_opacity(15),
+        );
+        r.register(
+            BlockID::DoubleWoodenSlab,
+            P::opaque("double_wooden_slab", 2.0).drops(BlockDrop::block(BlockID::WoodenSlab, 2)),
+        );
+        r.register(
+            BlockID::WoodenSlab,
+            P::transparent("wooden_slab", 2.0).light_opacity(15),
+        );
+        r.register(BlockID::HayBale, P::opaque("hay_block", 0.5));
+        r.register(BlockID::Carpet, P::transparent("carpet", 0.1));
+        r.register(BlockID::CoalBlock, P::opaque("coal_block", 5.0));
+        r.register(
+            BlockID::BeetrootBlock,
+            P::plant("beetroot").drops(BlockDrop::item(458, 1)),
+        );
+        r.register(BlockID::StoneCutter, P::opaque("stonecutter", 3.5));
+        r.register(
+            BlockID::GlowingObsidian,
+            P::opaque("glowing_obsidian", 10.0).light_emission(12),
+        );
+        r.register(BlockID::NetherReactor, P::opaque("reactor_core", 3.0));
+
+        registry
+    }
+}
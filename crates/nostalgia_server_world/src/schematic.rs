@@ -0,0 +1,420 @@
+use std::{collections::HashSet, io};
+
+use nbt::{BigEndian, Compound, Compression, Nbt, Tag};
+
+use crate::{invalid_data, java_to_legacy, to_local_position, BlockID, World};
+
+/// Block ids are the ones of the client, as opposed to `Alpha` for Java Edition ids.
+const POCKET_MATERIALS: &str = "Pocket";
+const JAVA_MATERIALS: &str = "Alpha";
+
+/// Pastes changing more blocks than this relight the whole world instead of every block.
+const INCREMENTAL_LIGHT_LIMIT: usize = 1024;
+
+const STAIRS: [BlockID; 10] = [
+    BlockID::WoodenStairs,
+    BlockID::CobblestoneStairs,
+    BlockID::BrickStairs,
+    BlockID::StoneBrickStairs,
+    BlockID::NetherBrickStairs,
+    BlockID::SandstoneStairs,
+    BlockID::SpruceWoodenStairs,
+    BlockID::BirchWoodenStairs,
+    BlockID::JungleWoodenStairs,
+    BlockID::QuartzStairs,
+];
+
+/// Metadata of a block facing north, east, south and west, which are each a clockwise
+/// quarter turn apart, along with the bits holding the direction.
+fn facings(id: BlockID) -> Option<([u8; 4], u8)> {
+    match id {
+        BlockID::Torch => Some(([4, 1, 3, 2], 0x07)),
+        BlockID::Ladder
+        | BlockID::WallSign
+        | BlockID::Chest
+        | BlockID::Furnace
+        | BlockID::LitFurnace => Some(([2, 5, 3, 4], 0x07)),
+        BlockID::WoodenDoorBlock | BlockID::IronDoorBlock => Some(([3, 0, 1, 2], 0x03)),
+        BlockID::FenceGate | BlockID::Pumpkin | BlockID::JackOLantern | BlockID::BedBlock => {
+            Some(([2, 3, 0, 1], 0x03))
+        }
+        BlockID::Trapdoor => Some(([1, 2, 0, 3], 0x03)),
+        id if STAIRS.contains(&id) => Some(([3, 0, 2, 1], 0x03)),
+        _ => None,
+    }
+}
+
+/// Turns the metadata of a block by clockwise quarter turns, for blocks facing a direction.
+pub fn rotate_metadata(id: BlockID, metadata: u8, turns: u8) -> u8 {
+    let turns = turns % 4;
+    let is_door_top =
+        matches!(id, BlockID::WoodenDoorBlock | BlockID::IronDoorBlock) && metadata & 0x08 != 0;
+    if id == BlockID::SignPost {
+        return (metadata + turns * 4) & 0x0F;
+    }
+
+    let Some((facings, mask)) = facings(id).filter(|_| !is_door_top) else {
+        return metadata;
+    };
+
+    match facings.iter().position(|&facing| facing == metadata & mask) {
+        Some(index) => metadata & !mask | facings[(index + turns as usize) % 4],
+        None => metadata,
+    }
+}
+
+pub(crate) fn tile_entity_position(tag: &Tag) -> Option<(i32, i32, i32)> {
+    Some((*tag.get_int("x")?, *tag.get_int("y")?, *tag.get_int("z")?))
+}
+
+fn with_position(tag: &Tag, (x, y, z): (i32, i32, i32)) -> Tag {
+    let mut tag = tag.clone();
+    if let Tag::Compound(tags) = &mut tag {
+        tags.insert("x".to_string(), Tag::Int(x));
+        tags.insert("y".to_string(), Tag::Int(y));
+        tags.insert("z".to_string(), Tag::Int(z));
+    }
+
+    tag
+}
+
+/// A cuboid of blocks in the MCEdit `.schematic` format.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Schematic {
+    /// Size along the x axis.
+    pub width: u16,
+    pub height: u16,
+    /// Size along the z axis.
+    pub length: u16,
+    blocks: Vec<u8>,
+    data: Vec<u8>,
+    /// Tile entities with their positions relative to the schematic.
+    pub tile_entities: Vec<Tag>,
+    /// Kept so schematics from other tools survive being loaded and saved again, but
+    /// never pasted.
+    pub entities: Vec<Tag>,
+}
+
+/// Number of blocks in a schematic of the given size, if it fits in memory at all.
+fn volume(width: u16, height: u16, length: u16) -> Option<usize> {
+    (width as usize)
+        .checked_mul(height as usize)?
+        .checked_mul(length as usize)
+}
+
+/// Sizes are stored as shorts, so schematics can't be larger than this along any axis.
+fn stored_size(size: i64, name: &str) -> io::Result<u16> {
+    u16::try_from(size)
+        .ok()
+        .filter(|&size| size <= i16::MAX as u16)
+        .ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("Schematic {name} {size} is larger than {}", i16::MAX),
+            )
+        })
+}
+
+impl Schematic {
+    /// Creates a schematic filled with air.
+    pub fn new(width: u16, height: u16, length: u16) -> Self {
+        let volume = width as usize * height as usize * length as usize;
+        Self {
+            width,
+            height,
+            length,
+            blocks: vec![0; volume],
+            data: vec![0; volume],
+            tile_entities: Vec::new(),
+            entities: Vec::new(),
+        }
+    }
+
+    fn index(&self, x: usize, y: usize, z: usize) -> usize {
+        (y * self.length as usize + z) * self.width as usize + x
+    }
+
+    pub fn get(&self, x: usize, y: usize, z: usize) -> (BlockID, u8) {
+        let index = self.index(x, y, z);
+        (BlockID(self.blocks[index]), self.data[index])
+    }
+
+    pub fn set(&mut self, x: usize, y: usize, z: usize, id: BlockID, metadata: u8) {
+        let index = self.index(x, y, z);
+        self.blocks[index] = id.0;
+        self.data[index] = metadata;
+    }
+
+    /// Reads a gzipped schematic. Schematics with Java Edition ids are converted to the
+    /// closest blocks the client has.
+    pub fn from_bytes(bytes: Vec<u8>) -> io::Result<Self> {
+        let (nbt, _) = Nbt::from_compressed::<BigEndian>(bytes)?;
+        let root = nbt.root();
+        let size = |name: &str| {
+            root.get_short(name)
+                .and_then(|&size| u16::try_from(size).ok())
+                .ok_or_else(|| invalid_data(format!("Schematic has no valid {name}")))
+        };
+
+        let (width, height, length) = (size("Width")?, size("Height")?, size("Length")?);
+        let blocks = root
+            .get_byte_array("Blocks")
+            .ok_or_else(|| invalid_data("Schematic has no blocks".to_string()))?;
+        let data = root.get_byte_array("Data");
+        // Checked before allocating, as the sizes alone could ask for terabytes
+        if volume(width, height, length) != Some(blocks.len())
+            || data.is_some_and(|data| data.len() != blocks.len())
+        {
+            return Err(invalid_data(format!(
+                "Schematic has {} blocks but is {width}x{height}x{length}",
+                blocks.len(),
+            )));
+        }
+
+        let mut schematic = Self::new(width, height, length);
+
+        let materials = root
+            .get_string("Materials")
+            .map_or(JAVA_MATERIALS, String::as_str);
+        let add = root.get_byte_array("AddBlocks");
+        for (index, &id) in blocks.iter().enumerate() {
+            let metadata = data.map_or(0, |data| data[index] & 0x0F);
+            let (id, metadata) = match materials {
+                POCKET_MATERIALS => (BlockID(id), metadata),
+                JAVA_MATERIALS => {
+                    // Upper bits of the ids, two blocks per byte with the first one high
+                    let add = add.and_then(|add| add.get(index / 2)).map_or(0, |&add| {
+                        if index % 2 == 0 {
+                            add >> 4
+                        } else {
+                            add & 0x0F
+                        }
+                    });
+                    java_to_legacy(id as u16 | (add as u16) << 8, metadata)
+                }
+                materials => {
+                    return Err(invalid_data(format!(
+                        "Schematic uses unknown materials {materials}"
+                    )))
+                }
+            };
+
+            schematic.blocks[index] = id.0;
+            schematic.data[index] = metadata;
+        }
+
+        schematic.tile_entities = root.get_list("TileEntities").cloned().unwrap_or_default();
+        schematic.entities = root.get_list("Entities").cloned().unwrap_or_default();
+        Ok(schematic)
+    }
+
+    pub fn to_bytes(&self) -> io::Result<Vec<u8>> {
+        let mut tags = Compound::new();
+        for (name, size) in [
+            ("Width", self.width),
+            ("Height", self.height),
+            ("Length", self.length),
+        ] {
+            let size = stored_size(size as i64, &name.to_lowercase())?;
+            tags.insert(name.to_string(), Tag::Short(size as i16));
+        }
+        tags.insert(
+            "Materials".to_string(),
+            Tag::String(POCKET_MATERIALS.to_string()),
+        );
+        tags.insert("Blocks".to_string(), Tag::ByteArray(self.blocks.clone()));
+        tags.insert("Data".to_string(), Tag::ByteArray(self.data.clone()));
+        tags.insert("Entities".to_string(), Tag::list(self.entities.clone()));
+        tags.insert(
+            "TileEntities".to_string(),
+            Tag::list(self.tile_entities.clone()),
+        );
+
+        Ok(Nbt::named("Schematic", tags).to_compressed::<BigEndian>(Compression::Gzip)?)
+    }
+
+    /// Returns a copy turned clockwise around the y axis by the given number of quarter
+    /// turns, seen from above.
+    pub fn rotated(&self, turns: u8) -> Self {
+        let turns = turns % 4;
+        let (width, length) = match turns % 2 {
+            0 => (self.width, self.length),
+            _ => (self.length, self.width),
+        };
+
+        // One turn moves x, z to length - 1 - z, x
+        let (old_width, old_length) = (self.width as i32, self.length as i32);
+        let rotate = |x: i32, z: i32| match turns {
+            0 => (x, z),
+            1 => (old_length - 1 - z, x),
+            2 => (old_width - 1 - x, old_length - 1 - z),
+            _ => (z, old_width - 1 - x),
+        };
+
+        let mut rotated = Self::new(width, self.height, length);
+        for y in 0..self.height as usize {
+            for z in 0..self.length as usize {
+                for x in 0..self.width as usize {
+                    let (id, metadata) = self.get(x, y, z);
+                    let (new_x, new_z) = rotate(x as i32, z as i32);
+                    rotated.set(
+                        new_x as usize,
+                        y,
+                        new_z as usize,
+                        id,
+                        rotate_metadata(id, metadata, turns),
+                    );
+                }
+            }
+        }
+
+        rotated.tile_entities = self
+            .tile_entities
+            .iter()
+            .map(|tag| match tile_entity_position(tag) {
+                Some((x, y, z)) => {
+                    let (x, z) = rotate(x, z);
+                    with_position(tag, (x, y, z))
+                }
+                None => tag.clone(),
+            })
+            .collect();
+        rotated.entities = self.entities.clone();
+        rotated
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct PasteOptions {
+    /// Leaves the blocks of the world where the schematic has air.
+    pub skip_air: bool,
+    /// Clockwise quarter turns around the y axis.
+    pub rotation: u8,
+}
+
+impl World {
+    /// Copies the blocks and tile entities between two opposite corners, both included.
+    /// Blocks outside of the world are copied as air. Fails when the area is too large
+    /// to be saved as a schematic.
+    pub fn export_schematic(
+        &self,
+        from: (i32, i32, i32),
+        to: (i32, i32, i32),
+    ) -> io::Result<Schematic> {
+        let min = (from.0.min(to.0), from.1.min(to.1), from.2.min(to.2));
+        let max = (from.0.max(to.0), from.1.max(to.1), from.2.max(to.2));
+        let size = |min: i32, max: i32, name: &str| stored_size(max as i64 - min as i64 + 1, name);
+        let mut schematic = Schematic::new(
+            size(min.0, max.0, "width")?,
+            size(min.1, max.1, "height")?,
+            size(min.2, max.2, "length")?,
+        );
+
+        for y in 0..schematic.height as usize {
+            for z in 0..schematic.length as usize {
+                for x in 0..schematic.width as usize {
+                    let position = (min.0 + x as i32, min.1 + y as i32, min.2 + z as i32);
+                    if let Some(block) = self.get_block(position.0, position.1, position.2) {
+                        schematic.set(x, y, z, block.id, block.metadata);
+                    }
+                }
+            }
+        }
+
+        schematic.tile_entities = self
+            .tile_entities
+            .iter()
+            .filter_map(|tag| {
+                let (x, y, z) = tile_entity_position(tag)?;
+                let inside = (min.0..=max.0).contains(&x)
+                    && (min.1..=max.1).contains(&y)
+                    && (min.2..=max.2).contains(&z);
+                inside.then(|| with_position(tag, (x - min.0, y - min.1, z - min.2)))
+            })
+            .collect();
+
+        Ok(schematic)
+    }
+
+    /// Pastes a schematic with its lowest corner at the given position, replacing the
+    /// tile entities of every block it writes. Returns the positions of the blocks that
+    /// changed, parts outside of the world are left out.
+    pub fn paste_schematic(
+        &mut self,
+        schematic: &Schematic,
+        (origin_x, origin_y, origin_z): (i32, i32, i32),
+        options: &PasteOptions,
+    ) -> Vec<(i32, i32, i32)> {
+        let rotated;
+        let schematic = match options.rotation % 4 {
+            0 => schematic,
+            turns => {
+                rotated = schematic.rotated(turns);
+                &rotated
+            }
+        };
+
+        let mut written = HashSet::new();
+        let mut changed = Vec::new();
+        for y in 0..schematic.height as usize {
+            for z in 0..schematic.length as usize {
+                for x in 0..schematic.width as usize {
+                    let (id, metadata) = schematic.get(x, y, z);
+                    if options.skip_air && id == BlockID::Air {
+                        continue;
+                    }
+
+                    let (world_x, world_y, world_z) = (
+                        origin_x + x as i32,
+                        origin_y + y as i32,
+                        origin_z + z as i32,
+                    );
+                    let Some((position, local_x, local_y, local_z)) =
+                        to_local_position(world_x, world_y, world_z)
+                    else {
+                        continue;
+                    };
+
+                    let Some(chunk) = self.chunk_at_mut(position.x, position.z) else {
+                        continue;
+                    };
+
+                    written.insert((world_x, world_y, world_z));
+                    if chunk.block_id(local_x, local_y, local_z) == id
+                        && chunk.metadata(local_x, local_y, local_z) == metadata
+                    {
+                        continue;
+                    }
+
+                    chunk.set_block_id(local_x, local_y, local_z, id);
+                    chunk.set_metadata(local_x, local_y, local_z, metadata);
+                    changed.push((world_x, world_y, world_z));
+                }
+            }
+        }
+
+        self.tile_entities.retain(|tag| {
+            tile_entity_position(tag).is_none_or(|position| !written.contains(&position))
+        });
+        for tag in &schematic.tile_entities {
+            let Some((x, y, z)) = tile_entity_position(tag) else {
+                continue;
+            };
+
+            let position = (origin_x + x, origin_y + y, origin_z + z);
+            if written.contains(&position) {
+                self.tile_entities.push(with_position(tag, position));
+            }
+        }
+
+        if changed.len() > INCREMENTAL_LIGHT_LIMIT {
+            self.calculate_light();
+        } else {
+            for &(x, y, z) in &changed {
+                self.update_light(x, y, z);
+            }
+        }
+
+        changed
+    }
+}
@@ -0,0 +1,62 @@
+use crate::{to_local_position, BlockID, World, WORLD_SIZE_Y};
+
+/// How far around the configured spawn to look for a safe one, in blocks.
+const SPAWN_SEARCH_RADIUS: i32 = 32;
+
+/// Whether a player can be spawned standing on the block. Leaves are solid, but the
+/// tree they belong to is no place to start, and cactus hurts to stand on.
+fn is_safe_floor(id: BlockID) -> bool {
+    id.properties().solid && !matches!(id, BlockID::Leaves | BlockID::Cactus)
+}
+
+impl World {
+    fn is_air(&self, x: i32, y: i32, z: i32) -> bool {
+        // Above the world is always open
+        y >= WORLD_SIZE_Y
+            || self
+                .get_block(x, y, z)
+                .is_some_and(|b| b.id == BlockID::Air)
+    }
+
+    /// Height of the highest spot in the column with a solid floor and two air blocks
+    /// above it, which is the y coordinate of the player's feet.
+    fn safe_height(&self, x: i32, z: i32) -> Option<i32> {
+        let (position, local_x, _, local_z) = to_local_position(x, 0, z)?;
+        let top = self
+            .chunk_at(position.x, position.z)?
+            .height(local_x, local_z)
+            .clamp(1, WORLD_SIZE_Y as usize - 1) as i32;
+
+        (1..=top).rev().find(|&y| {
+            self.get_block(x, y - 1, z)
+                .is_some_and(|floor| is_safe_floor(floor.id))
+                && self.is_air(x, y, z)
+                && self.is_air(x, y + 1, z)
+        })
+    }
+
+    /// Looks for a safe place to spawn, starting at the configured spawn and moving
+    /// outwards. Returns the position of the player's feet, or `None` if there's no safe
+    /// place nearby.
+    pub fn find_safe_spawn(&self) -> Option<(i32, i32, i32)> {
+        let (spawn_x, _, spawn_z) = self.level.spawn_position();
+
+        for radius in 0..=SPAWN_SEARCH_RADIUS {
+            for dx in -radius..=radius {
+                for dz in -radius..=radius {
+                    // Only the edge of the square, the inside was searched already
+                    if dx.abs() != radius && dz.abs() != radius {
+                        continue;
+                    }
+
+                    let (x, z) = (spawn_x + dx, spawn_z + dz);
+                    if let Some(y) = self.safe_height(x, z) {
+                        return Some((x, y, z));
+                    }
+                }
+            }
+        }
+
+        None
+    }
+}
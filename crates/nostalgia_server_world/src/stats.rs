@@ -0,0 +1,96 @@
+use std::collections::BTreeMap;
+
+use nbt::Tag;
+
+use crate::{BlockID, World, CHUNK_SIZE_X, CHUNK_SIZE_Y, CHUNK_SIZE_Z};
+
+/// Ores counted by height, glowing redstone being counted as redstone.
+pub const ORES: [BlockID; 6] = [
+    BlockID::CoalOre,
+    BlockID::IronOre,
+    BlockID::GoldOre,
+    BlockID::LapisOre,
+    BlockID::RedstoneOre,
+    BlockID::DiamondOre,
+];
+
+/// Height of the layers ores are counted in.
+pub const ORE_LAYER_HEIGHT: usize = 8;
+const ORE_LAYERS: usize = CHUNK_SIZE_Y / ORE_LAYER_HEIGHT;
+
+/// Name of an entity or tile entity, from its `id` tag which is a number for entities
+/// and a string for tile entities.
+pub fn entity_name(tag: &Tag) -> String {
+    match (tag.get_int("id"), tag.get_string("id")) {
+        (Some(id), _) => id.to_string(),
+        (_, Some(id)) => id.clone(),
+        _ => "unknown".to_string(),
+    }
+}
+
+/// Counts of what a world is made of.
+#[derive(Clone, Debug, Default)]
+pub struct WorldStats {
+    pub chunks: usize,
+    /// Blocks of every id, indexed by id.
+    pub blocks: Vec<usize>,
+    /// Ores of each kind in [`ORES`], by layer of [`ORE_LAYER_HEIGHT`] blocks from the
+    /// bottom of the world.
+    pub ores: [[usize; ORE_LAYERS]; ORES.len()],
+    pub entities: BTreeMap<String, usize>,
+    pub tile_entities: BTreeMap<String, usize>,
+}
+
+impl WorldStats {
+    pub fn collect(world: &World) -> Self {
+        let mut stats = Self {
+            blocks: vec![0; 256],
+            ..Default::default()
+        };
+
+        for (_, chunk) in world.chunks() {
+            stats.chunks += 1;
+            for x in 0..CHUNK_SIZE_X {
+                for z in 0..CHUNK_SIZE_Z {
+                    for y in 0..CHUNK_SIZE_Y {
+                        let id = chunk.block_id(x, y, z);
+                        stats.blocks[id.0 as usize] += 1;
+
+                        let ore = match id {
+                            BlockID::GlowingRedstoneOre => BlockID::RedstoneOre,
+                            id => id,
+                        };
+                        if let Some(ore) = ORES.iter().position(|&other| other == ore) {
+                            stats.ores[ore][y / ORE_LAYER_HEIGHT] += 1;
+                        }
+                    }
+                }
+            }
+        }
+
+        for (tags, counts) in [
+            (&world.entities, &mut stats.entities),
+            (&world.tile_entities, &mut stats.tile_entities),
+        ] {
+            for tag in tags {
+                *counts.entry(entity_name(tag)).or_default() += 1;
+            }
+        }
+
+        stats
+    }
+
+    /// Block ids with their counts, the most common first.
+    pub fn block_histogram(&self) -> Vec<(BlockID, usize)> {
+        let mut histogram: Vec<(BlockID, usize)> = self
+            .blocks
+            .iter()
+            .enumerate()
+            .filter(|(_, &count)| count > 0)
+            .map(|(id, &count)| (BlockID(id as u8), count))
+            .collect();
+
+        histogram.sort_by(|(a_id, a), (b_id, b)| b.cmp(a).then(a_id.0.cmp(&b_id.0)));
+        histogram
+    }
+}
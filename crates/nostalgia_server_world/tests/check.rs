@@ -0,0 +1,99 @@
+mod common;
+
+use std::{fs, path::Path};
+
+use world::{
+    BlockID, Chunk, ChunkPos, Generator, LevelData, TerrainGenerator, World, WorldCheck, WorldIssue,
+};
+
+use common::temporary_directory;
+
+const SEED: i64 = 99;
+
+fn level() -> LevelData {
+    LevelData {
+        seed: SEED,
+        ..Default::default()
+    }
+}
+
+fn empty_world() -> World {
+    let mut world = World::new(level());
+    for position in ChunkPos::all() {
+        world.insert_chunk(position, Chunk::new());
+    }
+
+    world
+}
+
+/// Saves the world to `path` with the header of one chunk record broken.
+fn save_damaged(world: &World, path: &Path, broken: ChunkPos) {
+    world.save(path.to_path_buf()).unwrap();
+
+    let mut chunks = fs::read(path.join("chunks.dat")).unwrap();
+    let index = (broken.x + broken.z * 32) as usize * 4;
+    let entry = u32::from_le_bytes(chunks[index..index + 4].try_into().unwrap());
+    chunks[(entry >> 8) as usize * 4096] ^= 0xFF;
+    fs::write(path.join("chunks.dat"), chunks).unwrap();
+}
+
+fn same_blocks(first: &World, second: &World, position: ChunkPos) -> bool {
+    let (first, second) = (
+        first.chunk_at(position.x, position.z).unwrap(),
+        second.chunk_at(position.x, position.z).unwrap(),
+    );
+    (0..16).all(|x| {
+        (0..16).all(|z| (0..128).all(|y| first.block_id(x, y, z) == second.block_id(x, y, z)))
+    })
+}
+
+#[test]
+fn broken_chunks_are_found() {
+    let path = temporary_directory("check-found");
+    let broken = ChunkPos::new(4, 9);
+    save_damaged(&empty_world(), &path, broken);
+
+    let check = WorldCheck::run(&path);
+    assert!(!check.is_clean());
+    assert!(matches!(
+        check.issues.as_slice(),
+        [WorldIssue::BadChunk { position, .. }] if *position == broken
+    ));
+
+    fs::remove_dir_all(path).unwrap();
+}
+
+#[test]
+fn repairs_replace_only_the_broken_chunk() {
+    let path = temporary_directory("check-repair");
+    let broken = ChunkPos::new(4, 9);
+    let original = World::generate(level(), &TerrainGenerator::new(SEED));
+    save_damaged(&original, &path, broken);
+
+    let blank = WorldCheck::run(&path).repair(None).unwrap();
+    let chunk = blank.chunk_at(broken.x, broken.z).unwrap();
+    assert_eq!(chunk.block_id(8, 0, 8), BlockID::Air);
+    assert!(same_blocks(&blank, &original, ChunkPos::new(5, 9)));
+
+    let generator = TerrainGenerator::new(SEED);
+    let regenerated = WorldCheck::run(&path)
+        .repair(Some(&generator as &dyn Generator))
+        .unwrap();
+    assert!(same_blocks(&regenerated, &original, broken));
+    assert!(same_blocks(&regenerated, &original, ChunkPos::new(5, 9)));
+
+    fs::remove_dir_all(path).unwrap();
+}
+
+#[test]
+fn repairing_needs_the_level() {
+    let path = temporary_directory("check-level");
+    save_damaged(&empty_world(), &path, ChunkPos::new(0, 0));
+    fs::write(path.join("level.dat"), [0; 4]).unwrap();
+
+    let check = WorldCheck::run(&path);
+    assert!(check.level().is_none());
+    assert!(check.repair(None).is_err());
+
+    fs::remove_dir_all(path).unwrap();
+}
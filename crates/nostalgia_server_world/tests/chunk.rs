@@ -0,0 +1,38 @@
+use std::io::Cursor;
+
+use world::{BlockID, Chunk, CHUNK_RECORD_SIZE};
+
+fn record(chunk: &Chunk) -> Vec<u8> {
+    let mut cursor = Cursor::new(Vec::new());
+    chunk.to_bytes(&mut cursor).unwrap();
+    cursor.into_inner()
+}
+
+#[test]
+fn records_read_back_the_same_chunk() {
+    let mut chunk = Chunk::new();
+    chunk.set_block_id(3, 70, 9, BlockID::Wool);
+    chunk.set_metadata(3, 70, 9, 14);
+
+    let bytes = record(&chunk);
+    assert_eq!(bytes.len(), CHUNK_RECORD_SIZE);
+
+    let loaded = Chunk::from_bytes(bytes).unwrap();
+    assert_eq!(loaded.terrain(), chunk.terrain());
+    assert_eq!(loaded.update_map(), chunk.update_map());
+    assert_eq!(loaded.height(3, 9), 71);
+}
+
+#[test]
+fn records_of_the_wrong_size_or_header_are_rejected() {
+    let bytes = record(&Chunk::new());
+    assert!(Chunk::from_bytes(bytes[..bytes.len() - 1].to_vec()).is_err());
+
+    let mut longer = bytes.clone();
+    longer.push(0);
+    assert!(Chunk::from_bytes(longer).is_err());
+
+    let mut broken = bytes;
+    broken[0] ^= 0xFF;
+    assert!(Chunk::from_bytes(broken).is_err());
+}
@@ -0,0 +1,24 @@
+//! Helpers shared by the integration tests, each of which only uses some of them.
+#![allow(dead_code)]
+
+use std::{fs, path::PathBuf};
+
+use nbt::{Compound, Tag};
+
+/// An empty directory for the test to save into, removed first if an earlier run left
+/// it behind.
+pub fn temporary_directory(name: &str) -> PathBuf {
+    let path = std::env::temp_dir().join(format!("nostalgia-{}-{}", name, std::process::id()));
+    let _ = fs::remove_dir_all(&path);
+    path
+}
+
+pub fn tags(tags: &[(&str, Tag)]) -> Compound {
+    tags.iter()
+        .map(|(name, tag)| (name.to_string(), tag.clone()))
+        .collect()
+}
+
+pub fn compound(tags: &[(&str, Tag)]) -> Tag {
+    Tag::Compound(self::tags(tags))
+}
@@ -0,0 +1,67 @@
+mod common;
+
+use std::fs;
+
+use world::{diff_worlds, Block, BlockID, Chunk, ChunkPos, LevelData, World, WorldChange};
+
+use common::temporary_directory;
+
+fn world_with(positions: &[ChunkPos]) -> World {
+    let mut world = World::new(LevelData::default());
+    for &position in positions {
+        world.insert_chunk(position, Chunk::new());
+    }
+
+    world
+}
+
+#[test]
+fn chunks_and_blocks_are_compared() {
+    let before = world_with(&[ChunkPos::new(0, 0), ChunkPos::new(1, 0)]);
+    let mut after = world_with(&[ChunkPos::new(0, 0), ChunkPos::new(2, 0)]);
+    after.set_block(3, 4, 5, Block::existing(BlockID::Wool, 0, 0, 2));
+
+    assert_eq!(
+        diff_worlds(&before, &after),
+        vec![
+            WorldChange::ChunkRemoved(ChunkPos::new(1, 0)),
+            WorldChange::ChunkAdded(ChunkPos::new(2, 0)),
+            WorldChange::Block {
+                position: (3, 4, 5),
+                before: (BlockID::Air, 0),
+                after: (BlockID::Wool, 2),
+            },
+        ]
+    );
+}
+
+#[test]
+fn unreadable_chunks_are_reported_as_such() {
+    let path = temporary_directory("diff-unreadable");
+    let broken = ChunkPos::new(1, 0);
+    let before = world_with(&[ChunkPos::new(0, 0), broken]);
+    before.save(path.clone()).unwrap();
+
+    // Break the header of one record
+    let mut chunks = fs::read(path.join("chunks.dat")).unwrap();
+    let index = (broken.x + broken.z * 32) as usize * 4;
+    let entry = u32::from_le_bytes(chunks[index..index + 4].try_into().unwrap());
+    chunks[(entry >> 8) as usize * 4096] ^= 0xFF;
+    fs::write(path.join("chunks.dat"), chunks).unwrap();
+
+    let after = World::from_file(path.clone()).unwrap();
+    let changes = diff_worlds(&before, &after);
+    assert!(matches!(
+        changes.as_slice(),
+        [WorldChange::ChunkUnreadable {
+            position,
+            before: None,
+            after: Some(_),
+        }] if *position == broken
+    ));
+    assert!(changes[0]
+        .to_string()
+        .starts_with("! chunk 1 0 is unreadable, after: "));
+
+    fs::remove_dir_all(path).unwrap();
+}
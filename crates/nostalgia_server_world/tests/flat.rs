@@ -0,0 +1,78 @@
+use world::{BlockID, ChunkPos, FlatGenerator, FlatLayer, Generator};
+
+fn layer(block: BlockID, metadata: u8, count: u8) -> FlatLayer {
+    FlatLayer {
+        block,
+        metadata,
+        count,
+    }
+}
+
+fn error(layers: &str) -> String {
+    FlatGenerator::from_layers(layers).unwrap_err().to_string()
+}
+
+#[test]
+fn layers_are_parsed_with_their_count_and_metadata() {
+    assert_eq!(
+        FlatLayer::parse("2*dirt").unwrap(),
+        layer(BlockID::Dirt, 0, 2)
+    );
+    assert_eq!(
+        FlatLayer::parse("35:14").unwrap(),
+        layer(BlockID::Wool, 14, 1)
+    );
+    assert_eq!(
+        FlatLayer::parse(" 3 * wool : 4 ").unwrap(),
+        layer(BlockID::Wool, 4, 3)
+    );
+}
+
+#[test]
+fn blocks_are_found_by_name_or_id() {
+    assert_eq!(FlatLayer::parse("grass").unwrap().block, BlockID::Grass);
+    assert_eq!(FlatLayer::parse("2").unwrap().block, BlockID::Grass);
+    assert!(FlatLayer::parse("no_such_block").is_err());
+    // Ids without a block aren't placed
+    assert!(FlatLayer::parse("255").is_err());
+}
+
+#[test]
+fn bad_numbers_say_what_is_wrong() {
+    assert!(error("300*stone").contains("count is too large"));
+    assert!(error("many*stone").contains("count is not a number"));
+    assert!(error("wool:300").contains("metadata is too large"));
+    assert!(error("wool:16").contains("metadata is above 15"));
+}
+
+#[test]
+fn layers_stack_from_the_bottom_up() {
+    let generator = FlatGenerator::from_layers("bedrock, 2*dirt,,grass").unwrap();
+    assert_eq!(
+        generator.layers(),
+        [
+            layer(BlockID::Bedrock, 0, 1),
+            layer(BlockID::Dirt, 0, 2),
+            layer(BlockID::Grass, 0, 1),
+        ]
+    );
+
+    let chunk = generator.generate_chunk(ChunkPos::new(3, 4));
+    let column: Vec<BlockID> = (0..5).map(|y| chunk.block_id(7, y, 7)).collect();
+    assert_eq!(
+        column,
+        [
+            BlockID::Bedrock,
+            BlockID::Dirt,
+            BlockID::Dirt,
+            BlockID::Grass,
+            BlockID::Air
+        ]
+    );
+}
+
+#[test]
+fn layers_higher_than_the_world_are_rejected() {
+    assert!(FlatGenerator::from_layers("64*stone,64*dirt").is_ok());
+    assert!(error("64*stone,64*dirt,grass").contains("129 blocks high"));
+}
@@ -0,0 +1,151 @@
+use world::{
+    generate_chunks, BlockID, CaveDecorator, Chunk, ChunkPos, Decorator, Generator, LevelData,
+    Random, TerrainGenerator, World, CHUNK_SIZE_X, CHUNK_SIZE_Y, CHUNK_SIZE_Z,
+};
+
+const POSITIONS: [ChunkPos; 4] = [
+    ChunkPos { x: 0, z: 0 },
+    ChunkPos { x: 5, z: 9 },
+    ChunkPos { x: 12, z: 3 },
+    ChunkPos { x: 15, z: 15 },
+];
+
+#[test]
+fn same_seed_generates_the_same_chunks() {
+    let first = TerrainGenerator::new(42);
+    let second = TerrainGenerator::new(42);
+
+    for position in POSITIONS {
+        assert_eq!(
+            first.generate_chunk(position).terrain(),
+            second.generate_chunk(position).terrain(),
+            "chunk {:?}",
+            position
+        );
+    }
+}
+
+#[test]
+fn different_seeds_generate_different_terrain() {
+    let first = TerrainGenerator::new(42);
+    let second = TerrainGenerator::new(43);
+
+    for position in POSITIONS {
+        assert_ne!(
+            first.generate_chunk(position).terrain(),
+            second.generate_chunk(position).terrain(),
+            "chunk {:?}",
+            position
+        );
+    }
+
+    let heights = |generator: &TerrainGenerator| -> Vec<i32> {
+        (0..256)
+            .step_by(16)
+            .map(|x| generator.surface_height(x, x))
+            .collect()
+    };
+    assert_ne!(heights(&first), heights(&second));
+}
+
+fn block_ids(chunk: &Chunk) -> Vec<(BlockID, u8)> {
+    let mut blocks = Vec::new();
+    for x in 0..CHUNK_SIZE_X {
+        for z in 0..CHUNK_SIZE_Z {
+            for y in 0..CHUNK_SIZE_Y {
+                blocks.push((chunk.block_id(x, y, z), chunk.metadata(x, y, z)));
+            }
+        }
+    }
+
+    blocks
+}
+
+#[test]
+fn single_chunks_match_the_generated_world() {
+    let generator = TerrainGenerator::new(42);
+    let world = World::generate(
+        LevelData {
+            seed: 42,
+            ..Default::default()
+        },
+        &generator,
+    );
+
+    let chunks = generate_chunks(42, &generator, &POSITIONS);
+    assert_eq!(chunks.len(), POSITIONS.len());
+    for position in POSITIONS {
+        assert_eq!(
+            block_ids(&chunks[&position]),
+            block_ids(world.chunk_at(position.x, position.z).unwrap()),
+            "chunk {:?}",
+            position
+        );
+    }
+}
+
+#[test]
+fn empty_ranges_give_their_minimum() {
+    let mut random = Random::new(42);
+    assert_eq!(random.next_between(1, 0), 1);
+    assert_eq!(random.next_between(5, 5), 5);
+    assert!((0..100).all(|_| (-2..=0).contains(&random.next_between(-2, 0))));
+}
+
+/// Terrain with caves in every chunk, but no tunnels allowed.
+struct NoTunnels {
+    terrain: TerrainGenerator,
+    decorators: Vec<Box<dyn Decorator>>,
+}
+
+impl Generator for NoTunnels {
+    fn generate_chunk(&self, position: ChunkPos) -> Chunk {
+        self.terrain.generate_chunk(position)
+    }
+
+    fn decorators(&self) -> &[Box<dyn Decorator>] {
+        &self.decorators
+    }
+}
+
+#[test]
+fn caves_without_tunnels_leave_the_terrain_alone() {
+    let generator = NoTunnels {
+        terrain: TerrainGenerator::new(42),
+        decorators: vec![Box::new(CaveDecorator {
+            chance: 1.0,
+            max_tunnels: 0,
+        })],
+    };
+
+    let chunks = generate_chunks(42, &generator, &POSITIONS);
+    for position in POSITIONS {
+        assert_eq!(
+            block_ids(&chunks[&position]),
+            block_ids(&generator.terrain.generate_chunk(position)),
+            "chunk {:?}",
+            position
+        );
+    }
+}
+
+#[test]
+fn decorating_twice_gives_the_same_chunks() {
+    let generator = TerrainGenerator::new(7);
+    let center = [ChunkPos::new(6, 6)];
+    let first = generate_chunks(7, &generator, &center);
+    let second = generate_chunks(7, &generator, &center);
+
+    let mut decorated = false;
+    for (position, chunk) in &first {
+        assert_eq!(
+            chunk.terrain(),
+            second[position].terrain(),
+            "{:?}",
+            position
+        );
+        decorated |= chunk.terrain() != generator.generate_chunk(*position).terrain();
+    }
+
+    assert!(decorated, "Decorators didn't change anything");
+}
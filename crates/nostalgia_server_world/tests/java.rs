@@ -0,0 +1,138 @@
+mod common;
+
+use nbt::Tag;
+use world::{convert_chunk, java_to_legacy, BlockID, CHUNK_SIZE_Y, CHUNK_SIZE_Z};
+
+use common::compound;
+
+fn set_nibble(array: &mut [u8], index: usize, value: u8) {
+    if index.is_multiple_of(2) {
+        array[index / 2] |= value;
+    } else {
+        array[index / 2] |= value << 4;
+    }
+}
+
+/// An Anvil section with a single block, its arrays ordered by y, z, x.
+fn section(y: i8, (x, block_y, z): (usize, usize, usize), id: u8, data: u8) -> Tag {
+    let index = x + z * 16 + block_y * 256;
+    let mut blocks = vec![0; 4096];
+    let mut data_array = vec![0; 2048];
+    blocks[index] = id;
+    set_nibble(&mut data_array, index, data);
+
+    compound(&[
+        ("Y", Tag::Byte(y as u8)),
+        ("Blocks", Tag::ByteArray(blocks)),
+        ("Data", Tag::ByteArray(data_array)),
+    ])
+}
+
+fn anvil_chunk(sections: Vec<Tag>) -> Tag {
+    compound(&[("Level", compound(&[("Sections", Tag::list(sections))]))])
+}
+
+#[test]
+fn known_blocks_keep_their_id() {
+    assert_eq!(java_to_legacy(4, 0), (BlockID::Cobblestone, 0));
+    assert_eq!(java_to_legacy(35, 14), (BlockID::Wool, 14));
+}
+
+#[test]
+fn variants_the_client_lacks_are_dropped() {
+    // Granite and a birch log lying along x
+    assert_eq!(java_to_legacy(1, 1), (BlockID::Stone, 0));
+    assert_eq!(java_to_legacy(17, 2 | 4), (BlockID::Wood, 2));
+}
+
+#[test]
+fn conflicting_and_unknown_blocks_use_the_fallback() {
+    // Stained glass is invisible bedrock on the client
+    assert_eq!(java_to_legacy(95, 3), (BlockID::Glass, 0));
+    // Redstone wire is left out
+    assert_eq!(java_to_legacy(55, 15), (BlockID::Air, 0));
+    // Glazed terracotta
+    assert_eq!(java_to_legacy(240, 0), (BlockID::Wool, 5));
+    // Ids only reachable through `Add`
+    assert_eq!(java_to_legacy(300, 0), (BlockID::Stone, 0));
+}
+
+#[test]
+fn mcregion_chunks_are_ordered_like_legacy_ones() {
+    let (x, y, z) = (1, 70, 3);
+    let index = y + z * CHUNK_SIZE_Y + x * CHUNK_SIZE_Y * CHUNK_SIZE_Z;
+    let mut blocks = vec![0; 16 * 16 * CHUNK_SIZE_Y];
+    let mut data = vec![0; blocks.len() / 2];
+    blocks[index] = 35;
+    set_nibble(&mut data, index, 11);
+
+    let root = compound(&[(
+        "Level",
+        compound(&[
+            ("Blocks", Tag::ByteArray(blocks)),
+            ("Data", Tag::ByteArray(data)),
+        ]),
+    )]);
+
+    let chunk = convert_chunk(&root, 0).unwrap();
+    assert_eq!(chunk.block_id(x, y, z), BlockID::Wool);
+    assert_eq!(chunk.metadata(x, y, z), 11);
+    assert_eq!(chunk.block_id(z, y, x), BlockID::Air);
+
+    // Everything is shifted down by min_y
+    let chunk = convert_chunk(&root, 64).unwrap();
+    assert_eq!(chunk.block_id(x, y - 64, z), BlockID::Wool);
+}
+
+#[test]
+fn anvil_sections_are_ordered_by_y_z_x() {
+    let root = anvil_chunk(vec![section(1, (1, 2, 3), 35, 11)]);
+
+    let chunk = convert_chunk(&root, 0).unwrap();
+    assert_eq!(chunk.block_id(1, 18, 3), BlockID::Wool);
+    assert_eq!(chunk.metadata(1, 18, 3), 11);
+    assert_eq!(chunk.block_id(3, 18, 1), BlockID::Air);
+}
+
+#[test]
+fn anvil_sections_above_the_legacy_height_are_cut_off() {
+    let root = anvil_chunk(vec![section(8, (0, 0, 0), 4, 0)]);
+
+    let chunk = convert_chunk(&root, 0).unwrap();
+    assert_eq!(chunk.block_id(0, 0, 0), BlockID::Air);
+    let chunk = convert_chunk(&root, 64).unwrap();
+    assert_eq!(chunk.block_id(0, 64, 0), BlockID::Cobblestone);
+}
+
+#[test]
+fn anvil_sections_below_zero_keep_their_height() {
+    let root = anvil_chunk(vec![section(-1, (5, 2, 6), 4, 0)]);
+
+    let chunk = convert_chunk(&root, -16).unwrap();
+    assert_eq!(chunk.block_id(5, 2, 6), BlockID::Cobblestone);
+    // Nothing ends up at the top of the world
+    let chunk = convert_chunk(&root, 0).unwrap();
+    assert!((0..CHUNK_SIZE_Y).all(|y| chunk.block_id(5, y, 6) == BlockID::Air));
+}
+
+#[test]
+fn chunks_without_block_ids_are_rejected() {
+    let root = anvil_chunk(vec![compound(&[("Y", Tag::Byte(0))])]);
+    assert!(convert_chunk(&root, 0).is_err());
+    assert!(convert_chunk(&compound(&[("Level", compound(&[]))]), 0).is_err());
+}
+
+#[test]
+fn block_arrays_of_the_wrong_size_are_rejected() {
+    let root = compound(&[(
+        "Level",
+        compound(&[("Blocks", Tag::ByteArray(vec![1; 40000]))]),
+    )]);
+    assert!(convert_chunk(&root, 0).is_err());
+
+    let section = compound(&[
+        ("Y", Tag::Byte(0)),
+        ("Blocks", Tag::ByteArray(vec![1; 4000])),
+    ]);
+    assert!(convert_chunk(&anvil_chunk(vec![section]), 0).is_err());
+}
@@ -0,0 +1,56 @@
+use nbt::{Compound, Header, Nbt, Tag, LEVEL_STORAGE_VERSION};
+use world::LevelData;
+
+fn unknown_tags() -> Compound {
+    let player = Compound::from([("Health".to_string(), Tag::Short(20))]);
+    Compound::from([
+        ("EmptyCompounds".to_string(), Tag::List(10, vec![])),
+        ("EmptyInts".to_string(), Tag::List(3, vec![])),
+        (
+            "Players".to_string(),
+            Tag::list(vec![Tag::Compound(player)]),
+        ),
+        ("Flag".to_string(), Tag::Byte(1)),
+    ])
+}
+
+#[test]
+fn unknown_tags_survive_a_round_trip() {
+    let level = LevelData {
+        name: "Unknown".to_string(),
+        unknown: unknown_tags(),
+        ..Default::default()
+    };
+
+    let loaded = LevelData::from_bytes(level.to_bytes().unwrap()).unwrap();
+    assert_eq!(loaded, level);
+    assert_eq!(
+        loaded.unknown.get("EmptyCompounds"),
+        Some(&Tag::List(10, vec![]))
+    );
+}
+
+#[test]
+fn unknown_tags_of_a_client_file_are_kept() {
+    let mut tags = unknown_tags();
+    tags.insert("LevelName".to_string(), Tag::String("Client".to_string()));
+    tags.insert(
+        "StorageVersion".to_string(),
+        Tag::Int(LEVEL_STORAGE_VERSION),
+    );
+    let bytes = Nbt::new(tags)
+        .to_pocket_file(Header::Level(LEVEL_STORAGE_VERSION))
+        .unwrap();
+
+    let level = LevelData::from_bytes(bytes).unwrap();
+    assert_eq!(level.name, "Client");
+    assert_eq!(level.unknown, unknown_tags());
+
+    let (nbt, _) = Nbt::from_pocket_file(level.to_bytes().unwrap()).unwrap();
+    let Tag::Compound(saved) = nbt.root() else {
+        panic!("level.dat root isn't a compound");
+    };
+    for (name, tag) in &unknown_tags() {
+        assert_eq!(saved.get(name), Some(tag), "{}", name);
+    }
+}
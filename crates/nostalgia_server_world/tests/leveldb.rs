@@ -0,0 +1,108 @@
+mod common;
+
+use std::fs;
+
+use nbt::Tag;
+use world::{Block, BlockID, Chunk, ChunkPos, LevelData, World};
+
+use common::{compound, temporary_directory};
+
+fn entity(x: f32, z: f32) -> Tag {
+    compound(&[
+        ("id", Tag::Int(10)),
+        (
+            "Pos",
+            Tag::list(vec![Tag::Float(x), Tag::Float(64.0), Tag::Float(z)]),
+        ),
+    ])
+}
+
+fn sample_world() -> World {
+    let mut world = World::new(LevelData {
+        name: "LevelDB".to_string(),
+        ..Default::default()
+    });
+    for position in [ChunkPos::new(0, 0), ChunkPos::new(9, 4)] {
+        world.insert_chunk(position, Chunk::new());
+    }
+
+    world.set_block(1, 2, 3, Block::new(BlockID::Stone));
+    world.set_block(150, 64, 70, Block::existing(BlockID::Wool, 0, 0, 14));
+    world.entities = vec![entity(1.5, 3.5), entity(150.5, 70.5)];
+    world.tile_entities.push(compound(&[
+        ("id", Tag::String("Chest".to_string())),
+        ("x", Tag::Int(150)),
+        ("y", Tag::Int(64)),
+        ("z", Tag::Int(70)),
+    ]));
+
+    world
+}
+
+fn sorted(tags: &[Tag]) -> Vec<String> {
+    let mut tags: Vec<String> = tags.iter().map(|tag| format!("{:?}", tag)).collect();
+    tags.sort();
+    tags
+}
+
+fn assert_same_contents(expected: &World, actual: &World) {
+    for (position, chunk) in expected.chunks() {
+        let loaded = actual.chunk_at(position.x, position.z).unwrap();
+        assert_eq!(loaded.terrain(), chunk.terrain(), "chunk {:?}", position);
+    }
+
+    assert_eq!(sorted(&actual.entities), sorted(&expected.entities));
+    assert_eq!(
+        sorted(&actual.tile_entities),
+        sorted(&expected.tile_entities)
+    );
+}
+
+#[test]
+fn world_round_trips_through_a_store() {
+    let world = sample_world();
+    let store = world.to_leveldb().unwrap();
+    let loaded = World::from_leveldb(world.level.clone(), &store).unwrap();
+
+    assert_same_contents(&world, &loaded);
+    // Missing chunks are filled in
+    assert!(loaded.chunk_at(15, 15).is_some());
+}
+
+#[test]
+fn entities_outside_of_the_world_are_kept() {
+    let mut world = sample_world();
+    world.entities.push(entity(-20.0, 300.0));
+    world
+        .tile_entities
+        .push(compound(&[("id", Tag::String("Sign".to_string()))]));
+
+    let store = world.to_leveldb().unwrap();
+    let loaded = World::from_leveldb(world.level.clone(), &store).unwrap();
+    assert_same_contents(&world, &loaded);
+}
+
+#[test]
+fn saved_store_opens_the_same() {
+    let path = temporary_directory("leveldb-round-trip");
+    let world = sample_world();
+    world.save_leveldb(&path).unwrap();
+
+    let loaded = World::from_leveldb_file(&path).unwrap();
+    assert_eq!(loaded.level.name, "LevelDB");
+    assert_same_contents(&world, &loaded);
+
+    // Saving again replaces the store instead of adding to it
+    let mut changed = sample_world();
+    changed.set_block(1, 2, 3, Block::new(BlockID::Air));
+    changed.entities.clear();
+    changed.save_leveldb(&path).unwrap();
+
+    let loaded = World::from_leveldb_file(&path).unwrap();
+    assert_same_contents(&changed, &loaded);
+    assert!(path.join("db").is_dir());
+    assert!(!path.join("db.tmp").exists());
+    assert!(!path.join("db.old").exists());
+
+    fs::remove_dir_all(path).unwrap();
+}
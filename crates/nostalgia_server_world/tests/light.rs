@@ -0,0 +1,126 @@
+use world::{
+    Block, BlockID, Chunk, ChunkPos, LevelData, LightKind, World, CHUNK_SIZE_X, CHUNK_SIZE_Z,
+    MAX_LIGHT,
+};
+
+const FLOOR_HEIGHT: usize = 4;
+
+/// Two chunks side by side along x, with a stone floor, lit from scratch.
+fn world_with_floor(blocks: &[(i32, i32, i32, BlockID)]) -> World {
+    let mut world = World::new(LevelData::default());
+    for chunk_x in 0..2 {
+        let mut chunk = Chunk::new();
+        for x in 0..CHUNK_SIZE_X {
+            for z in 0..CHUNK_SIZE_Z {
+                for y in 0..FLOOR_HEIGHT {
+                    chunk.set_block_id(x, y, z, BlockID::Stone);
+                }
+            }
+        }
+
+        chunk.calculate_height_map();
+        world.insert_chunk(ChunkPos::new(chunk_x, 0), chunk);
+    }
+
+    world.calculate_light();
+    for &(x, y, z, id) in blocks {
+        world.set_block(x, y, z, Block::new(id));
+    }
+
+    world
+}
+
+/// Asserts the light above the floor matches lighting the same blocks from scratch.
+fn assert_matches_full_lighting(world: &World, blocks: &[(i32, i32, i32, BlockID)]) {
+    let mut expected = world_with_floor(&[]);
+    for &(x, y, z, id) in blocks {
+        let (position, x, y, z) = world::to_local_position(x, y, z).unwrap();
+        expected
+            .chunk_at_mut(position.x, position.z)
+            .unwrap()
+            .set_block_id(x, y, z, id);
+    }
+    expected.calculate_light();
+
+    for kind in [LightKind::Sky, LightKind::Block] {
+        for x in 0..32 {
+            for z in 0..16 {
+                for y in FLOOR_HEIGHT as i32..32 {
+                    assert_eq!(
+                        world.get_light(kind, x, y, z),
+                        expected.get_light(kind, x, y, z),
+                        "{:?} light at {} {} {}",
+                        kind,
+                        x,
+                        y,
+                        z
+                    );
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn opaque_block_shades_the_column_below() {
+    let world = world_with_floor(&[(8, 20, 8, BlockID::Stone)]);
+
+    assert_eq!(world.get_light(LightKind::Sky, 8, 21, 8), Some(MAX_LIGHT));
+    assert_eq!(world.get_light(LightKind::Sky, 8, 20, 8), Some(0));
+    // Light still comes in from the open columns next to it
+    assert_eq!(
+        world.get_light(LightKind::Sky, 8, 19, 8),
+        Some(MAX_LIGHT - 1)
+    );
+    assert_eq!(
+        world.get_light(LightKind::Sky, 8, FLOOR_HEIGHT as i32, 8),
+        Some(MAX_LIGHT - 1)
+    );
+
+    assert_matches_full_lighting(&world, &[(8, 20, 8, BlockID::Stone)]);
+}
+
+#[test]
+fn removing_an_opaque_block_lets_the_sky_back_in() {
+    let mut world = world_with_floor(&[(8, 20, 8, BlockID::Stone)]);
+    world.set_block(8, 20, 8, Block::new(BlockID::Air));
+
+    for y in FLOOR_HEIGHT as i32..=20 {
+        assert_eq!(world.get_light(LightKind::Sky, 8, y, 8), Some(MAX_LIGHT));
+    }
+    assert_matches_full_lighting(&world, &[]);
+}
+
+#[test]
+fn torch_lights_its_surroundings_until_removed() {
+    let mut world = world_with_floor(&[(8, 10, 8, BlockID::Torch)]);
+
+    assert_eq!(world.get_light(LightKind::Block, 8, 10, 8), Some(14));
+    assert_eq!(world.get_light(LightKind::Block, 8, 10, 11), Some(11));
+    assert_eq!(world.get_light(LightKind::Block, 10, 12, 8), Some(10));
+    assert_matches_full_lighting(&world, &[(8, 10, 8, BlockID::Torch)]);
+
+    world.set_block(8, 10, 8, Block::new(BlockID::Air));
+    assert_eq!(world.get_light(LightKind::Block, 8, 10, 8), Some(0));
+    assert_eq!(world.get_light(LightKind::Block, 8, 10, 11), Some(0));
+    assert_matches_full_lighting(&world, &[]);
+}
+
+#[test]
+fn light_crosses_chunk_borders() {
+    let torch = [(15, 10, 8, BlockID::Torch)];
+    let world = world_with_floor(&torch);
+
+    assert_eq!(world.get_light(LightKind::Block, 16, 10, 8), Some(13));
+    assert_eq!(world.get_light(LightKind::Block, 20, 10, 8), Some(9));
+    assert_matches_full_lighting(&world, &torch);
+
+    // A roof over the border shades both chunks
+    let roof: Vec<(i32, i32, i32, BlockID)> = (12..20)
+        .flat_map(|x| (4..12).map(move |z| (x, 20, z, BlockID::Stone)))
+        .collect();
+    let world = world_with_floor(&roof);
+    assert!(world.get_light(LightKind::Sky, 15, 19, 8).unwrap() < MAX_LIGHT);
+    assert!(world.get_light(LightKind::Sky, 16, 19, 8).unwrap() < MAX_LIGHT);
+    assert_matches_full_lighting(&world, &roof);
+}
@@ -0,0 +1,93 @@
+use world::{ChunkLocation, ChunkPos, LocationProblem, LocationTable, CHUNK_RECORD_SIZE};
+
+const SECTOR_SIZE: usize = 4096;
+const CHUNK_SECTORS: usize = CHUNK_RECORD_SIZE.div_ceil(SECTOR_SIZE);
+
+/// A location table with the given chunks, and the length of a file holding all of them.
+fn table_bytes(entries: &[(ChunkPos, usize)]) -> (Vec<u8>, usize) {
+    let mut table = vec![0; SECTOR_SIZE];
+    let mut end = SECTOR_SIZE;
+    for &(position, sector) in entries {
+        let index = (position.x + position.z * 32) as usize * 4;
+        let entry = (sector << 8 | CHUNK_SECTORS) as u32;
+        table[index..index + 4].copy_from_slice(&entry.to_le_bytes());
+        end = end.max(sector * SECTOR_SIZE + CHUNK_RECORD_SIZE);
+    }
+
+    (table, end)
+}
+
+fn location(position: ChunkPos, sector: usize) -> ChunkLocation {
+    ChunkLocation {
+        position,
+        sector,
+        sector_count: CHUNK_SECTORS,
+    }
+}
+
+#[test]
+fn chunks_without_an_entry_are_missing() {
+    let first = ChunkPos::new(0, 0);
+    let (bytes, length) = table_bytes(&[(first, 1)]);
+    let table = LocationTable::read(&bytes, length).unwrap();
+
+    assert_eq!(table.locations, vec![location(first, 1)]);
+    assert_eq!(table.problems.len(), 16 * 16 - 1);
+    assert!(table
+        .problems
+        .contains(&LocationProblem::Missing(ChunkPos::new(15, 15))));
+    // Only the legacy world has to be complete
+    assert!(!table
+        .problems
+        .contains(&LocationProblem::Missing(ChunkPos::new(16, 0))));
+}
+
+#[test]
+fn records_past_the_end_of_the_file_are_truncated() {
+    let position = ChunkPos::new(3, 4);
+    let (bytes, length) = table_bytes(&[(position, 1)]);
+    let table = LocationTable::read(&bytes, length - 1).unwrap();
+
+    assert!(table.locations.is_empty());
+    assert!(table
+        .problems
+        .contains(&LocationProblem::Truncated(location(position, 1))));
+}
+
+#[test]
+fn records_sharing_sectors_overlap() {
+    let (first, second) = (ChunkPos::new(0, 0), ChunkPos::new(1, 0));
+    // The second record starts in the last sector of the first one
+    let (bytes, length) = table_bytes(&[(first, 1), (second, CHUNK_SECTORS)]);
+    let table = LocationTable::read(&bytes, length).unwrap();
+
+    assert!(table.problems.contains(&LocationProblem::Overlapping {
+        location: location(second, CHUNK_SECTORS),
+        other: Some(first),
+    }));
+
+    // Records right after each other don't
+    let (bytes, length) = table_bytes(&[(first, 1), (second, 1 + CHUNK_SECTORS)]);
+    let table = LocationTable::read(&bytes, length).unwrap();
+    assert!(table
+        .problems
+        .iter()
+        .all(|problem| matches!(problem, LocationProblem::Missing(_))));
+}
+
+#[test]
+fn records_in_sector_zero_overlap_the_table() {
+    let position = ChunkPos::new(2, 2);
+    let (bytes, length) = table_bytes(&[(position, 0)]);
+    let table = LocationTable::read(&bytes, length.max(SECTOR_SIZE * 2)).unwrap();
+
+    assert!(table.problems.contains(&LocationProblem::Overlapping {
+        location: location(position, 0),
+        other: None,
+    }));
+}
+
+#[test]
+fn short_tables_are_an_error() {
+    assert!(LocationTable::read(&[0; 100], SECTOR_SIZE).is_err());
+}
@@ -0,0 +1,45 @@
+use std::collections::HashMap;
+
+use world::{to_local_position, BlockID, Chunk, ChunkNeighbourhood, ChunkPos};
+
+fn chunks() -> HashMap<ChunkPos, Chunk> {
+    let mut chunks = HashMap::new();
+    for x in 0..5 {
+        for z in 0..5 {
+            chunks.insert(ChunkPos::new(x, z), Chunk::new());
+        }
+    }
+
+    chunks
+}
+
+#[test]
+fn set_block_reaches_into_neighbouring_chunks() {
+    let mut chunks = chunks();
+    let mut neighbourhood = ChunkNeighbourhood::new(ChunkPos::new(2, 2), &mut chunks);
+
+    // Just past the corner of the center chunk, in chunk 1, 3
+    assert!(neighbourhood.set_block(31, 70, 48, BlockID::Leaves, 2));
+    assert_eq!(neighbourhood.block_id(31, 70, 48), Some(BlockID::Leaves));
+
+    let chunk = &chunks[&ChunkPos::new(1, 3)];
+    assert_eq!(chunk.block_id(15, 70, 0), BlockID::Leaves);
+    assert_eq!(chunk.metadata(15, 70, 0), 2);
+}
+
+#[test]
+fn set_block_refuses_chunks_two_away() {
+    let mut chunks = chunks();
+    let mut neighbourhood = ChunkNeighbourhood::new(ChunkPos::new(2, 2), &mut chunks);
+
+    let outside = [(15, 40), (64, 40), (40, 15), (40, 64)];
+    for (x, z) in outside {
+        assert!(!neighbourhood.set_block(x, 70, z, BlockID::Stone, 0));
+        assert_eq!(neighbourhood.block_id(x, 70, z), None);
+    }
+
+    for (x, z) in outside {
+        let (position, x, y, z) = to_local_position(x, 70, z).unwrap();
+        assert_eq!(chunks[&position].block_id(x, y, z), BlockID::Air);
+    }
+}
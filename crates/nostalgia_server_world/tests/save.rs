@@ -0,0 +1,173 @@
+mod common;
+
+use std::fs;
+
+use nbt::Tag;
+use world::{Block, BlockID, Chunk, ChunkPos, LevelData, LocationProblem, World};
+
+use common::{compound, temporary_directory};
+
+fn sample_world() -> World {
+    let mut world = World::new(LevelData {
+        name: "Round trip".to_string(),
+        seed: 1234,
+        ..Default::default()
+    });
+
+    for position in [
+        ChunkPos::new(0, 0),
+        ChunkPos::new(3, 7),
+        ChunkPos::new(15, 15),
+    ] {
+        world.insert_chunk(position, Chunk::new());
+    }
+
+    world.set_block(1, 2, 3, Block::new(BlockID::Stone));
+    world.set_block(60, 64, 120, Block::existing(BlockID::Wool, 0, 0, 14));
+    world.set_block(255, 127, 255, Block::new(BlockID::Glass));
+
+    world.entities.push(compound(&[
+        ("id", Tag::Int(10)),
+        (
+            "Pos",
+            Tag::list(vec![Tag::Float(1.5), Tag::Float(3.0), Tag::Float(3.5)]),
+        ),
+    ]));
+    world.tile_entities.push(compound(&[
+        ("id", Tag::String("Chest".to_string())),
+        ("x", Tag::Int(1)),
+        ("y", Tag::Int(2)),
+        ("z", Tag::Int(3)),
+    ]));
+
+    world
+}
+
+fn assert_same_world(expected: &World, actual: &World) {
+    assert_eq!(actual.level, expected.level);
+    assert_eq!(actual.entities, expected.entities);
+    assert_eq!(actual.tile_entities, expected.tile_entities);
+
+    let mut positions: Vec<ChunkPos> = actual.chunks().map(|(position, _)| position).collect();
+    positions.sort();
+    let mut expected_positions: Vec<ChunkPos> =
+        expected.chunks().map(|(position, _)| position).collect();
+    expected_positions.sort();
+    assert_eq!(positions, expected_positions);
+
+    for position in positions {
+        let expected = expected.chunk_at(position.x, position.z).unwrap();
+        let actual = actual.chunk_at(position.x, position.z).unwrap();
+        assert_eq!(actual.terrain(), expected.terrain(), "chunk {:?}", position);
+        assert_eq!(actual.update_map(), expected.update_map());
+    }
+}
+
+#[test]
+fn saved_world_loads_back_the_same() {
+    let path = temporary_directory("save-round-trip");
+    let world = sample_world();
+    world.save(path.clone()).unwrap();
+
+    let loaded = World::from_file(path.clone()).unwrap();
+    assert!(loaded
+        .chunk_problems()
+        .iter()
+        .all(|problem| matches!(problem, LocationProblem::Missing(_))));
+    assert_same_world(&world, &loaded);
+    assert_eq!(
+        loaded
+            .get_block(60, 64, 120)
+            .map(|block| (block.id, block.metadata)),
+        Some((BlockID::Wool, 14))
+    );
+
+    for name in ["chunks.dat", "level.dat", "entities.dat"] {
+        assert!(path.join(name).is_file());
+        assert!(!path.join(format!("{}.tmp", name)).exists());
+    }
+
+    fs::remove_dir_all(path).unwrap();
+}
+
+#[test]
+fn saving_over_the_loaded_files_keeps_unread_chunks() {
+    let path = temporary_directory("save-over");
+    let world = sample_world();
+    world.save(path.clone()).unwrap();
+
+    // Nothing was read from chunks.dat yet when it gets replaced
+    let mut loaded = World::from_file(path.clone()).unwrap();
+    loaded.set_block(2, 2, 3, Block::new(BlockID::Dirt));
+    loaded.save(path.clone()).unwrap();
+
+    let reloaded = World::from_file(path.clone()).unwrap();
+    assert_same_world(&loaded, &reloaded);
+    assert_eq!(
+        reloaded.get_block(255, 127, 255).map(|block| block.id),
+        Some(BlockID::Glass)
+    );
+
+    fs::remove_dir_all(path).unwrap();
+}
+
+#[test]
+fn entities_with_padding_after_the_nbt_load() {
+    let path = temporary_directory("save-padding");
+    let world = sample_world();
+    world.save(path.clone()).unwrap();
+
+    let mut entities = fs::read(path.join("entities.dat")).unwrap();
+    entities.extend([0; 16]);
+    fs::write(path.join("entities.dat"), &entities).unwrap();
+    assert_same_world(&world, &World::from_file(path.clone()).unwrap());
+
+    // The header still has to be there
+    fs::write(path.join("entities.dat"), &entities[12..]).unwrap();
+    assert!(World::from_file(path.clone()).is_err());
+
+    fs::remove_dir_all(path).unwrap();
+}
+
+/// Byte range of a chunk's record, from the location table of a `chunks.dat`.
+fn record_range(chunks: &[u8], position: ChunkPos) -> std::ops::Range<usize> {
+    let index = (position.x + position.z * 32) as usize * 4;
+    let entry = u32::from_le_bytes(chunks[index..index + 4].try_into().unwrap());
+    let start = (entry >> 8) as usize * 4096;
+    start..start + world::CHUNK_RECORD_SIZE
+}
+
+#[test]
+fn unreadable_chunks_are_saved_unchanged() {
+    let path = temporary_directory("save-unreadable");
+    let copy = temporary_directory("save-unreadable-copy");
+    sample_world().save(path.clone()).unwrap();
+
+    // Break the header of one record
+    let broken = ChunkPos::new(3, 7);
+    let mut chunks = fs::read(path.join("chunks.dat")).unwrap();
+    let range = record_range(&chunks, broken);
+    chunks[range.start] ^= 0xFF;
+    fs::write(path.join("chunks.dat"), &chunks).unwrap();
+
+    let mut loaded = World::from_file(path.clone()).unwrap();
+    assert!(loaded.try_chunk_at(broken.x, broken.z).is_err());
+    assert!(loaded.chunk_at_mut(broken.x, broken.z).is_none());
+    loaded.save(copy.clone()).unwrap();
+
+    let copied = fs::read(copy.join("chunks.dat")).unwrap();
+    assert_eq!(
+        copied[record_range(&copied, broken)],
+        chunks[record_range(&chunks, broken)]
+    );
+
+    // Inserting a chunk replaces the unreadable one
+    let mut reloaded = World::from_file(copy.clone()).unwrap();
+    reloaded.insert_chunk(broken, Chunk::new());
+    reloaded.save(copy.clone()).unwrap();
+    let reloaded = World::from_file(copy.clone()).unwrap();
+    assert!(reloaded.try_chunk_at(broken.x, broken.z).unwrap().is_some());
+
+    fs::remove_dir_all(path).unwrap();
+    fs::remove_dir_all(copy).unwrap();
+}
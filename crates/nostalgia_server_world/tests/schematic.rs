@@ -0,0 +1,85 @@
+mod common;
+
+use nbt::{BigEndian, Compression, Nbt, Tag};
+use world::{
+    Block, BlockID, Chunk, ChunkPos, LevelData, PasteOptions, Schematic, World, WORLD_SIZE_CHUNKS,
+};
+
+fn schematic_bytes(tags: &[(&str, Tag)]) -> Vec<u8> {
+    Nbt::named("Schematic", common::tags(tags))
+        .to_compressed::<BigEndian>(Compression::Gzip)
+        .unwrap()
+}
+
+fn world_with_chunks() -> World {
+    let mut world = World::new(LevelData::default());
+    for x in 0..WORLD_SIZE_CHUNKS {
+        for z in 0..WORLD_SIZE_CHUNKS {
+            world.insert_chunk(ChunkPos::new(x, z), Chunk::new());
+        }
+    }
+
+    world
+}
+
+#[test]
+fn exported_schematic_pastes_back_the_same_blocks() {
+    let mut world = world_with_chunks();
+    world.set_block(10, 64, 10, Block::new(BlockID::Stone));
+    world.set_block(11, 65, 12, Block::existing(BlockID::Wool, 0, 0, 3));
+
+    let schematic = world.export_schematic((10, 64, 10), (11, 65, 12)).unwrap();
+    assert_eq!(
+        (schematic.width, schematic.height, schematic.length),
+        (2, 2, 3)
+    );
+
+    let schematic = Schematic::from_bytes(schematic.to_bytes().unwrap()).unwrap();
+    let changed = world.paste_schematic(&schematic, (100, 64, 100), &PasteOptions::default());
+    assert_eq!(changed.len(), 2);
+    assert_eq!(
+        world.get_block(100, 64, 100).map(|block| block.id),
+        Some(BlockID::Stone)
+    );
+    assert_eq!(
+        world
+            .get_block(101, 65, 102)
+            .map(|block| (block.id, block.metadata)),
+        Some((BlockID::Wool, 3))
+    );
+}
+
+#[test]
+fn sizes_too_large_for_a_short_are_rejected() {
+    let world = world_with_chunks();
+    assert!(world
+        .export_schematic((0, 0, 0), (i16::MAX as i32, 0, 0))
+        .is_err());
+    assert!(world
+        .export_schematic((i32::MIN, 0, 0), (i32::MAX, 0, 0))
+        .is_err());
+
+    let schematic = Schematic::new(i16::MAX as u16 + 1, 1, 1);
+    assert!(schematic.to_bytes().is_err());
+}
+
+#[test]
+fn block_count_is_checked_before_allocating() {
+    // Would need terabytes if the sizes were trusted
+    let bytes = schematic_bytes(&[
+        ("Width", Tag::Short(i16::MAX)),
+        ("Height", Tag::Short(i16::MAX)),
+        ("Length", Tag::Short(i16::MAX)),
+        ("Blocks", Tag::ByteArray(vec![1; 8])),
+    ]);
+    assert!(Schematic::from_bytes(bytes).is_err());
+
+    let bytes = schematic_bytes(&[
+        ("Width", Tag::Short(2)),
+        ("Height", Tag::Short(2)),
+        ("Length", Tag::Short(2)),
+        ("Blocks", Tag::ByteArray(vec![1; 8])),
+        ("Data", Tag::ByteArray(vec![0; 4])),
+    ]);
+    assert!(Schematic::from_bytes(bytes).is_err());
+}
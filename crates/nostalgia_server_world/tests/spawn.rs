@@ -0,0 +1,58 @@
+use world::{Block, BlockID, Chunk, ChunkPos, LevelData, World};
+
+fn world_with_floor() -> World {
+    let mut level = LevelData::default();
+    level.set_spawn_position((8, 64, 8));
+
+    let mut chunk = Chunk::new();
+    for x in 0..16 {
+        for z in 0..16 {
+            for y in 0..4 {
+                chunk.set_block_id(x, y, z, BlockID::Stone);
+            }
+        }
+    }
+
+    let mut world = World::new(level);
+    world.insert_chunk(ChunkPos::new(0, 0), chunk);
+    world
+}
+
+#[test]
+fn spawns_on_the_highest_solid_floor() {
+    let world = world_with_floor();
+    assert_eq!(world.find_safe_spawn(), Some((8, 4, 8)));
+}
+
+#[test]
+fn never_spawns_on_leaves() {
+    let mut world = world_with_floor();
+    let chunk = world.chunk_at_mut(0, 0).unwrap();
+    for y in 4..10 {
+        chunk.set_block_id(8, y, 8, BlockID::Wood);
+    }
+    for x in 6..=10 {
+        for z in 6..=10 {
+            chunk.set_block_id(x, 10, z, BlockID::Leaves);
+        }
+    }
+
+    // On the ground under the canopy, next to the trunk
+    assert_eq!(world.find_safe_spawn(), Some((7, 4, 7)));
+}
+
+#[test]
+fn never_spawns_on_cactus() {
+    let mut world = world_with_floor();
+    for y in 4..7 {
+        world.set_block(8, y, 8, Block::new(BlockID::Cactus));
+    }
+    // A pillar hanging over it up to the top of the world, so the only open spot in
+    // the column is right on top of the cactus
+    for y in 9..128 {
+        world.set_block(8, y, 8, Block::new(BlockID::Stone));
+    }
+
+    // Beside the cactus rather than on top of it
+    assert_eq!(world.find_safe_spawn(), Some((7, 4, 7)));
+}
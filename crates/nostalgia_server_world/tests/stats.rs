@@ -0,0 +1,31 @@
+use world::{Block, BlockID, Chunk, ChunkPos, LevelData, World, WorldStats, ORES};
+
+#[test]
+fn glowing_redstone_counts_as_redstone_ore_only_by_height() {
+    let mut world = World::new(LevelData::default());
+    world.insert_chunk(ChunkPos::new(0, 0), Chunk::new());
+    world.set_block(1, 10, 1, Block::new(BlockID::RedstoneOre));
+    world.set_block(2, 10, 1, Block::new(BlockID::GlowingRedstoneOre));
+    world.set_block(3, 30, 1, Block::new(BlockID::CoalOre));
+
+    let stats = WorldStats::collect(&world);
+    assert_eq!(stats.chunks, 1);
+    assert_eq!(stats.blocks[BlockID::RedstoneOre.0 as usize], 1);
+    assert_eq!(stats.blocks[BlockID::GlowingRedstoneOre.0 as usize], 1);
+
+    let redstone = ORES
+        .iter()
+        .position(|&ore| ore == BlockID::RedstoneOre)
+        .unwrap();
+    let coal = ORES
+        .iter()
+        .position(|&ore| ore == BlockID::CoalOre)
+        .unwrap();
+    assert_eq!(stats.ores[redstone][1], 2);
+    assert_eq!(stats.ores[coal][3], 1);
+    assert_eq!(stats.ores.iter().flatten().sum::<usize>(), 3);
+
+    let histogram = stats.block_histogram();
+    assert_eq!(histogram[0].0, BlockID::Air);
+    assert!(histogram.contains(&(BlockID::GlowingRedstoneOre, 1)));
+}
@@ -1,6 +1,6 @@
 #!/usr/bin/env python3
 
-import os, re
+import os, re, subprocess, sys
 from dataclasses import dataclass
 
 def to_constant_case(name):
@@ -199,6 +199,7 @@ MINECRAFT_PACKETS = [
     Packet(name="UseItem", id=163, fields=[
         Field(name="x", type="i32"),
         Field(name="y", type="i32"),
+        Field(name="z", type="i32"),
         Field(name="face", type="i32"),
         Field(name="block", type="u16"),
         Field(name="meta", type="u8"),
@@ -302,52 +303,66 @@ MINECRAFT_PACKETS = [
 
 BASE_PATH = "crates/nostalgia_server_protocol/src/packets/"
 
-with open(f"{BASE_PATH}mod.rs", "w") as file:
-    for packet in MINECRAFT_PACKETS:
-        file.write(f"pub mod {packet.as_identifier()};\n")
-    file.write("\n")
+# Packets named on the command line are regenerated alone, leaving mod.rs and the
+# hand-maintained packets untouched.
+SELECTED = set(sys.argv[1:])
+unknown = SELECTED - {packet.name for packet in MINECRAFT_PACKETS}
+if unknown:
+    sys.exit(f"Unknown packets: {', '.join(sorted(unknown))}")
+
+generated = []
 
-    for packet in MINECRAFT_PACKETS:
-        file.write(f"pub use {packet.as_identifier()}::*;\n")
-    file.write("\n")
+if not SELECTED:
+    generated.append(f"{BASE_PATH}mod.rs")
+    with open(f"{BASE_PATH}mod.rs", "w") as file:
+        for packet in MINECRAFT_PACKETS:
+            file.write(f"pub mod {packet.as_identifier()};\n")
+        file.write("\n")
 
-    file.write("use std::io::{Cursor, Result};\n")
-    file.write("use crate::reader;\n\n")
+        for packet in MINECRAFT_PACKETS:
+            file.write(f"pub use {packet.as_identifier()}::*;\n")
+        file.write("\n")
 
-    file.write("#[derive(Clone, Debug)]\n")
-    file.write("pub enum Packet {\n")
-    for packet in MINECRAFT_PACKETS:
-        file.write(f"    {packet.name}({packet.name}),\n")
-    file.write("}\n\n")
+        file.write("use std::io::{Cursor, Result};\n")
+        file.write("use crate::reader;\n\n")
 
-    file.write("impl Packet {\n")
-    file.write("    pub fn parse(mut cursor: &mut Cursor<Vec<u8>>) -> Result<Option<Self>> {\n")
-    file.write("        match reader::read_u8(&mut cursor)? {\n")
-    for packet in MINECRAFT_PACKETS:
-        file.write(f"            0x{packet.id:2X} => Ok(Some(Packet::{packet.name}({packet.name}::parse(&mut cursor)?))),\n")
-    file.write("            _ => Ok(None),\n")
-    file.write("        }\n")
-    file.write("    }\n\n")
-    file.write("    pub fn serialize(&self, mut cursor: &mut Cursor<Vec<u8>>) -> Result<()> {\n")
-    file.write("        match self {\n")
-    for packet in MINECRAFT_PACKETS:
-        file.write(f"            Packet::{packet.name}(packet) => packet.serialize(&mut cursor),\n")
-    file.write("        }\n")
-    file.write("    }\n")
-    file.write("}\n")
+        file.write("#[derive(Clone, Debug)]\n")
+        file.write("pub enum Packet {\n")
+        for packet in MINECRAFT_PACKETS:
+            file.write(f"    {packet.name}({packet.name}),\n")
+        file.write("}\n\n")
 
-    file.write("\n")
+        file.write("impl Packet {\n")
+        file.write("    pub fn parse(mut cursor: &mut Cursor<Vec<u8>>) -> Result<Option<Self>> {\n")
+        file.write("        match reader::read_u8(&mut cursor)? {\n")
+        for packet in MINECRAFT_PACKETS:
+            file.write(f"            0x{packet.id:2X} => Ok(Some(Packet::{packet.name}({packet.name}::parse(&mut cursor)?))),\n")
+        file.write("            _ => Ok(None),\n")
+        file.write("        }\n")
+        file.write("    }\n\n")
+        file.write("    pub fn serialize(&self, mut cursor: &mut Cursor<Vec<u8>>) -> Result<()> {\n")
+        file.write("        match self {\n")
+        for packet in MINECRAFT_PACKETS:
+            file.write(f"            Packet::{packet.name}(packet) => packet.serialize(&mut cursor),\n")
+        file.write("        }\n")
+        file.write("    }\n")
+        file.write("}\n")
 
-    for packet in MINECRAFT_PACKETS:
-        file.write(f"impl From<{packet.name}> for Packet {{\n")
-        file.write(f"    fn from(packet: {packet.name}) -> Self {{\n")
-        file.write(f"        Packet::{packet.name}(packet)\n")
-        file.write(f"    }}\n")
-        file.write(f"}}\n")
         file.write("\n")
 
+        for packet in MINECRAFT_PACKETS:
+            file.write(f"impl From<{packet.name}> for Packet {{\n")
+            file.write(f"    fn from(packet: {packet.name}) -> Self {{\n")
+            file.write(f"        Packet::{packet.name}(packet)\n")
+            file.write(f"    }}\n")
+            file.write(f"}}\n")
+            file.write("\n")
+
 
 for packet in MINECRAFT_PACKETS:
+    if SELECTED and packet.name not in SELECTED:
+        continue
+
     has_vector3 = False
     for field in packet.fields:
         if field.type == "Vector3":
@@ -356,6 +371,7 @@ for packet in MINECRAFT_PACKETS:
 
     implementation = f"{BASE_PATH}{to_identifier_case(packet.name)}.rs"
     implementation_file = open(implementation, "w")
+    generated.append(implementation)
 
     implementation_file.write(f"use crate::{{reader, writer}};\n")
     implementation_file.write(f"use std::io::{{Cursor, Result}};\n")
@@ -386,3 +402,6 @@ for packet in MINECRAFT_PACKETS:
     implementation_file.write(f"    }}\n")
 
     implementation_file.write(f"}}\n\n")
+    implementation_file.close()
+
+subprocess.run(["rustfmt", "--edition", "2021", *generated], check=True)